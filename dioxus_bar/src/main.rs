@@ -367,6 +367,13 @@ fn App() -> Element {
 
     // 按钮状态数组
     let mut button_states = use_signal(|| vec![ButtonStateData::default(); BUTTONS.len()]);
+    // 标签文字：收到 jwm 消息前使用内置图标
+    let mut tag_labels = use_signal(|| {
+        BUTTONS
+            .iter()
+            .map(|label| label.to_string())
+            .collect::<Vec<String>>()
+    });
     let mut last_update = use_signal(|| Instant::now());
     let mut show_seconds = use_signal(|| true);
     let mut system_snapshot = use_signal(|| None::<SystemSnapshot>);
@@ -431,14 +438,18 @@ fn App() -> Element {
         spawn(async move {
             // 异步等待消息，无需轮询
             while let Some(shared_message) = message_receiver.recv().await {
-                let mut new_states = vec![ButtonStateData::default(); BUTTONS.len()];
                 let monitor_info = shared_message.monitor_info;
+                let labels = monitor_info.get_tag_labels();
+                let mut new_states = vec![ButtonStateData::default(); labels.len()];
+                if *tag_labels.read() != labels {
+                    tag_labels.set(labels);
+                }
 
                 layout_symbol.set(monitor_info.get_ltsymbol());
                 monitor_num.set(Some(monitor_info.monitor_num));
 
                 // 更新按钮状态
-                for (index, tag_status) in monitor_info.tag_status_vec.iter().enumerate() {
+                for (index, tag_status) in monitor_info.tag_statuses().iter().enumerate() {
                     if index < new_states.len() {
                         new_states[index] = ButtonStateData {
                             is_filtered: tag_status.is_filled,
@@ -502,7 +513,7 @@ fn App() -> Element {
 
             div { class: "buttons-container",
                 // 工作区按钮（Tag）
-                for (i, emoji) in BUTTONS.iter().enumerate() {
+                for (i, emoji) in tag_labels().into_iter().enumerate() {
                     {
                         let base_class = get_button_class(i, &button_states());
                         let is_pressed = pressed_button() == Some(i);
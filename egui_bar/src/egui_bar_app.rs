@@ -674,12 +674,19 @@ impl EguiBarApp {
             .map(|m| m.monitor_info)
             .unwrap_or_default();
 
+        // Use the labels published by jwm, or the built-in icons before the first message
+        let tag_labels: Vec<String> = if self.state.current_message.is_some() {
+            monitor_info.get_tag_labels()
+        } else {
+            icons::TAG_ICONS.iter().map(|icon| icon.to_string()).collect()
+        };
+
         // Draw tag icons as buttons
-        for (index, &tag_icon) in icons::TAG_ICONS.iter().enumerate() {
-            let tag_color = colors::TAG_COLORS[index];
+        for (index, tag_icon) in tag_labels.iter().enumerate() {
+            let tag_color = colors::TAG_COLORS[index % colors::TAG_COLORS.len()];
             let tag_bit = 1 << index;
 
-            let rich_text = egui::RichText::new(tag_icon).monospace();
+            let rich_text = egui::RichText::new(tag_icon.as_str()).monospace();
 
             let mut is_urg = false;
             let mut is_filled = false;
@@ -687,7 +694,7 @@ impl EguiBarApp {
             let mut tooltip = format!("Tag {}", index + 1);
             let mut button_bg_color = Color32::TRANSPARENT;
 
-            if let Some(tag_status) = monitor_info.tag_status_vec.get(index) {
+            if let Some(tag_status) = monitor_info.tag_statuses().get(index) {
                 if tag_status.is_urg {
                     tooltip.push_str(" (urgent)");
                     is_urg = true;
@@ -117,7 +117,8 @@ struct TabBarApp {
     // GTK widgets
     builder: Builder,
     window: ApplicationWindow,
-    tab_buttons: Vec<Button>,
+    tab_box: gtk4::Box,
+    tab_buttons: RefCell<Vec<Button>>,
     time_button: Button,
     monitor_label: Label,
     memory_label: Label,
@@ -150,15 +151,10 @@ impl TabBarApp {
             .expect("Failed to get main_window from builder");
         window.set_application(Some(app));
 
-        // 标签按钮
-        let mut tab_buttons = Vec::new();
-        for i in 0..9 {
-            let button_id = format!("tab_button_{}", i);
-            let button: Button = builder
-                .object(&button_id)
-                .expect(&format!("Failed to get {} from builder", button_id));
-            tab_buttons.push(button);
-        }
+        // 标签按钮容器，按钮随 jwm 上报的 tag 数量创建
+        let tab_box: gtk4::Box = builder
+            .object("tab_box")
+            .expect("Failed to get tab_box from builder");
 
         // 其他组件
         let time_button: Button = builder
@@ -209,7 +205,8 @@ impl TabBarApp {
         let app_instance = Rc::new(Self {
             builder,
             window,
-            tab_buttons,
+            tab_box,
+            tab_buttons: RefCell::new(Vec::new()),
             time_button,
             monitor_label,
             memory_label,
@@ -235,7 +232,7 @@ impl TabBarApp {
                 while let Ok(event) = ui_receiver.recv().await {
                     match event {
                         AppEvent::SharedMessage(message) => {
-                            app_clone.on_shared_message(message);
+                            Self::on_shared_message(&app_clone, message);
                         }
                     }
                 }
@@ -315,16 +312,6 @@ impl TabBarApp {
     }
 
     fn setup_event_handlers(app: Rc<Self>) {
-        // 标签按钮点击
-        for (i, button) in app.tab_buttons.iter().enumerate() {
-            button.connect_clicked({
-                let app = app.clone();
-                move |_| {
-                    Self::handle_tab_selected(app.clone(), i);
-                }
-            });
-        }
-
         // 布局开关
         app.layout_toggle.connect_clicked({
             let app = app.clone();
@@ -376,8 +363,9 @@ impl TabBarApp {
     }
 
     // ========= Worker事件处理 =========
-    fn on_shared_message(&self, message: SharedMessage) {
-        if let Ok(mut st) = self.state.try_borrow_mut() {
+    fn on_shared_message(app: &Rc<Self>, message: SharedMessage) {
+        Self::sync_tab_buttons(app, message.monitor_info.tag_count());
+        if let Ok(mut st) = app.state.try_borrow_mut() {
             let ts: u128 = message.timestamp.into();
             if st.last_message_ts == ts {
                 return; // 去重
@@ -386,10 +374,10 @@ impl TabBarApp {
 
             st.layout_symbol = message.monitor_info.get_ltsymbol();
             st.monitor_num = message.monitor_info.monitor_num as u8;
-            st.tag_status_vec = message.monitor_info.tag_status_vec.to_vec();
+            st.tag_status_vec = message.monitor_info.tag_statuses().to_vec();
 
             // 更新活动标签
-            for (idx, tag) in message.monitor_info.tag_status_vec.iter().enumerate() {
+            for (idx, tag) in message.monitor_info.tag_statuses().iter().enumerate() {
                 if tag.is_selected {
                     st.active_tab = idx;
                     break;
@@ -397,13 +385,46 @@ impl TabBarApp {
             }
 
             // 确保掩码数组长度匹配
-            if st.last_class_masks.len() != self.tab_buttons.len() {
-                st.last_class_masks = vec![0u8; self.tab_buttons.len()];
+            let tab_count = app.tab_buttons.borrow().len();
+            if st.last_class_masks.len() != tab_count {
+                st.last_class_masks = vec![0u8; tab_count];
             }
         }
+        app.update_tab_labels(&message.monitor_info.get_tag_labels());
         // 更新 UI（差量）
-        self.update_ui();
-        self.update_layout_ui();
+        app.update_ui();
+        app.update_layout_ui();
+    }
+
+    // tag 数量变化时增删标签按钮，已有按钮保持不变
+    fn sync_tab_buttons(app: &Rc<Self>, count: usize) {
+        let mut buttons = app.tab_buttons.borrow_mut();
+        if buttons.len() == count {
+            return;
+        }
+        while buttons.len() > count {
+            if let Some(button) = buttons.pop() {
+                app.tab_box.remove(&button);
+            }
+        }
+        while buttons.len() < count {
+            let index = buttons.len();
+            let button = Button::new();
+            button.set_size_request(40, 32);
+            button.add_css_class("tab-button");
+            button.connect_clicked({
+                let app = app.clone();
+                move |_| {
+                    Self::handle_tab_selected(app.clone(), index);
+                }
+            });
+            app.tab_box.append(&button);
+            buttons.push(button);
+        }
+        // 新按钮需要重新应用样式
+        if let Ok(mut st) = app.state.try_borrow_mut() {
+            st.last_class_masks = vec![0u8; count];
+        }
     }
 
     // ========= 交互 =========
@@ -460,13 +481,23 @@ impl TabBarApp {
         self.update_tab_styles();
     }
 
+    // 标签文字来自 jwm 配置
+    fn update_tab_labels(&self, labels: &[String]) {
+        for (button, label) in self.tab_buttons.borrow().iter().zip(labels) {
+            if button.label().as_deref() != Some(label.as_str()) {
+                button.set_label(label);
+            }
+        }
+    }
+
     fn update_tab_styles(&self) {
         if let Ok(mut st) = self.state.try_borrow_mut() {
-            if st.last_class_masks.len() != self.tab_buttons.len() {
-                st.last_class_masks = vec![0u8; self.tab_buttons.len()];
+            let tab_buttons = self.tab_buttons.borrow();
+            if st.last_class_masks.len() != tab_buttons.len() {
+                st.last_class_masks = vec![0u8; tab_buttons.len()];
             }
 
-            for (i, button) in self.tab_buttons.iter().enumerate() {
+            for (i, button) in tab_buttons.iter().enumerate() {
                 let tag_opt = st.tag_status_vec.get(i);
                 let desired_mask = Self::classes_mask_for(tag_opt, i == st.active_tab);
                 let prev_mask = st.last_class_masks[i];
@@ -92,7 +92,8 @@ enum Message {
 
 struct IcedBar {
     active_tab: usize,
-    tabs: [String; 9],
+    // 初始为内置图标，收到 jwm 消息后替换为其发布的标签
+    tabs: Vec<String>,
     tab_colors: [Color; 9],
     shared_buffer_rc: Option<Arc<SharedRingBuffer>>,
     shared_path: String,
@@ -141,7 +142,7 @@ impl IcedBar {
 
         Self {
             active_tab: 0,
-            tabs: [
+            tabs: vec![
                 "🏠".to_string(),
                 "💻".to_string(),
                 "🌐".to_string(),
@@ -379,7 +380,11 @@ impl IcedBar {
                 if let Some(monitor_info) = self.monitor_info_opt.as_ref() {
                     self.layout_symbol = monitor_info.get_ltsymbol();
                     self.monitor_num = monitor_info.monitor_num;
-                    for (index, tag_status) in monitor_info.tag_status_vec.iter().enumerate() {
+                    let labels = monitor_info.get_tag_labels();
+                    if !labels.is_empty() {
+                        self.tabs = labels;
+                    }
+                    for (index, tag_status) in monitor_info.tag_statuses().iter().enumerate() {
                         if tag_status.is_selected {
                             self.active_tab = index;
                         }
@@ -443,7 +448,7 @@ impl IcedBar {
             .unwrap_or(Self::DEFAULT_COLOR);
 
         if let Some(monitor) = self.monitor_info_opt.as_ref() {
-            if let Some(status) = monitor.tag_status_vec.get(index) {
+            if let Some(status) = monitor.tag_statuses().get(index) {
                 if status.is_urg {
                     // urgent: red bg + bold violet border
                     return (
@@ -11,13 +11,16 @@ pub struct ScreenInfo {
     pub height: i32,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct OutputInfo {
     pub id: i32,
     pub x: i32,
     pub y: i32,
     pub width: i32,
     pub height: i32,
+    // 输出名（如 "HDMI-1"）与 EDID 摘要，插拔后据此识别同一台显示器
+    pub name: String,
+    pub edid: Option<u64>,
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -27,9 +30,17 @@ pub struct Capabilities {
     pub supports_client_list: bool,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NetWmState {
-    Fullscreen, /* 后续可扩充 */
+    Fullscreen,
+    Sticky,
+    Above,
+    Below,
+    MaximizedVert,
+    MaximizedHorz,
+    Hidden,
+    DemandsAttention,
+    SkipTaskbar,
 }
 #[derive(Debug, Clone, Copy)]
 pub enum NetWmAction {
@@ -38,6 +49,15 @@ pub enum NetWmAction {
     Toggle,
 }
 
+// _NET_WM_MOVERESIZE 请求的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveResizeAction {
+    Move,
+    // 拖动的边：-1 为左/上，1 为右/下，0 表示该方向不变
+    Resize { horizontal: i8, vertical: i8 },
+    Cancel,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum PropertyKind {
     WmTransientFor,
@@ -46,6 +66,7 @@ pub enum PropertyKind {
     WmName,
     NetWmName,
     NetWmWindowType,
+    Strut, // _NET_WM_STRUT 或 _NET_WM_STRUT_PARTIAL
     Other, // 后端无法识别
 }
 
@@ -59,6 +80,38 @@ pub enum BackendEvent {
     ActiveWindowMessage {
         window: WindowId,
     },
+    // 分页器请求切换桌面（_NET_CURRENT_DESKTOP）
+    CurrentDesktopMessage {
+        desktop: u32,
+    },
+    // 请求把窗口移到某个桌面（_NET_WM_DESKTOP）
+    WmDesktopMessage {
+        window: WindowId,
+        desktop: u32,
+    },
+    // 客户端自绘标题栏发起的拖动（_NET_WM_MOVERESIZE）
+    MoveResizeMessage {
+        window: WindowId,
+        action: MoveResizeAction,
+    },
+    // 直接移动/调整窗口（_NET_MOVERESIZE_WINDOW），None 表示该项不变
+    MoveResizeWindowMessage {
+        window: WindowId,
+        x: Option<i32>,
+        y: Option<i32>,
+        w: Option<u32>,
+        h: Option<u32>,
+    },
+    // 分页器/任务栏请求关闭窗口（_NET_CLOSE_WINDOW）
+    CloseWindowMessage {
+        window: WindowId,
+    },
+    // 请求调整层叠顺序（_NET_RESTACK_WINDOW），above 为 false 时压到下方
+    RestackWindowMessage {
+        window: WindowId,
+        sibling: Option<WindowId>,
+        above: bool,
+    },
     PropertyChanged {
         window: WindowId,
         kind: PropertyKind,
@@ -72,6 +125,8 @@ pub enum BackendEvent {
     },
     ButtonRelease {
         window: WindowId,
+        state: u16,
+        detail: u8,
         time: u32,
     },
     MotionNotify {
@@ -84,9 +139,15 @@ pub enum BackendEvent {
         keycode: u8,
         state: u16,
     },
+    KeyRelease {
+        keycode: u8,
+        state: u16,
+    },
     MappingNotify {
         request: u8, // 与 X11 Mapping 枚举值一致
     },
+    // 显示器插拔、分辨率或排列变化（RandR），短时间内可能连续到达多次
+    OutputsChanged,
     ClientMessage {
         window: WindowId,
         type_: u32,
@@ -206,6 +267,10 @@ pub trait InputOps: Send {
     ) -> Result<bool, Box<dyn std::error::Error>>;
     fn ungrab_pointer(&self) -> Result<(), Box<dyn std::error::Error>>;
 
+    // 主动抓取整个键盘（按键序列进行中），返回是否成功
+    fn grab_keyboard(&self) -> Result<bool, Box<dyn std::error::Error>>;
+    fn ungrab_keyboard(&self) -> Result<(), Box<dyn std::error::Error>>;
+
     fn allow_events(&self, mode: AllowMode, time: u32) -> Result<(), Box<dyn std::error::Error>>;
 
     fn query_pointer_root(&self) -> Result<(i32, i32, u16, u16), Box<dyn std::error::Error>>;
@@ -229,6 +294,10 @@ pub trait InputOps: Send {
 pub trait OutputOps: Send {
     fn screen_info(&self) -> ScreenInfo;
     fn enumerate_outputs(&self) -> Vec<OutputInfo>;
+    // 订阅输出变化，之后以 BackendEvent::OutputsChanged 通知；不支持热插拔的后端保持默认
+    fn watch_output_changes(&self) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
 }
 
 // 事件源（供 JWM 主循环消费）
@@ -368,6 +437,22 @@ pub struct WmHints {
     pub urgent: bool,
     pub input: Option<bool>, // None 表示未提供 InputHint
 }
+// _NET_WM_STRUT_PARTIAL：各边预留的宽度（相对根窗口边缘）及其覆盖范围（闭区间）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Strut {
+    pub left: u32,
+    pub right: u32,
+    pub top: u32,
+    pub bottom: u32,
+    pub left_start_y: u32,
+    pub left_end_y: u32,
+    pub right_start_y: u32,
+    pub right_end_y: u32,
+    pub top_start_x: u32,
+    pub top_end_x: u32,
+    pub bottom_start_x: u32,
+    pub bottom_end_x: u32,
+}
 
 // 属性接口
 pub trait PropertyOps: Send {
@@ -379,6 +464,8 @@ pub trait PropertyOps: Send {
         end_x: u32,
     ) -> Result<(), Box<dyn std::error::Error>>;
     fn clear_window_strut(&self, win: WindowId) -> Result<(), Box<dyn std::error::Error>>;
+    // 优先读取 _NET_WM_STRUT_PARTIAL，只有 _NET_WM_STRUT 时覆盖整条边
+    fn get_window_strut(&self, win: WindowId) -> Option<Strut>;
     fn get_text_property_best_title(&self, win: WindowId) -> String;
     fn get_wm_class(&self, win: WindowId) -> Option<(String, String)>;
 
@@ -390,6 +477,8 @@ pub trait PropertyOps: Send {
         win: WindowId,
         on: bool,
     ) -> Result<(), Box<dyn std::error::Error>>;
+    fn is_sticky(&self, win: WindowId) -> Result<bool, Box<dyn std::error::Error>>;
+    fn set_sticky_state(&self, win: WindowId, on: bool) -> Result<(), Box<dyn std::error::Error>>;
 
     // 语义化：ICCCM WM_HINTS
     fn get_wm_hints(&self, win: WindowId) -> Option<WmHints>;
@@ -402,6 +491,9 @@ pub trait PropertyOps: Send {
     // 语义化：WM_TRANSIENT_FOR
     fn transient_for(&self, win: WindowId) -> Option<WindowId>;
 
+    // 语义化：_NET_WM_PID
+    fn get_window_pid(&self, win: WindowId) -> Option<u32>;
+
     // 语义化：WM_NORMAL_HINTS（WmSizeHints）
     fn fetch_normal_hints(
         &self,
@@ -420,6 +512,14 @@ pub trait PropertyOps: Send {
         monitor_num: u32,
     ) -> Result<(), Box<dyn std::error::Error>>;
 
+    // 语义化：读取/整体写回 _NET_WM_STATE，写回时保留 WM 不认识的状态
+    fn get_net_wm_states(&self, win: WindowId) -> Vec<NetWmState>;
+    fn set_net_wm_states(
+        &self,
+        win: WindowId,
+        states: &[NetWmState],
+    ) -> Result<(), Box<dyn std::error::Error>>;
+
     fn get_net_wm_state_atoms(&self, win: WindowId)
         -> Result<Vec<u32>, Box<dyn std::error::Error>>;
     fn has_net_wm_state(
@@ -428,6 +528,16 @@ pub trait PropertyOps: Send {
         state_atom: u32,
     ) -> Result<bool, Box<dyn std::error::Error>>;
     fn get_window_types(&self, win: WindowId) -> Vec<u32>;
+    // 窗口类型的小写短名（如 "dialog"、"utility"），未知类型被忽略
+    fn get_window_type_names(&self, win: WindowId) -> Vec<String>;
+    // 语义化：WM_WINDOW_ROLE
+    fn get_window_role(&self, win: WindowId) -> Option<String>;
+    // 语义化：_NET_WM_WINDOW_OPACITY（交给合成器处理），None 表示移除
+    fn set_window_opacity(
+        &self,
+        win: WindowId,
+        opacity: Option<f32>,
+    ) -> Result<(), Box<dyn std::error::Error>>;
 
     // 新增：设置、添加、删除 _NET_WM_STATE
     fn set_net_wm_state_atoms(
@@ -461,12 +571,33 @@ pub enum EwmhFeature {
     WmState,
     SupportingWmCheck,
     WmStateFullscreen,
+    WmStateSticky,
+    WmStateAbove,
+    WmStateBelow,
+    WmStateMaximizedVert,
+    WmStateMaximizedHorz,
+    WmStateHidden,
+    WmStateDemandsAttention,
+    WmStateSkipTaskbar,
+    WmMoveresize,
+    MoveresizeWindow,
+    CloseWindow,
+    RestackWindow,
     ClientList,
     ClientInfo,
     WmWindowType,
     WmWindowTypeDialog,
+    DesktopNames,
+    NumberOfDesktops,
+    CurrentDesktop,
+    DesktopViewport,
+    Workarea,
+    WmDesktop,
 }
 
+/// _NET_WM_DESKTOP 中表示"所有桌面"的值
+pub const ALL_DESKTOPS: u32 = 0xFFFF_FFFF;
+
 // EWMH 门面（Wayland 可 no-op）
 pub trait EwmhFacade: Send {
     fn set_active_window(&self, win: WindowId) -> Result<(), Box<dyn std::error::Error>>;
@@ -484,6 +615,19 @@ pub trait EwmhFacade: Send {
 
     fn declare_supported(&self, features: &[EwmhFeature])
         -> Result<(), Box<dyn std::error::Error>>;
+    // _NET_DESKTOP_NAMES：每个 tag 的名称
+    fn set_desktop_names(&self, names: &[String]) -> Result<(), Box<dyn std::error::Error>>;
+    // _NET_NUMBER_OF_DESKTOPS，同时把每个桌面的 _NET_DESKTOP_VIEWPORT 设为原点
+    fn set_number_of_desktops(&self, count: u32) -> Result<(), Box<dyn std::error::Error>>;
+    fn set_current_desktop(&self, index: u32) -> Result<(), Box<dyn std::error::Error>>;
+    // _NET_WORKAREA：每个桌面一个 [x, y, w, h]
+    fn set_workarea(&self, areas: &[[u32; 4]]) -> Result<(), Box<dyn std::error::Error>>;
+    // _NET_WM_DESKTOP：窗口所在桌面，ALL_DESKTOPS 表示所有桌面
+    fn set_window_desktop(
+        &self,
+        win: WindowId,
+        desktop: u32,
+    ) -> Result<(), Box<dyn std::error::Error>>;
     // 可选：退出清理根属性
     fn reset_root_properties(&self) -> Result<(), Box<dyn std::error::Error>> {
         Ok(())
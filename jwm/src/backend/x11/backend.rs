@@ -76,7 +76,8 @@ impl X11Backend {
             conn.clone(),
             screen.default_colormap,
         ));
-        let event_source: Box<dyn EventSource> = Box::new(X11EventSource::new(conn.clone()));
+        let event_source: Box<dyn EventSource> =
+            Box::new(X11EventSource::new(conn.clone(), atoms.clone()));
 
         let caps = Capabilities {
             can_warp_pointer: true,
@@ -4,18 +4,42 @@ use x11rb::connection::Connection;
 use x11rb::protocol::xproto as x;
 use x11rb::protocol::Event as XEvent;
 
-use crate::backend::api::{BackendEvent, EventSource, WindowId};
+use crate::backend::api::{
+    BackendEvent, EventSource, MoveResizeAction, NetWmAction, PropertyKind, WindowId,
+};
+use crate::backend::x11::Atoms;
 
 pub struct X11EventSource<C: Connection> {
     conn: Arc<C>,
+    atoms: Atoms,
 }
 
 impl<C: Connection> X11EventSource<C> {
-    pub fn new(conn: Arc<C>) -> Self {
-        Self { conn }
+    pub fn new(conn: Arc<C>, atoms: Atoms) -> Self {
+        Self { conn, atoms }
     }
 
-    fn map_event(ev: XEvent) -> Option<BackendEvent> {
+    fn property_kind_from_atom(&self, atom: u32) -> Option<PropertyKind> {
+        if atom == u32::from(x::AtomEnum::WM_TRANSIENT_FOR) {
+            Some(PropertyKind::WmTransientFor)
+        } else if atom == u32::from(x::AtomEnum::WM_NORMAL_HINTS) {
+            Some(PropertyKind::WmNormalHints)
+        } else if atom == u32::from(x::AtomEnum::WM_HINTS) {
+            Some(PropertyKind::WmHints)
+        } else if atom == u32::from(x::AtomEnum::WM_NAME) {
+            Some(PropertyKind::WmName)
+        } else if atom == self.atoms._NET_WM_NAME {
+            Some(PropertyKind::NetWmName)
+        } else if atom == self.atoms._NET_WM_WINDOW_TYPE {
+            Some(PropertyKind::NetWmWindowType)
+        } else if atom == self.atoms._NET_WM_STRUT || atom == self.atoms._NET_WM_STRUT_PARTIAL {
+            Some(PropertyKind::Strut)
+        } else {
+            None
+        }
+    }
+
+    fn map_event(&self, ev: XEvent) -> Option<BackendEvent> {
         match ev {
             XEvent::ButtonPress(e) => Some(BackendEvent::ButtonPress {
                 window: WindowId(e.event as u64),
@@ -25,6 +49,8 @@ impl<C: Connection> X11EventSource<C> {
             }),
             XEvent::ButtonRelease(e) => Some(BackendEvent::ButtonRelease {
                 window: WindowId(e.event as u64),
+                state: e.state.bits(),
+                detail: e.detail,
                 time: e.time,
             }),
             XEvent::MotionNotify(e) => Some(BackendEvent::MotionNotify {
@@ -37,11 +63,99 @@ impl<C: Connection> X11EventSource<C> {
                 keycode: e.detail,
                 state: e.state.bits(),
             }),
+            XEvent::KeyRelease(e) => Some(BackendEvent::KeyRelease {
+                keycode: e.detail,
+                state: e.state.bits(),
+            }),
+            XEvent::RandrScreenChangeNotify(_) | XEvent::RandrNotify(_) => {
+                Some(BackendEvent::OutputsChanged)
+            }
             XEvent::MappingNotify(e) => Some(BackendEvent::MappingNotify {
                 request: u8::from(e.request),
             }),
             XEvent::ClientMessage(e) => {
                 let d = e.data.as_data32();
+                // EWMH 客户端请求转为语义事件
+                if e.type_ == self.atoms._NET_WM_STATE {
+                    let action = match d[0] {
+                        0 => NetWmAction::Remove,
+                        1 => NetWmAction::Add,
+                        _ => NetWmAction::Toggle,
+                    };
+                    return Some(BackendEvent::EwmhState {
+                        window: WindowId(e.window as u64),
+                        action,
+                        states: [
+                            self.atoms.net_wm_state_from_atom(d[1]),
+                            self.atoms.net_wm_state_from_atom(d[2]),
+                        ],
+                    });
+                }
+                if e.type_ == self.atoms._NET_ACTIVE_WINDOW {
+                    return Some(BackendEvent::ActiveWindowMessage {
+                        window: WindowId(e.window as u64),
+                    });
+                }
+                if e.type_ == self.atoms._NET_CURRENT_DESKTOP {
+                    return Some(BackendEvent::CurrentDesktopMessage { desktop: d[0] });
+                }
+                if e.type_ == self.atoms._NET_WM_MOVERESIZE {
+                    // 键盘方式（9、10）不支持
+                    let (horizontal, vertical) = match d[2] {
+                        0 => (-1, -1),
+                        1 => (0, -1),
+                        2 => (1, -1),
+                        3 => (1, 0),
+                        4 => (1, 1),
+                        5 => (0, 1),
+                        6 => (-1, 1),
+                        7 => (-1, 0),
+                        _ => (0, 0),
+                    };
+                    let action = match d[2] {
+                        0..=7 => MoveResizeAction::Resize {
+                            horizontal,
+                            vertical,
+                        },
+                        8 => MoveResizeAction::Move,
+                        11 => MoveResizeAction::Cancel,
+                        _ => return None,
+                    };
+                    return Some(BackendEvent::MoveResizeMessage {
+                        window: WindowId(e.window as u64),
+                        action,
+                    });
+                }
+                if e.type_ == self.atoms._NET_MOVERESIZE_WINDOW {
+                    // data[0] 的 8-11 位标明 x/y/width/height 是否有效
+                    let flags = d[0];
+                    return Some(BackendEvent::MoveResizeWindowMessage {
+                        window: WindowId(e.window as u64),
+                        x: (flags & (1 << 8) != 0).then_some(d[1] as i32),
+                        y: (flags & (1 << 9) != 0).then_some(d[2] as i32),
+                        w: (flags & (1 << 10) != 0).then_some(d[3]),
+                        h: (flags & (1 << 11) != 0).then_some(d[4]),
+                    });
+                }
+                if e.type_ == self.atoms._NET_CLOSE_WINDOW {
+                    return Some(BackendEvent::CloseWindowMessage {
+                        window: WindowId(e.window as u64),
+                    });
+                }
+                if e.type_ == self.atoms._NET_RESTACK_WINDOW {
+                    // detail：0 Above，1 Below，其余按 Above 处理
+                    return Some(BackendEvent::RestackWindowMessage {
+                        window: WindowId(e.window as u64),
+                        sibling: (d[1] != 0).then_some(WindowId(d[1] as u64)),
+                        above: d[2] != 1,
+                    });
+                }
+                if e.type_ == self.atoms._NET_WM_DESKTOP {
+                    return Some(BackendEvent::WmDesktopMessage {
+                        window: WindowId(e.window as u64),
+                        desktop: d[0],
+                    });
+                }
                 Some(BackendEvent::ClientMessage {
                     window: WindowId(e.window as u64),
                     type_: e.type_,
@@ -90,11 +204,21 @@ impl<C: Connection> X11EventSource<C> {
             XEvent::MapRequest(e) => Some(BackendEvent::MapRequest {
                 window: WindowId(e.window as u64),
             }),
-            XEvent::PropertyNotify(e) => Some(BackendEvent::PropertyNotify {
-                window: WindowId(e.window as u64),
-                atom: e.atom,
-                state: e.state.into(),
-            }),
+            XEvent::PropertyNotify(e) => {
+                // 已知的客户端属性转为语义事件，其余原样上报
+                if let Some(kind) = self.property_kind_from_atom(e.atom) {
+                    return Some(BackendEvent::PropertyChanged {
+                        window: WindowId(e.window as u64),
+                        kind,
+                        deleted: e.state == x::Property::DELETE,
+                    });
+                }
+                Some(BackendEvent::PropertyNotify {
+                    window: WindowId(e.window as u64),
+                    atom: e.atom,
+                    state: e.state.into(),
+                })
+            }
             XEvent::UnmapNotify(e) => Some(BackendEvent::UnmapNotify {
                 window: WindowId(e.window as u64),
                 from_configure: e.from_configure,
@@ -107,7 +231,7 @@ impl<C: Connection> X11EventSource<C> {
 impl<C: Connection + Send + Sync + 'static> EventSource for X11EventSource<C> {
     fn poll_event(&mut self) -> Result<Option<BackendEvent>, Box<dyn std::error::Error>> {
         let ev = self.conn.poll_for_event()?;
-        Ok(ev.and_then(Self::map_event))
+        Ok(ev.and_then(|ev| self.map_event(ev)))
     }
 
     fn flush(&mut self) -> Result<(), Box<dyn std::error::Error>> {
@@ -28,10 +28,28 @@ impl<C: Connection + Send + Sync + 'static> X11EwmhFacade<C> {
             EwmhFeature::WmState => self.atoms._NET_WM_STATE,
             EwmhFeature::SupportingWmCheck => self.atoms._NET_SUPPORTING_WM_CHECK,
             EwmhFeature::WmStateFullscreen => self.atoms._NET_WM_STATE_FULLSCREEN,
+            EwmhFeature::WmStateSticky => self.atoms._NET_WM_STATE_STICKY,
+            EwmhFeature::WmStateAbove => self.atoms._NET_WM_STATE_ABOVE,
+            EwmhFeature::WmStateBelow => self.atoms._NET_WM_STATE_BELOW,
+            EwmhFeature::WmStateMaximizedVert => self.atoms._NET_WM_STATE_MAXIMIZED_VERT,
+            EwmhFeature::WmStateMaximizedHorz => self.atoms._NET_WM_STATE_MAXIMIZED_HORZ,
+            EwmhFeature::WmStateHidden => self.atoms._NET_WM_STATE_HIDDEN,
+            EwmhFeature::WmStateDemandsAttention => self.atoms._NET_WM_STATE_DEMANDS_ATTENTION,
+            EwmhFeature::WmStateSkipTaskbar => self.atoms._NET_WM_STATE_SKIP_TASKBAR,
+            EwmhFeature::WmMoveresize => self.atoms._NET_WM_MOVERESIZE,
+            EwmhFeature::MoveresizeWindow => self.atoms._NET_MOVERESIZE_WINDOW,
+            EwmhFeature::CloseWindow => self.atoms._NET_CLOSE_WINDOW,
+            EwmhFeature::RestackWindow => self.atoms._NET_RESTACK_WINDOW,
             EwmhFeature::ClientList => self.atoms._NET_CLIENT_LIST,
             EwmhFeature::ClientInfo => self.atoms._NET_CLIENT_INFO,
             EwmhFeature::WmWindowType => self.atoms._NET_WM_WINDOW_TYPE,
             EwmhFeature::WmWindowTypeDialog => self.atoms._NET_WM_WINDOW_TYPE_DIALOG,
+            EwmhFeature::DesktopNames => self.atoms._NET_DESKTOP_NAMES,
+            EwmhFeature::NumberOfDesktops => self.atoms._NET_NUMBER_OF_DESKTOPS,
+            EwmhFeature::CurrentDesktop => self.atoms._NET_CURRENT_DESKTOP,
+            EwmhFeature::DesktopViewport => self.atoms._NET_DESKTOP_VIEWPORT,
+            EwmhFeature::Workarea => self.atoms._NET_WORKAREA,
+            EwmhFeature::WmDesktop => self.atoms._NET_WM_DESKTOP,
         }
     }
 }
@@ -60,6 +78,11 @@ impl<C: Connection + Send + Sync + 'static> EwmhFacade for X11EwmhFacade<C> {
             self.atoms._NET_ACTIVE_WINDOW,
             self.atoms._NET_CLIENT_LIST,
             self.atoms._NET_SUPPORTED,
+            self.atoms._NET_DESKTOP_NAMES,
+            self.atoms._NET_NUMBER_OF_DESKTOPS,
+            self.atoms._NET_CURRENT_DESKTOP,
+            self.atoms._NET_DESKTOP_VIEWPORT,
+            self.atoms._NET_WORKAREA,
         ]
         .iter()
         {
@@ -116,6 +139,81 @@ impl<C: Connection + Send + Sync + 'static> EwmhFacade for X11EwmhFacade<C> {
         Ok(WindowId(frame_win as u64))
     }
 
+    fn set_desktop_names(&self, names: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+        // UTF8_STRING 列表，每个名称以 NUL 结尾
+        let mut data = Vec::new();
+        for name in names {
+            data.extend_from_slice(name.as_bytes());
+            data.push(0);
+        }
+        self.conn.change_property8(
+            PropMode::REPLACE,
+            self.root.0 as u32,
+            self.atoms._NET_DESKTOP_NAMES,
+            self.atoms.UTF8_STRING,
+            &data,
+        )?;
+        Ok(())
+    }
+
+    fn set_number_of_desktops(&self, count: u32) -> Result<(), Box<dyn std::error::Error>> {
+        self.conn.change_property32(
+            PropMode::REPLACE,
+            self.root.0 as u32,
+            self.atoms._NET_NUMBER_OF_DESKTOPS,
+            AtomEnum::CARDINAL,
+            &[count],
+        )?;
+        // 不支持大于屏幕的桌面，视口都在原点
+        let viewport = vec![0u32; count as usize * 2];
+        self.conn.change_property32(
+            PropMode::REPLACE,
+            self.root.0 as u32,
+            self.atoms._NET_DESKTOP_VIEWPORT,
+            AtomEnum::CARDINAL,
+            &viewport,
+        )?;
+        Ok(())
+    }
+
+    fn set_current_desktop(&self, index: u32) -> Result<(), Box<dyn std::error::Error>> {
+        self.conn.change_property32(
+            PropMode::REPLACE,
+            self.root.0 as u32,
+            self.atoms._NET_CURRENT_DESKTOP,
+            AtomEnum::CARDINAL,
+            &[index],
+        )?;
+        Ok(())
+    }
+
+    fn set_workarea(&self, areas: &[[u32; 4]]) -> Result<(), Box<dyn std::error::Error>> {
+        let data: Vec<u32> = areas.iter().flatten().copied().collect();
+        self.conn.change_property32(
+            PropMode::REPLACE,
+            self.root.0 as u32,
+            self.atoms._NET_WORKAREA,
+            AtomEnum::CARDINAL,
+            &data,
+        )?;
+        Ok(())
+    }
+
+    fn set_window_desktop(
+        &self,
+        win: WindowId,
+        desktop: u32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.conn.change_property32(
+            PropMode::REPLACE,
+            win.0 as u32,
+            self.atoms._NET_WM_DESKTOP,
+            AtomEnum::CARDINAL,
+            &[desktop],
+        )?;
+        Ok(())
+    }
+
     fn set_supported_atoms(&self, supported: &[u32]) -> Result<(), Box<dyn std::error::Error>> {
         self.conn.change_property32(
             PropMode::REPLACE,
@@ -181,6 +181,25 @@ impl<C: Connection + Send + Sync + 'static> InputOpsTrait for X11InputOps<C> {
         Ok(())
     }
 
+    fn grab_keyboard(&self) -> Result<bool, Box<dyn std::error::Error>> {
+        let reply = self
+            .conn
+            .grab_keyboard(
+                false,
+                self.root,
+                x11rb::CURRENT_TIME,
+                GrabMode::ASYNC,
+                GrabMode::ASYNC,
+            )?
+            .reply()?;
+        Ok(reply.status == GrabStatus::SUCCESS)
+    }
+
+    fn ungrab_keyboard(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.conn.ungrab_keyboard(x11rb::CURRENT_TIME)?.check()?;
+        Ok(())
+    }
+
     fn allow_events(&self, mode: AllowMode, time: u32) -> Result<(), Box<dyn std::error::Error>> {
         let allow = Self::map_allow_mode(mode);
         self.allow_events_raw(allow, time)
@@ -10,6 +10,8 @@ pub mod output_ops;
 pub mod property_ops;
 pub mod window_ops;
 
+use crate::backend::api::NetWmState;
+
 x11rb::atom_manager! {
     pub Atoms: AtomsCookie {
         WM_PROTOCOLS,
@@ -17,6 +19,7 @@ x11rb::atom_manager! {
         WM_STATE,
         WM_TAKE_FOCUS,
         WM_TRANSIENT_FOR,
+        WM_WINDOW_ROLE,
 
         _NET_ACTIVE_WINDOW,
         _NET_SUPPORTED,
@@ -25,11 +28,29 @@ x11rb::atom_manager! {
         _NET_WM_STATE,
         _NET_SUPPORTING_WM_CHECK,
         _NET_WM_STATE_FULLSCREEN,
+        _NET_WM_STATE_STICKY,
+        _NET_WM_STATE_ABOVE,
+        _NET_WM_STATE_BELOW,
+        _NET_WM_STATE_MAXIMIZED_VERT,
+        _NET_WM_STATE_MAXIMIZED_HORZ,
+        _NET_WM_STATE_HIDDEN,
+        _NET_WM_STATE_DEMANDS_ATTENTION,
+        _NET_WM_STATE_SKIP_TASKBAR,
         _NET_WM_WINDOW_TYPE,
         _NET_WM_WINDOW_TYPE_DIALOG,
         _NET_CLIENT_LIST,
         _NET_CLIENT_LIST_STACKING,
         _NET_CLIENT_INFO,
+        _NET_DESKTOP_NAMES,
+        _NET_NUMBER_OF_DESKTOPS,
+        _NET_CURRENT_DESKTOP,
+        _NET_DESKTOP_VIEWPORT,
+        _NET_WORKAREA,
+        _NET_WM_DESKTOP,
+        _NET_WM_MOVERESIZE,
+        _NET_MOVERESIZE_WINDOW,
+        _NET_CLOSE_WINDOW,
+        _NET_RESTACK_WINDOW,
         _NET_WM_STRUT,
         _NET_WM_STRUT_PARTIAL,
         _NET_WM_WINDOW_TYPE_POPUP_MENU,
@@ -38,8 +59,52 @@ x11rb::atom_manager! {
         _NET_WM_WINDOW_TYPE_TOOLTIP,
         _NET_WM_WINDOW_TYPE_COMBO,
         _NET_WM_WINDOW_TYPE_NOTIFICATION,
+        _NET_WM_WINDOW_TYPE_NORMAL,
+        _NET_WM_WINDOW_TYPE_DESKTOP,
+        _NET_WM_WINDOW_TYPE_DOCK,
+        _NET_WM_WINDOW_TYPE_TOOLBAR,
+        _NET_WM_WINDOW_TYPE_UTILITY,
+        _NET_WM_WINDOW_TYPE_SPLASH,
+        _NET_WM_WINDOW_OPACITY,
 
         UTF8_STRING,
         COMPOUND_TEXT,
     }
 }
+
+impl Atoms {
+    // _NET_WM_STATE 的 atom 与语义状态互转
+    pub(crate) fn net_wm_state_atom(&self, state: NetWmState) -> u32 {
+        match state {
+            NetWmState::Fullscreen => self._NET_WM_STATE_FULLSCREEN,
+            NetWmState::Sticky => self._NET_WM_STATE_STICKY,
+            NetWmState::Above => self._NET_WM_STATE_ABOVE,
+            NetWmState::Below => self._NET_WM_STATE_BELOW,
+            NetWmState::MaximizedVert => self._NET_WM_STATE_MAXIMIZED_VERT,
+            NetWmState::MaximizedHorz => self._NET_WM_STATE_MAXIMIZED_HORZ,
+            NetWmState::Hidden => self._NET_WM_STATE_HIDDEN,
+            NetWmState::DemandsAttention => self._NET_WM_STATE_DEMANDS_ATTENTION,
+            NetWmState::SkipTaskbar => self._NET_WM_STATE_SKIP_TASKBAR,
+        }
+    }
+
+    pub(crate) fn net_wm_state_from_atom(&self, atom: u32) -> Option<NetWmState> {
+        NET_WM_STATES
+            .iter()
+            .copied()
+            .find(|&state| self.net_wm_state_atom(state) == atom)
+    }
+}
+
+// WM 管理的全部 _NET_WM_STATE
+pub(crate) const NET_WM_STATES: [NetWmState; 9] = [
+    NetWmState::Fullscreen,
+    NetWmState::Sticky,
+    NetWmState::Above,
+    NetWmState::Below,
+    NetWmState::MaximizedVert,
+    NetWmState::MaximizedHorz,
+    NetWmState::Hidden,
+    NetWmState::DemandsAttention,
+    NetWmState::SkipTaskbar,
+];
@@ -1,8 +1,11 @@
 // src/backend/x11/output_ops.rs
 use crate::backend::api::{OutputInfo, OutputOps, ScreenInfo};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 use x11rb::connection::Connection;
-use x11rb::protocol::randr::ConnectionExt as RandrExt;
+use x11rb::protocol::randr::{ConnectionExt as RandrExt, NotifyMask, Output};
+use x11rb::protocol::xproto::{AtomEnum, ConnectionExt as _};
 
 pub struct X11OutputOps<C: Connection> {
     conn: Arc<C>,
@@ -15,6 +18,38 @@ impl<C: Connection> X11OutputOps<C> {
     pub fn new(conn: Arc<C>, root: u32, sw: i32, sh: i32) -> Self {
         Self { conn, root, sw, sh }
     }
+
+    // 输出名与 EDID 摘要；读取失败时分别为空串和 None
+    fn output_identity(&self, output: Output) -> (String, Option<u64>) {
+        let name = self
+            .conn
+            .randr_get_output_info(output, 0)
+            .ok()
+            .and_then(|c| c.reply().ok())
+            .map(|r| String::from_utf8_lossy(&r.name).into_owned())
+            .unwrap_or_default();
+        let edid = self
+            .conn
+            .intern_atom(true, b"EDID")
+            .ok()
+            .and_then(|c| c.reply().ok())
+            .map(|r| r.atom)
+            .filter(|&atom| atom != 0)
+            .and_then(|atom| {
+                self.conn
+                    .randr_get_output_property(output, atom, AtomEnum::ANY, 0, 64, false, false)
+                    .ok()?
+                    .reply()
+                    .ok()
+            })
+            .filter(|r| !r.data.is_empty())
+            .map(|r| {
+                let mut hasher = DefaultHasher::new();
+                r.data.hash(&mut hasher);
+                hasher.finish()
+            });
+        (name, edid)
+    }
 }
 
 impl<C: Connection + Send + Sync + 'static> OutputOps for X11OutputOps<C> {
@@ -38,12 +73,27 @@ impl<C: Connection + Send + Sync + 'static> OutputOps for X11OutputOps<C> {
                         let mut out = Vec::new();
                         for (i, m) in reply.unwrap().monitors.into_iter().enumerate() {
                             if m.width > 0 && m.height > 0 {
+                                // 没有关联输出的虚拟显示器用其自身名称
+                                let (name, edid) = match m.outputs.first() {
+                                    Some(&output) => self.output_identity(output),
+                                    None => (
+                                        self.conn
+                                            .get_atom_name(m.name)
+                                            .ok()
+                                            .and_then(|c| c.reply().ok())
+                                            .map(|r| String::from_utf8_lossy(&r.name).into_owned())
+                                            .unwrap_or_default(),
+                                        None,
+                                    ),
+                                };
                                 out.push(OutputInfo {
                                     id: i as i32,
                                     x: m.x as i32,
                                     y: m.y as i32,
                                     width: m.width as i32,
                                     height: m.height as i32,
+                                    name,
+                                    edid,
                                 });
                             }
                         }
@@ -70,12 +120,19 @@ impl<C: Connection + Send + Sync + 'static> OutputOps for X11OutputOps<C> {
                 {
                     let ci = ci.unwrap();
                     if ci.width > 0 && ci.height > 0 {
+                        let (name, edid) = ci
+                            .outputs
+                            .first()
+                            .map(|&output| self.output_identity(output))
+                            .unwrap_or_default();
                         out.push(OutputInfo {
                             id: i as i32,
                             x: ci.x as i32,
                             y: ci.y as i32,
                             width: ci.width as i32,
                             height: ci.height as i32,
+                            name,
+                            edid,
                         });
                     }
                 }
@@ -89,6 +146,14 @@ impl<C: Connection + Send + Sync + 'static> OutputOps for X11OutputOps<C> {
             y: 0,
             width: self.sw,
             height: self.sh,
+            name: String::new(),
+            edid: None,
         }]
     }
+
+    fn watch_output_changes(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let mask = NotifyMask::SCREEN_CHANGE | NotifyMask::OUTPUT_CHANGE | NotifyMask::CRTC_CHANGE;
+        self.conn.randr_select_input(self.root, mask)?.check()?;
+        Ok(())
+    }
 }
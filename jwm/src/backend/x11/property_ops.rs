@@ -1,5 +1,7 @@
 // src/backend/x11/property_ops.rs
+use crate::backend::api::NetWmState;
 use crate::backend::api::NormalHints;
+use crate::backend::api::Strut;
 use crate::backend::api::WmHints;
 use crate::backend::api::{PropertyOps as PropertyOpsTrait, WindowId};
 use crate::backend::x11::Atoms;
@@ -114,6 +116,56 @@ impl<C: Connection + Send + Sync + 'static> PropertyOpsTrait for X11PropertyOps<
             .delete_property(win.0 as u32, self.atoms._NET_WM_STRUT_PARTIAL);
         Ok(())
     }
+    fn get_window_strut(&self, win: WindowId) -> Option<Strut> {
+        let read = |atom: Atom, len: u32| -> Option<Vec<u32>> {
+            let reply = self
+                .get_property(win.0 as u32, atom, AtomEnum::CARDINAL, 0, len)
+                .ok()?;
+            let values: Vec<u32> = reply.value32()?.collect();
+            (values.len() == len as usize).then_some(values)
+        };
+        if let Some(v) = read(self.atoms._NET_WM_STRUT_PARTIAL, 12) {
+            // 某些 dock 把范围全填 0，按整条边处理
+            let range = |start: u32, end: u32| {
+                if start == 0 && end == 0 {
+                    (0, u32::MAX)
+                } else {
+                    (start, end)
+                }
+            };
+            let (left_start_y, left_end_y) = range(v[4], v[5]);
+            let (right_start_y, right_end_y) = range(v[6], v[7]);
+            let (top_start_x, top_end_x) = range(v[8], v[9]);
+            let (bottom_start_x, bottom_end_x) = range(v[10], v[11]);
+            return Some(Strut {
+                left: v[0],
+                right: v[1],
+                top: v[2],
+                bottom: v[3],
+                left_start_y,
+                left_end_y,
+                right_start_y,
+                right_end_y,
+                top_start_x,
+                top_end_x,
+                bottom_start_x,
+                bottom_end_x,
+            });
+        }
+        let v = read(self.atoms._NET_WM_STRUT, 4)?;
+        Some(Strut {
+            left: v[0],
+            right: v[1],
+            top: v[2],
+            bottom: v[3],
+            left_end_y: u32::MAX,
+            right_end_y: u32::MAX,
+            top_end_x: u32::MAX,
+            bottom_end_x: u32::MAX,
+            ..Strut::default()
+        })
+    }
+
     fn is_fullscreen(&self, win: WindowId) -> Result<bool, Box<dyn std::error::Error>> {
         let cookie = self.conn.get_property(
             false,
@@ -181,6 +233,32 @@ impl<C: Connection + Send + Sync + 'static> PropertyOpsTrait for X11PropertyOps<
         Ok(())
     }
 
+    fn get_window_pid(&self, win: WindowId) -> Option<u32> {
+        let reply = self
+            .get_property(
+                win.0 as u32,
+                self.atoms._NET_WM_PID,
+                AtomEnum::CARDINAL,
+                0,
+                1,
+            )
+            .ok()?;
+        let pid = reply.value32()?.next()?;
+        (pid > 0).then_some(pid)
+    }
+
+    fn is_sticky(&self, win: WindowId) -> Result<bool, Box<dyn std::error::Error>> {
+        self.has_net_wm_state(win, self.atoms._NET_WM_STATE_STICKY)
+    }
+
+    fn set_sticky_state(&self, win: WindowId, on: bool) -> Result<(), Box<dyn std::error::Error>> {
+        if on {
+            self.add_net_wm_state_atom(win, self.atoms._NET_WM_STATE_STICKY)
+        } else {
+            self.remove_net_wm_state_atom(win, self.atoms._NET_WM_STATE_STICKY)
+        }
+    }
+
     fn get_wm_hints(&self, win: WindowId) -> Option<WmHints> {
         let prop = self
             .conn
@@ -519,6 +597,65 @@ impl<C: Connection + Send + Sync + 'static> PropertyOpsTrait for X11PropertyOps<
         Vec::new()
     }
 
+    fn get_window_type_names(&self, win: WindowId) -> Vec<String> {
+        let table = [
+            (self.atoms._NET_WM_WINDOW_TYPE_NORMAL, "normal"),
+            (self.atoms._NET_WM_WINDOW_TYPE_DIALOG, "dialog"),
+            (self.atoms._NET_WM_WINDOW_TYPE_UTILITY, "utility"),
+            (self.atoms._NET_WM_WINDOW_TYPE_TOOLBAR, "toolbar"),
+            (self.atoms._NET_WM_WINDOW_TYPE_SPLASH, "splash"),
+            (self.atoms._NET_WM_WINDOW_TYPE_DOCK, "dock"),
+            (self.atoms._NET_WM_WINDOW_TYPE_DESKTOP, "desktop"),
+            (self.atoms._NET_WM_WINDOW_TYPE_MENU, "menu"),
+            (self.atoms._NET_WM_WINDOW_TYPE_POPUP_MENU, "popup_menu"),
+            (
+                self.atoms._NET_WM_WINDOW_TYPE_DROPDOWN_MENU,
+                "dropdown_menu",
+            ),
+            (self.atoms._NET_WM_WINDOW_TYPE_TOOLTIP, "tooltip"),
+            (self.atoms._NET_WM_WINDOW_TYPE_COMBO, "combo"),
+            (self.atoms._NET_WM_WINDOW_TYPE_NOTIFICATION, "notification"),
+        ];
+        self.get_window_types(win)
+            .into_iter()
+            .filter_map(|atom| {
+                table
+                    .iter()
+                    .find(|(a, _)| *a == atom)
+                    .map(|(_, name)| (*name).to_string())
+            })
+            .collect()
+    }
+
+    fn get_window_role(&self, win: WindowId) -> Option<String> {
+        self.get_text_property(win, self.atoms.WM_WINDOW_ROLE)
+    }
+
+    fn set_window_opacity(
+        &self,
+        win: WindowId,
+        opacity: Option<f32>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        use x11rb::wrapper::ConnectionExt;
+        match opacity {
+            Some(value) => {
+                let value = (f64::from(value.clamp(0.0, 1.0)) * f64::from(u32::MAX)) as u32;
+                self.conn.change_property32(
+                    PropMode::REPLACE,
+                    win.0 as u32,
+                    self.atoms._NET_WM_WINDOW_OPACITY,
+                    AtomEnum::CARDINAL,
+                    &[value],
+                )?;
+            }
+            None => {
+                self.conn
+                    .delete_property(win.0 as u32, self.atoms._NET_WM_WINDOW_OPACITY)?;
+            }
+        }
+        Ok(())
+    }
+
     fn set_net_wm_state_atoms(
         &self,
         win: WindowId,
@@ -535,6 +672,29 @@ impl<C: Connection + Send + Sync + 'static> PropertyOpsTrait for X11PropertyOps<
         Ok(())
     }
 
+    fn get_net_wm_states(&self, win: WindowId) -> Vec<NetWmState> {
+        self.get_net_wm_state_atoms(win)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|atom| self.atoms.net_wm_state_from_atom(atom))
+            .collect()
+    }
+
+    fn set_net_wm_states(
+        &self,
+        win: WindowId,
+        states: &[NetWmState],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut atoms: Vec<u32> = self
+            .get_net_wm_state_atoms(win)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|&atom| self.atoms.net_wm_state_from_atom(atom).is_none())
+            .collect();
+        atoms.extend(states.iter().map(|&s| self.atoms.net_wm_state_atom(s)));
+        self.set_net_wm_state_atoms(win, &atoms)
+    }
+
     fn get_net_wm_state_atoms(
         &self,
         win: WindowId,
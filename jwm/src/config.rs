@@ -1,20 +1,26 @@
 use cfg_if::cfg_if;
+use nix::sys::inotify::{AddWatchFlags, InitFlags, Inotify, WatchDescriptor};
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ffi::OsString;
 use std::fs;
 use std::path::Path;
+use std::sync::{Arc, RwLock};
 
 use std::fmt;
-use std::rc::Rc;
 
 use crate::jwm::WMFuncType;
-use crate::jwm::{self, Jwm, LayoutEnum, WMButton, WMClickType, WMKey, WMRule};
+use crate::jwm::{self, Jwm, RulePattern, WMButton, WMClickType, WMKey, WMRule};
+use crate::layout::{Gaps, LAYOUTS};
 use crate::terminal_prober::ADVANCED_TERMINAL_PROBER;
+use shared_structures::MAX_TAGS;
 
 use crate::backend::common_define::keys as k;
-use crate::backend::common_define::{KeySym, Mods, MouseButton};
+use crate::backend::common_define::{ArgbColor, KeySym, Mods, MouseButton};
 
-pub const LOAD_LOCAL_CONFIG: bool = false;
+// 配置文件不存在时是否生成模板
+pub const GENERATE_CONFIG_TEMPLATE: bool = false;
 
 macro_rules! status_bar_config {
     ($($feature:literal => $name:literal),* $(,)?) => {
@@ -58,6 +64,55 @@ pub struct TomlConfig {
     pub mouse_bindings: MouseBindingsConfig,
     pub rules: Vec<RuleConfig>,
     pub layout: LayoutConfig,
+    #[serde(default)]
+    pub scratchpads: Vec<ScratchpadConfig>,
+    // 非空时 tag 数量由此决定，覆盖 layout.tags_length
+    #[serde(default)]
+    pub tags: Vec<TagConfig>,
+    #[serde(default)]
+    pub monitors: Vec<MonitorConfig>,
+}
+
+/// 具名 tag：name 发布为 _NET_DESKTOP_NAMES，状态栏优先显示 icon
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagConfig {
+    pub name: String,
+    #[serde(default)]
+    pub icon: String,
+}
+
+/// 单个显示器的默认值：按输出名（如 "HDMI-1"）或序号匹配，未设置的字段沿用全局配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MonitorConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<usize>,
+    // 所有 tag 的默认布局
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout: Option<String>,
+    // 按 tag 顺序覆盖 layout
+    #[serde(default)]
+    pub layouts: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub m_fact: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub n_master: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub show_bar: Option<bool>,
+    // 初始显示的 tag，从 1 开始
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<usize>,
+}
+
+impl MonitorConfig {
+    // tag 从 1 开始，0 表示查看全部 tag
+    pub fn tag_layout(&self, tag: usize) -> Option<&str> {
+        tag.checked_sub(1)
+            .and_then(|i| self.layouts.get(i))
+            .or(self.layout.as_ref())
+            .map(String::as_str)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -67,6 +122,18 @@ pub struct AppearanceConfig {
     pub dmenu_font: String,
     pub status_bar_padding: i32,
     pub status_bar_height: i32,
+    // 间隙：inner_h 左右相邻窗口之间，inner_v 上下相邻窗口之间，outer_h/outer_v 与屏幕边缘之间
+    #[serde(default)]
+    pub gap_inner_h: u32,
+    #[serde(default)]
+    pub gap_inner_v: u32,
+    #[serde(default)]
+    pub gap_outer_h: u32,
+    #[serde(default)]
+    pub gap_outer_v: u32,
+    // 只有一个平铺窗口时不留间隙
+    #[serde(default)]
+    pub smart_gaps: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -80,6 +147,9 @@ pub struct BehaviorConfig {
 pub struct StatusBarConfig {
     pub name: String,
     pub show_bar: bool,
+    // 每个显示器各运行一个状态栏，而不是一个随焦点移动的状态栏
+    #[serde(default)]
+    pub per_monitor: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -107,16 +177,60 @@ pub struct LayoutConfig {
 pub struct KeyBindingsConfig {
     pub modkey: String, // "Mod1", "Mod4", etc.
     pub keys: Vec<KeyConfig>,
+    // 多键序列，如 Mod1+x 之后再按 f
+    #[serde(default)]
+    pub chords: Vec<ChordConfig>,
+    // 命名按键模式：进入后只抓取模式内的按键，Escape 返回默认模式
+    #[serde(default)]
+    pub modes: Vec<KeyModeConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChordStepConfig {
+    #[serde(default)]
+    pub modifier: Vec<String>,
+    pub key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChordConfig {
+    // 依次按下的键，至少一个
+    pub keys: Vec<ChordStepConfig>,
+    pub function: String,
+    pub argument: ArgumentConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyModeConfig {
+    pub name: String,
+    // 触发任一绑定后自动返回默认模式（如启动模式）
+    #[serde(default)]
+    pub oneshot: bool,
+    #[serde(default)]
+    pub keys: Vec<KeyConfig>,
+    #[serde(default)]
+    pub chords: Vec<ChordConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeyConfig {
     pub modifier: Vec<String>, // ["Mod1", "Shift"]
     pub key: String,           // "Return", "j", "k", etc.
+    #[serde(default)]
+    pub on: TriggerEvent, // "press" 或 "release"
     pub function: String,      // "spawn", "focusstack", etc.
     pub argument: ArgumentConfig,
 }
 
+// 绑定在按下还是松开时触发；松开绑定可用于单独轻按修饰键
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TriggerEvent {
+    #[default]
+    Press,
+    Release,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ArgumentConfig {
@@ -127,6 +241,100 @@ pub enum ArgumentConfig {
     StringVec(Vec<String>),
 }
 
+impl ArgumentConfig {
+    fn type_name(&self) -> &'static str {
+        match self {
+            ArgumentConfig::Int(_) => "integer",
+            ArgumentConfig::UInt(_) => "unsigned integer",
+            ArgumentConfig::Float(_) => "float",
+            ArgumentConfig::String(_) => "string",
+            ArgumentConfig::StringVec(_) => "string array",
+        }
+    }
+}
+
+// 绑定函数期望的参数类型，用于配置检查和参数转换
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArgumentKind {
+    // 参数被忽略
+    Ignored,
+    Int,
+    // 标签位掩码；TOML 整数按 Int 解析，转换时再转为 UInt
+    TagMask,
+    Float,
+    // 命令或名称：字符串或字符串数组
+    Strings,
+    // 布局名；其他类型表示在两个布局间切换
+    Layout,
+    // 按键模式名，"default" 表示默认模式
+    Mode,
+}
+
+// 规则 window_type 可用的短名，与后端 _NET_WM_WINDOW_TYPE 映射一致
+pub const WINDOW_TYPE_NAMES: &[&str] = &[
+    "normal",
+    "dialog",
+    "utility",
+    "toolbar",
+    "splash",
+    "dock",
+    "desktop",
+    "menu",
+    "popup_menu",
+    "dropdown_menu",
+    "tooltip",
+    "combo",
+    "notification",
+];
+
+// 可绑定的最大鼠标按键号（8、9 通常为侧键）
+const MAX_MOUSE_BUTTON: u8 = 9;
+
+/// 配置中的一个问题；path 形如 `keybindings.keys[3].function`
+#[derive(Debug, Clone)]
+pub struct ConfigIssue {
+    pub path: String,
+    pub message: String,
+    // 仅提示，不会使配置无效
+    pub warning: bool,
+}
+
+impl ConfigIssue {
+    fn new(path: String, message: String) -> Self {
+        Self {
+            path,
+            message,
+            warning: false,
+        }
+    }
+
+    fn warning(path: String, message: String) -> Self {
+        Self {
+            path,
+            message,
+            warning: true,
+        }
+    }
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.warning {
+            write!(f, "{}: warning: {}", self.path, self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+fn unknown_key_message(key: &str) -> String {
+    format!(
+        "unknown key '{}': not a keysym name known to xkbcommon \
+         (e.g. \"Return\", \"minus\", \"bracketleft\", \"XF86AudioRaiseVolume\")",
+        key
+    )
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MouseBindingsConfig {
     pub buttons: Vec<ButtonConfig>,
@@ -134,25 +342,144 @@ pub struct MouseBindingsConfig {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ButtonConfig {
-    pub click_type: String, //
+    pub click_type: String, // "ClkClientWin" 或 "ClkRootWin"（空白桌面）
     pub modifier: Vec<String>,
-    pub button: u8, // 1, 2, 3
+    pub button: u8, // 1-3 为左/中/右键，4-7 为滚轮上/下/左/右
+    #[serde(default)]
+    pub on: TriggerEvent,
     pub function: String,
     pub argument: ArgumentConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RuleConfig {
+    // 匹配条件：空字符串表示忽略该字段
+    #[serde(default)]
     pub class: String,
+    #[serde(default)]
     pub instance: String,
+    #[serde(default)]
     pub name: String,
+    // WM_WINDOW_ROLE
+    #[serde(default)]
+    pub role: String,
+    // _NET_WM_WINDOW_TYPE 短名，如 "dialog"、"utility"、"splash"
+    #[serde(default)]
+    pub window_type: String,
+    // class/instance/name/role 的匹配方式
+    #[serde(default)]
+    pub match_mode: RuleMatchMode,
+
+    #[serde(default)]
     pub tags_mask: usize,
+    #[serde(default)]
     pub is_floating: bool,
+    #[serde(default)]
+    pub is_sticky: bool,
+    #[serde(default)]
+    pub is_terminal: bool,
+    #[serde(default)]
+    pub no_swallow: bool,
+    #[serde(default = "default_rule_monitor")]
     pub monitor: i32,
+
+    // 初始几何（隐含浮动）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geometry: Option<GeometryConfig>,
+    // 在显示器工作区内居中（隐含浮动）
+    #[serde(default)]
+    pub center: bool,
+    #[serde(default)]
+    pub fullscreen: bool,
+    // 新窗口不抢焦点
+    #[serde(default)]
+    pub no_focus: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub border_width: Option<u32>,
+    // 0.0 ~ 1.0，写入 _NET_WM_WINDOW_OPACITY 由合成器处理
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opacity: Option<f32>,
+    // 窗口出现时切换到其所在显示器和 tag
+    #[serde(default)]
+    pub switch_to_tag: bool,
+}
+
+fn default_rule_monitor() -> i32 {
+    -1
+}
+
+/// contains：子串匹配；glob：整串通配（`*`、`?`、`[...]`）；regex：正则搜索
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleMatchMode {
+    #[default]
+    Contains,
+    Glob,
+    Regex,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScratchpadConfig {
+    pub name: String,
+    pub command: Vec<String>,
+    // 与 rules 相同：子串匹配，空字符串表示忽略该字段
+    #[serde(default)]
+    pub class: String,
+    #[serde(default)]
+    pub instance: String,
+    #[serde(default)]
+    pub geometry: GeometryConfig,
+}
+
+/// 未指定 x/y 时在显示器工作区内居中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeometryConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x: Option<SizeValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y: Option<SizeValue>,
+    pub width: SizeValue,
+    pub height: SizeValue,
+}
+
+impl Default for GeometryConfig {
+    fn default() -> Self {
+        Self {
+            x: None,
+            y: None,
+            width: SizeValue::Relative("60%".to_string()),
+            height: SizeValue::Relative("50%".to_string()),
+        }
+    }
+}
+
+/// 像素值（800）或相对显示器工作区的百分比（"60%"）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SizeValue {
+    Pixels(i32),
+    Relative(String),
+}
+
+impl SizeValue {
+    pub fn resolve(&self, total: i32) -> i32 {
+        match self {
+            SizeValue::Pixels(px) => *px,
+            SizeValue::Relative(s) => match s.trim().trim_end_matches('%').parse::<f32>() {
+                Ok(percent) => (total as f32 * percent / 100.0) as i32,
+                Err(_) => {
+                    eprintln!("Invalid size value: {}", s);
+                    total / 2
+                }
+            },
+        }
+    }
 }
 
 pub struct Config {
     inner: TomlConfig,
+    // 加载时预编译的窗口规则，与 inner.rules 一一对应
+    rules: Vec<WMRule>,
 }
 
 impl Default for Config {
@@ -165,6 +492,11 @@ impl Default for Config {
                     dmenu_font: "SauceCodePro Nerd Font Regular 11".to_string(),
                     status_bar_padding: 5,
                     status_bar_height: 42,
+                    gap_inner_h: 0,
+                    gap_inner_v: 0,
+                    gap_outer_h: 0,
+                    gap_outer_v: 0,
+                    smart_gaps: false,
                 },
                 behavior: BehaviorConfig {
                     focus_follows_new_window: false,
@@ -174,6 +506,7 @@ impl Default for Config {
                 status_bar: StatusBarConfig {
                     name: STATUS_BAR_NAME.to_string(),
                     show_bar: true,
+                    per_monitor: false,
                 },
                 colors: ColorsConfig {
                     dark_sea_green1: "#afffd7".to_string(),
@@ -195,12 +528,18 @@ impl Default for Config {
                 keybindings: KeyBindingsConfig {
                     modkey: "Mod1".to_string(),
                     keys: Self::get_default_keys(),
+                    chords: Vec::new(),
+                    modes: Vec::new(),
                 },
                 mouse_bindings: MouseBindingsConfig {
                     buttons: Self::get_default_button_configs(),
                 },
                 rules: Self::get_default_rules(),
+                scratchpads: Vec::new(),
+                tags: Vec::new(),
+                monitors: Vec::new(),
             },
+            rules: Self::compile_rules(&Self::get_default_rules()),
         }
     }
 }
@@ -214,6 +553,7 @@ impl Config {
             KeyConfig {
                 modifier: vec!["Mod1".to_string()],
                 key: "e".to_string(),
+                on: TriggerEvent::Press,
                 function: "spawn".to_string(),
                 argument: ArgumentConfig::StringVec(vec![
                     "dmenu_run".to_string(),
@@ -235,6 +575,7 @@ impl Config {
             KeyConfig {
                 modifier: vec!["Mod1".to_string()],
                 key: "r".to_string(),
+                on: TriggerEvent::Press,
                 function: "spawn".to_string(),
                 argument: ArgumentConfig::StringVec(vec![
                     "dmenu_run".to_string(),
@@ -256,12 +597,14 @@ impl Config {
             KeyConfig {
                 modifier: vec!["Mod1".to_string(), "Shift".to_string()],
                 key: "Return".to_string(),
+                on: TriggerEvent::Press,
                 function: "spawn".to_string(),
                 argument: ArgumentConfig::StringVec(Self::get_termcmd()),
             },
             KeyConfig {
                 modifier: vec!["Mod1".to_string()],
                 key: "b".to_string(),
+                on: TriggerEvent::Press,
                 function: "togglebar".to_string(),
                 argument: ArgumentConfig::Int(0),
             },
@@ -269,12 +612,14 @@ impl Config {
             KeyConfig {
                 modifier: vec!["Mod1".to_string()],
                 key: "j".to_string(),
+                on: TriggerEvent::Press,
                 function: "focusstack".to_string(),
                 argument: ArgumentConfig::Int(1),
             },
             KeyConfig {
                 modifier: vec!["Mod1".to_string()],
                 key: "k".to_string(),
+                on: TriggerEvent::Press,
                 function: "focusstack".to_string(),
                 argument: ArgumentConfig::Int(-1),
             },
@@ -282,12 +627,14 @@ impl Config {
             KeyConfig {
                 modifier: vec!["Mod1".to_string()],
                 key: "i".to_string(),
+                on: TriggerEvent::Press,
                 function: "incnmaster".to_string(),
                 argument: ArgumentConfig::Int(1),
             },
             KeyConfig {
                 modifier: vec!["Mod1".to_string()],
                 key: "d".to_string(),
+                on: TriggerEvent::Press,
                 function: "incnmaster".to_string(),
                 argument: ArgumentConfig::Int(-1),
             },
@@ -295,12 +642,14 @@ impl Config {
             KeyConfig {
                 modifier: vec!["Mod1".to_string()],
                 key: "h".to_string(),
+                on: TriggerEvent::Press,
                 function: "setmfact".to_string(),
                 argument: ArgumentConfig::Float(-0.025),
             },
             KeyConfig {
                 modifier: vec!["Mod1".to_string()],
                 key: "l".to_string(),
+                on: TriggerEvent::Press,
                 function: "setmfact".to_string(),
                 argument: ArgumentConfig::Float(0.025),
             },
@@ -308,31 +657,69 @@ impl Config {
             KeyConfig {
                 modifier: vec!["Mod1".to_string(), "Shift".to_string()],
                 key: "h".to_string(),
+                on: TriggerEvent::Press,
                 function: "setcfact".to_string(),
                 argument: ArgumentConfig::Float(0.2),
             },
             KeyConfig {
                 modifier: vec!["Mod1".to_string(), "Shift".to_string()],
                 key: "l".to_string(),
+                on: TriggerEvent::Press,
                 function: "setcfact".to_string(),
                 argument: ArgumentConfig::Float(-0.2),
             },
             KeyConfig {
                 modifier: vec!["Mod1".to_string(), "Shift".to_string()],
                 key: "o".to_string(),
+                on: TriggerEvent::Press,
                 function: "setcfact".to_string(),
                 argument: ArgumentConfig::Float(0.0),
             },
+            // 间隙调整
+            KeyConfig {
+                modifier: vec!["Mod1".to_string(), "Control".to_string()],
+                key: "k".to_string(),
+                on: TriggerEvent::Press,
+                function: "incrgaps".to_string(),
+                argument: ArgumentConfig::Int(2),
+            },
+            KeyConfig {
+                modifier: vec!["Mod1".to_string(), "Control".to_string()],
+                key: "j".to_string(),
+                on: TriggerEvent::Press,
+                function: "incrgaps".to_string(),
+                argument: ArgumentConfig::Int(-2),
+            },
+            KeyConfig {
+                modifier: vec!["Mod1".to_string(), "Control".to_string()],
+                key: "0".to_string(),
+                on: TriggerEvent::Press,
+                function: "togglegaps".to_string(),
+                argument: ArgumentConfig::Int(0),
+            },
+            KeyConfig {
+                modifier: vec![
+                    "Mod1".to_string(),
+                    "Control".to_string(),
+                    "Shift".to_string(),
+                ],
+                key: "0".to_string(),
+                on: TriggerEvent::Press,
+                function: "defaultgaps".to_string(),
+                argument: ArgumentConfig::Int(0),
+            },
             // 窗口移动
             KeyConfig {
                 modifier: vec!["Mod1".to_string(), "Shift".to_string()],
                 key: "j".to_string(),
+                on: TriggerEvent::Press,
                 function: "movestack".to_string(),
                 argument: ArgumentConfig::Int(1),
             },
             KeyConfig {
                 modifier: vec!["Mod1".to_string(), "Shift".to_string()],
                 key: "k".to_string(),
+                on: TriggerEvent::Press,
                 function: "movestack".to_string(),
                 argument: ArgumentConfig::Int(-1),
             },
@@ -340,6 +727,7 @@ impl Config {
             KeyConfig {
                 modifier: vec!["Mod1".to_string()],
                 key: "Return".to_string(),
+                on: TriggerEvent::Press,
                 function: "zoom".to_string(),
                 argument: ArgumentConfig::Int(0),
             },
@@ -347,24 +735,28 @@ impl Config {
             KeyConfig {
                 modifier: vec!["Mod1".to_string()],
                 key: "Tab".to_string(),
+                on: TriggerEvent::Press,
                 function: "loopview".to_string(),
                 argument: ArgumentConfig::Int(1),
             },
             KeyConfig {
                 modifier: vec!["Mod1".to_string(), "Shift".to_string()],
                 key: "Tab".to_string(),
+                on: TriggerEvent::Press,
                 function: "loopview".to_string(),
                 argument: ArgumentConfig::Int(-1),
             },
             KeyConfig {
                 modifier: vec!["Mod1".to_string()],
                 key: "Page_Up".to_string(),
+                on: TriggerEvent::Press,
                 function: "loopview".to_string(),
                 argument: ArgumentConfig::Int(-1),
             },
             KeyConfig {
                 modifier: vec!["Mod1".to_string()],
                 key: "Page_Down".to_string(),
+                on: TriggerEvent::Press,
                 function: "loopview".to_string(),
                 argument: ArgumentConfig::Int(1),
             },
@@ -372,6 +764,7 @@ impl Config {
             KeyConfig {
                 modifier: vec!["Mod1".to_string(), "Shift".to_string()],
                 key: "c".to_string(),
+                on: TriggerEvent::Press,
                 function: "killclient".to_string(),
                 argument: ArgumentConfig::Int(0),
             },
@@ -379,37 +772,85 @@ impl Config {
             KeyConfig {
                 modifier: vec!["Mod1".to_string()],
                 key: "t".to_string(),
+                on: TriggerEvent::Press,
                 function: "setlayout".to_string(),
                 argument: ArgumentConfig::String("tile".to_string()),
             },
             KeyConfig {
                 modifier: vec!["Mod1".to_string()],
                 key: "f".to_string(),
+                on: TriggerEvent::Press,
                 function: "setlayout".to_string(),
                 argument: ArgumentConfig::String("float".to_string()),
             },
             KeyConfig {
                 modifier: vec!["Mod1".to_string()],
                 key: "m".to_string(),
+                on: TriggerEvent::Press,
                 function: "setlayout".to_string(),
                 argument: ArgumentConfig::String("monocle".to_string()),
             },
+            KeyConfig {
+                modifier: vec!["Mod1".to_string()],
+                key: "u".to_string(),
+                on: TriggerEvent::Press,
+                function: "setlayout".to_string(),
+                argument: ArgumentConfig::String("bstack".to_string()),
+            },
+            KeyConfig {
+                modifier: vec!["Mod1".to_string()],
+                key: "o".to_string(),
+                on: TriggerEvent::Press,
+                function: "setlayout".to_string(),
+                argument: ArgumentConfig::String("centeredmaster".to_string()),
+            },
+            KeyConfig {
+                modifier: vec!["Mod1".to_string()],
+                key: "g".to_string(),
+                on: TriggerEvent::Press,
+                function: "setlayout".to_string(),
+                argument: ArgumentConfig::String("grid".to_string()),
+            },
+            KeyConfig {
+                modifier: vec!["Mod1".to_string()],
+                key: "y".to_string(),
+                on: TriggerEvent::Press,
+                function: "setlayout".to_string(),
+                argument: ArgumentConfig::String("spiral".to_string()),
+            },
+            KeyConfig {
+                modifier: vec!["Mod1".to_string(), "Shift".to_string()],
+                key: "y".to_string(),
+                on: TriggerEvent::Press,
+                function: "setlayout".to_string(),
+                argument: ArgumentConfig::String("dwindle".to_string()),
+            },
+            KeyConfig {
+                modifier: vec!["Mod1".to_string(), "Shift".to_string()],
+                key: "m".to_string(),
+                on: TriggerEvent::Press,
+                function: "setlayout".to_string(),
+                argument: ArgumentConfig::String("deck".to_string()),
+            },
             // 布局切换
             KeyConfig {
                 modifier: vec!["Mod1".to_string()],
                 key: "space".to_string(),
+                on: TriggerEvent::Press,
                 function: "setlayout".to_string(),
                 argument: ArgumentConfig::UInt(0),
             },
             KeyConfig {
                 modifier: vec!["Mod1".to_string(), "Shift".to_string()],
                 key: "space".to_string(),
+                on: TriggerEvent::Press,
                 function: "togglefloating".to_string(),
                 argument: ArgumentConfig::Int(0),
             },
             KeyConfig {
                 modifier: vec!["Mod1".to_string(), "Shift".to_string()],
                 key: "f".to_string(),
+                on: TriggerEvent::Press,
                 function: "togglefullscr".to_string(),
                 argument: ArgumentConfig::Int(0),
             },
@@ -417,12 +858,14 @@ impl Config {
             KeyConfig {
                 modifier: vec!["Mod1".to_string()],
                 key: "0".to_string(),
+                on: TriggerEvent::Press,
                 function: "view".to_string(),
                 argument: ArgumentConfig::UInt(!0), // 所有标签
             },
             KeyConfig {
                 modifier: vec!["Mod1".to_string(), "Shift".to_string()],
                 key: "0".to_string(),
+                on: TriggerEvent::Press,
                 function: "tag".to_string(),
                 argument: ArgumentConfig::UInt(!0), // 所有标签
             },
@@ -430,46 +873,125 @@ impl Config {
             KeyConfig {
                 modifier: vec!["Mod1".to_string()],
                 key: "comma".to_string(),
+                on: TriggerEvent::Press,
                 function: "focusmon".to_string(),
                 argument: ArgumentConfig::Int(-1),
             },
             KeyConfig {
                 modifier: vec!["Mod1".to_string()],
                 key: "period".to_string(),
+                on: TriggerEvent::Press,
                 function: "focusmon".to_string(),
                 argument: ArgumentConfig::Int(1),
             },
             KeyConfig {
                 modifier: vec!["Mod1".to_string()],
                 key: "s".to_string(),
+                on: TriggerEvent::Press,
                 function: "take_screenshot".to_string(),
                 argument: ArgumentConfig::Int(0),
             },
+            KeyConfig {
+                modifier: vec!["Mod1".to_string(), "Shift".to_string()],
+                key: "s".to_string(),
+                on: TriggerEvent::Press,
+                function: "togglesticky".to_string(),
+                argument: ArgumentConfig::Int(0),
+            },
             KeyConfig {
                 modifier: vec!["Mod1".to_string(), "Shift".to_string()],
                 key: "comma".to_string(),
+                on: TriggerEvent::Press,
                 function: "tagmon".to_string(),
                 argument: ArgumentConfig::Int(-1),
             },
             KeyConfig {
                 modifier: vec!["Mod1".to_string(), "Shift".to_string()],
                 key: "period".to_string(),
+                on: TriggerEvent::Press,
                 function: "tagmon".to_string(),
                 argument: ArgumentConfig::Int(1),
             },
+            KeyConfig {
+                modifier: vec!["Mod1".to_string(), "Control".to_string()],
+                key: "comma".to_string(),
+                on: TriggerEvent::Press,
+                function: "swapmon".to_string(),
+                argument: ArgumentConfig::Int(-1),
+            },
+            KeyConfig {
+                modifier: vec!["Mod1".to_string(), "Control".to_string()],
+                key: "period".to_string(),
+                on: TriggerEvent::Press,
+                function: "swapmon".to_string(),
+                argument: ArgumentConfig::Int(1),
+            },
             // 退出
             KeyConfig {
                 modifier: vec!["Mod1".to_string(), "Shift".to_string()],
                 key: "q".to_string(),
+                on: TriggerEvent::Press,
                 function: "quit".to_string(),
                 argument: ArgumentConfig::Int(0),
             },
             KeyConfig {
                 modifier: vec!["Mod1".to_string(), "Shift".to_string()],
                 key: "r".to_string(),
+                on: TriggerEvent::Press,
                 function: "restart".to_string(),
                 argument: ArgumentConfig::Int(0),
             },
+            // 多媒体键
+            KeyConfig {
+                modifier: vec![],
+                key: "XF86AudioRaiseVolume".to_string(),
+                on: TriggerEvent::Press,
+                function: "volume".to_string(),
+                argument: ArgumentConfig::Int(5),
+            },
+            KeyConfig {
+                modifier: vec![],
+                key: "XF86AudioLowerVolume".to_string(),
+                on: TriggerEvent::Press,
+                function: "volume".to_string(),
+                argument: ArgumentConfig::Int(-5),
+            },
+            KeyConfig {
+                modifier: vec![],
+                key: "XF86AudioMute".to_string(),
+                on: TriggerEvent::Press,
+                function: "volume".to_string(),
+                argument: ArgumentConfig::Int(0),
+            },
+            KeyConfig {
+                modifier: vec![],
+                key: "XF86MonBrightnessUp".to_string(),
+                on: TriggerEvent::Press,
+                function: "spawn".to_string(),
+                argument: ArgumentConfig::StringVec(vec![
+                    "brightnessctl".to_string(),
+                    "set".to_string(),
+                    "5%+".to_string(),
+                ]),
+            },
+            KeyConfig {
+                modifier: vec![],
+                key: "XF86MonBrightnessDown".to_string(),
+                on: TriggerEvent::Press,
+                function: "spawn".to_string(),
+                argument: ArgumentConfig::StringVec(vec![
+                    "brightnessctl".to_string(),
+                    "set".to_string(),
+                    "5%-".to_string(),
+                ]),
+            },
+            KeyConfig {
+                modifier: vec![],
+                key: "Print".to_string(),
+                on: TriggerEvent::Press,
+                function: "take_screenshot".to_string(),
+                argument: ArgumentConfig::Int(0),
+            },
         ]
     }
 
@@ -480,6 +1002,7 @@ impl Config {
                 click_type: "ClkClientWin".to_string(),
                 modifier: vec!["Mod1".to_string()],
                 button: 1, // 左键
+                on: TriggerEvent::Press,
                 function: "movemouse".to_string(),
                 argument: ArgumentConfig::Int(0),
             },
@@ -487,6 +1010,7 @@ impl Config {
                 click_type: "ClkClientWin".to_string(),
                 modifier: vec!["Mod1".to_string()],
                 button: 2, // 中键
+                on: TriggerEvent::Press,
                 function: "togglefloating".to_string(),
                 argument: ArgumentConfig::Int(0),
             },
@@ -494,9 +1018,42 @@ impl Config {
                 click_type: "ClkClientWin".to_string(),
                 modifier: vec!["Mod1".to_string()],
                 button: 3, // 右键
+                on: TriggerEvent::Press,
                 function: "resizemouse".to_string(),
                 argument: ArgumentConfig::Int(0),
             },
+            ButtonConfig {
+                click_type: "ClkClientWin".to_string(),
+                modifier: vec!["Mod1".to_string()],
+                button: 4, // 滚轮上
+                on: TriggerEvent::Press,
+                function: "loopview".to_string(),
+                argument: ArgumentConfig::Int(-1),
+            },
+            ButtonConfig {
+                click_type: "ClkClientWin".to_string(),
+                modifier: vec!["Mod1".to_string()],
+                button: 5, // 滚轮下
+                on: TriggerEvent::Press,
+                function: "loopview".to_string(),
+                argument: ArgumentConfig::Int(1),
+            },
+            ButtonConfig {
+                click_type: "ClkRootWin".to_string(),
+                modifier: vec!["Mod1".to_string()],
+                button: 4,
+                on: TriggerEvent::Press,
+                function: "loopview".to_string(),
+                argument: ArgumentConfig::Int(-1),
+            },
+            ButtonConfig {
+                click_type: "ClkRootWin".to_string(),
+                modifier: vec!["Mod1".to_string()],
+                button: 5,
+                on: TriggerEvent::Press,
+                function: "loopview".to_string(),
+                argument: ArgumentConfig::Int(1),
+            },
         ]
     }
 
@@ -507,8 +1064,22 @@ impl Config {
 
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
         let content = fs::read_to_string(path)?;
-        let config: TomlConfig = toml::from_str(&content)?;
-        Ok(Self { inner: config })
+        Self::from_toml_str(&content)
+    }
+
+    pub fn from_toml_str(content: &str) -> Result<Self, ConfigError> {
+        let config: TomlConfig = toml::from_str(content)?;
+        Ok(Self {
+            rules: Self::compile_rules(&config.rules),
+            inner: config,
+        })
+    }
+
+    /// 加载并校验配置文件，失败时不产生任何副作用
+    pub fn load_validated<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let config = Self::load_from_file(path)?;
+        config.validate()?;
+        Ok(config)
     }
 
     pub fn load_default() -> Self {
@@ -534,6 +1105,20 @@ impl Config {
         self.inner.appearance.status_bar_padding
     }
 
+    pub fn gaps(&self) -> Gaps {
+        let appearance = &self.inner.appearance;
+        Gaps {
+            inner_h: appearance.gap_inner_h as i32,
+            inner_v: appearance.gap_inner_v as i32,
+            outer_h: appearance.gap_outer_h as i32,
+            outer_v: appearance.gap_outer_v as i32,
+        }
+    }
+
+    pub fn smart_gaps(&self) -> bool {
+        self.inner.appearance.smart_gaps
+    }
+
     pub fn status_bar_height(&self) -> i32 {
         self.inner.appearance.status_bar_height
     }
@@ -546,6 +1131,10 @@ impl Config {
         self.inner.status_bar.show_bar
     }
 
+    pub fn status_bar_per_monitor(&self) -> bool {
+        self.inner.status_bar.per_monitor
+    }
+
     pub fn status_bar_name(&self) -> &str {
         &self.inner.status_bar.name
     }
@@ -570,23 +1159,48 @@ impl Config {
         self.inner.layout.n_master
     }
 
+    // tag 以 u32 掩码表示，最多 MAX_TAGS 个
     pub fn tags_length(&self) -> usize {
-        self.inner.layout.tags_length
+        let length = if self.inner.tags.is_empty() {
+            self.inner.layout.tags_length
+        } else {
+            self.inner.tags.len()
+        };
+        length.clamp(1, MAX_TAGS)
+    }
+
+    // tag 名称，未命名时为序号（从 1 开始）
+    pub fn tag_name(&self, index: usize) -> String {
+        match self.inner.tags.get(index) {
+            Some(tag) if !tag.name.is_empty() => tag.name.clone(),
+            _ => (index + 1).to_string(),
+        }
+    }
+
+    // 状态栏显示的标签：有图标用图标，否则用名称
+    pub fn tag_label(&self, index: usize) -> String {
+        match self.inner.tags.get(index) {
+            Some(tag) if !tag.icon.is_empty() => tag.icon.clone(),
+            _ => self.tag_name(index),
+        }
+    }
+
+    pub fn tag_names(&self) -> Vec<String> {
+        (0..self.tags_length()).map(|i| self.tag_name(i)).collect()
+    }
+
+    pub fn tag_labels(&self) -> Vec<String> {
+        (0..self.tags_length()).map(|i| self.tag_label(i)).collect()
     }
 
     pub fn tagmask(&self) -> u32 {
-        (1 << self.tags_length()) - 1
+        u32::MAX >> (32 - self.tags_length())
     }
 
     // 转换方法
     pub fn get_keys(&self) -> Vec<WMKey> {
-        let mut keys = Vec::new();
-
-        for key_config in &self.inner.keybindings.keys {
-            if let Some(key) = self.convert_key_config(key_config) {
-                keys.push(key);
-            }
-        }
+        let keybindings = &self.inner.keybindings;
+        let mut keys = self.convert_key_set(&keybindings.keys, &keybindings.chords);
 
         // 添加标签键
         for i in 0..self.tags_length() {
@@ -596,23 +1210,83 @@ impl Config {
         keys
     }
 
-    pub fn get_rules(&self) -> Vec<WMRule> {
+    pub fn key_mode(&self, name: &str) -> Option<&KeyModeConfig> {
         self.inner
-            .rules
+            .keybindings
+            .modes
             .iter()
-            .map(|rule| {
-                WMRule::new(
-                    rule.class.clone(),
-                    rule.instance.clone(),
-                    rule.name.clone(),
-                    rule.tags_mask,
-                    rule.is_floating,
-                    rule.monitor,
-                )
+            .find(|mode| mode.name == name)
+    }
+
+    /// 指定模式下生效的按键，不含默认模式的绑定
+    pub fn get_mode_keys(&self, name: &str) -> Option<Vec<WMKey>> {
+        self.key_mode(name)
+            .map(|mode| self.convert_key_set(&mode.keys, &mode.chords))
+    }
+
+    fn convert_key_set(&self, keys: &[KeyConfig], chords: &[ChordConfig]) -> Vec<WMKey> {
+        keys.iter()
+            .filter_map(|key| self.convert_key_config(key))
+            .chain(
+                chords
+                    .iter()
+                    .filter_map(|chord| self.convert_chord_config(chord)),
+            )
+            .collect()
+    }
+
+    pub fn get_rules(&self) -> &[WMRule] {
+        &self.rules
+    }
+
+    // 编译规则中的匹配模式，只在加载配置时执行一次
+    fn compile_rules(rules: &[RuleConfig]) -> Vec<WMRule> {
+        rules
+            .iter()
+            .map(|rule| WMRule {
+                class: RulePattern::new(rule.match_mode, &rule.class),
+                instance: RulePattern::new(rule.match_mode, &rule.instance),
+                name: RulePattern::new(rule.match_mode, &rule.name),
+                role: RulePattern::new(rule.match_mode, &rule.role),
+                window_type: rule.window_type.to_lowercase(),
+                tags: rule.tags_mask,
+                is_floating: rule.is_floating,
+                is_sticky: rule.is_sticky,
+                is_terminal: rule.is_terminal,
+                no_swallow: rule.no_swallow,
+                monitor: rule.monitor,
+                geometry: rule.geometry.clone(),
+                center: rule.center,
+                fullscreen: rule.fullscreen,
+                no_focus: rule.no_focus,
+                border_width: rule.border_width,
+                opacity: rule.opacity,
+                switch_to_tag: rule.switch_to_tag,
             })
             .collect()
     }
 
+    pub fn scratchpads(&self) -> &[ScratchpadConfig] {
+        &self.inner.scratchpads
+    }
+
+    pub fn scratchpad(&self, name: &str) -> Option<&ScratchpadConfig> {
+        self.inner.scratchpads.iter().find(|sp| sp.name == name)
+    }
+
+    // 输出名优先，其次是只指定了序号的条目
+    pub fn monitor_config(&self, output: &str, index: usize) -> Option<&MonitorConfig> {
+        let monitors = &self.inner.monitors;
+        monitors
+            .iter()
+            .find(|m| !output.is_empty() && m.output.as_deref() == Some(output))
+            .or_else(|| {
+                monitors
+                    .iter()
+                    .find(|m| m.output.is_none() && m.index == Some(index))
+            })
+    }
+
     pub fn get_dmenucmd(&self) -> Vec<String> {
         // 从配置中查找 dmenu 命令，或使用默认值
         self.inner
@@ -639,192 +1313,158 @@ impl Config {
     }
 
     fn convert_button_config(&self, btn_config: &ButtonConfig) -> Option<WMButton> {
-        let click_type = self.parse_click_type(&btn_config.click_type)?;
+        let click_type = self.parse_click_type(&btn_config.click_type);
+        let lookup = self.lookup_function(&btn_config.function);
+        let (click_type, (function, kind)) = match (click_type, lookup) {
+            (Some(click_type), Some(lookup)) => (click_type, lookup),
+            _ => {
+                eprintln!(
+                    "Ignoring button binding {} on {}: unknown click type or function",
+                    btn_config.function, btn_config.click_type
+                );
+                return None;
+            }
+        };
         let modifiers = self.parse_modifiers(&btn_config.modifier);
         let button = MouseButton::from_u8(btn_config.button as u8);
-        let function = self.parse_function(&btn_config.function)?;
-        let arg = self.convert_argument(&btn_config.argument);
+        let arg = self.convert_argument(&btn_config.argument, kind);
 
-        Some(WMButton::new(
-            click_type,
-            modifiers,
-            button,
-            Some(function),
-            arg,
-        ))
+        Some(
+            WMButton::new(click_type, modifiers, button, Some(function), arg)
+                .with_release(btn_config.on == TriggerEvent::Release),
+        )
     }
 
     fn parse_click_type(&self, click_type: &str) -> Option<WMClickType> {
         match click_type {
             "ClkClientWin" => Some(WMClickType::ClickClientWin),
             "ClkRootWin" => Some(WMClickType::ClickRootWin),
-            _ => {
-                eprintln!("Unknown click type: {}", click_type);
-                None
-            }
+            _ => None,
+        }
+    }
+
+    /// 按函数名和参数解析一次外部调用（IPC），检查规则与配置文件相同
+    pub fn resolve_function(
+        &self,
+        func_name: &str,
+        arg: &ArgumentConfig,
+    ) -> Result<(WMFuncType, jwm::WMArgEnum), String> {
+        let (func, kind) = self
+            .lookup_function(func_name)
+            .ok_or_else(|| format!("unknown function '{}'", func_name))?;
+        if let Some(message) = self.check_argument(kind, arg) {
+            return Err(format!("{}: {}", func_name, message));
         }
+        Ok((func, self.convert_argument(arg, kind)))
     }
 
-    // 扩展 parse_function 以支持更多函数
     fn parse_function(&self, func_name: &str) -> Option<WMFuncType> {
+        self.lookup_function(func_name).map(|(func, _)| func)
+    }
+
+    // 函数名 -> (函数, 期望的参数类型)
+    fn lookup_function(&self, func_name: &str) -> Option<(WMFuncType, ArgumentKind)> {
         match func_name {
             // 窗口管理
-            "spawn" => Some(Jwm::spawn),
-            "focusstack" => Some(Jwm::focusstack),
-            "focusmon" => Some(Jwm::focusmon),
-            "take_screenshot" => Some(Jwm::take_screenshot),
-            "quit" => Some(Jwm::quit),
-            "restart" => Some(Jwm::restart),
-            "killclient" => Some(Jwm::killclient),
-            "zoom" => Some(Jwm::zoom),
+            "spawn" => Some((Jwm::spawn, ArgumentKind::Strings)),
+            "focusstack" => Some((Jwm::focusstack, ArgumentKind::Int)),
+            "focusmon" => Some((Jwm::focusmon, ArgumentKind::Int)),
+            "take_screenshot" => Some((Jwm::take_screenshot, ArgumentKind::Ignored)),
+            "volume" => Some((Jwm::volume, ArgumentKind::Int)),
+            "quit" => Some((Jwm::quit, ArgumentKind::Ignored)),
+            "restart" => Some((Jwm::restart, ArgumentKind::Ignored)),
+            "reload" => Some((Jwm::reload, ArgumentKind::Int)),
+            "killclient" => Some((Jwm::killclient, ArgumentKind::Ignored)),
+            "zoom" => Some((Jwm::zoom, ArgumentKind::Ignored)),
+            "togglescratch" => Some((Jwm::togglescratch, ArgumentKind::Strings)),
+            "mode" => Some((Jwm::mode, ArgumentKind::Mode)),
 
             // 布局相关
-            "setlayout" => Some(Jwm::setlayout),
-            "togglefloating" => Some(Jwm::togglefloating),
-            "togglefullscr" => Some(Jwm::togglefullscr),
-            "togglebar" => Some(Jwm::togglebar),
-            "setmfact" => Some(Jwm::setmfact),
-            "setcfact" => Some(Jwm::setcfact),
-            "incnmaster" => Some(Jwm::incnmaster),
-            "movestack" => Some(Jwm::movestack),
+            "setlayout" => Some((Jwm::setlayout, ArgumentKind::Layout)),
+            "togglefloating" => Some((Jwm::togglefloating, ArgumentKind::Ignored)),
+            "togglesticky" => Some((Jwm::togglesticky, ArgumentKind::Ignored)),
+            "togglefullscr" => Some((Jwm::togglefullscr, ArgumentKind::Ignored)),
+            "togglebar" => Some((Jwm::togglebar, ArgumentKind::Ignored)),
+            "setmfact" => Some((Jwm::setmfact, ArgumentKind::Float)),
+            "setcfact" => Some((Jwm::setcfact, ArgumentKind::Float)),
+            "incnmaster" => Some((Jwm::incnmaster, ArgumentKind::Int)),
+            "movestack" => Some((Jwm::movestack, ArgumentKind::Int)),
+            "incrgaps" => Some((Jwm::incrgaps, ArgumentKind::Int)),
+            "incrigaps" => Some((Jwm::incrigaps, ArgumentKind::Int)),
+            "incrogaps" => Some((Jwm::incrogaps, ArgumentKind::Int)),
+            "incrihgaps" => Some((Jwm::incrihgaps, ArgumentKind::Int)),
+            "incrivgaps" => Some((Jwm::incrivgaps, ArgumentKind::Int)),
+            "incrohgaps" => Some((Jwm::incrohgaps, ArgumentKind::Int)),
+            "incrovgaps" => Some((Jwm::incrovgaps, ArgumentKind::Int)),
+            "togglegaps" => Some((Jwm::togglegaps, ArgumentKind::Ignored)),
+            "defaultgaps" => Some((Jwm::defaultgaps, ArgumentKind::Ignored)),
 
             // 标签相关
-            "view" => Some(Jwm::view),
-            "tag" => Some(Jwm::tag),
-            "toggleview" => Some(Jwm::toggleview),
-            "toggletag" => Some(Jwm::toggletag),
-            "tagmon" => Some(Jwm::tagmon),
-            "loopview" => Some(Jwm::loopview),
+            "view" => Some((Jwm::view, ArgumentKind::TagMask)),
+            "tag" => Some((Jwm::tag, ArgumentKind::TagMask)),
+            "toggleview" => Some((Jwm::toggleview, ArgumentKind::TagMask)),
+            "toggletag" => Some((Jwm::toggletag, ArgumentKind::TagMask)),
+            "tagmon" => Some((Jwm::tagmon, ArgumentKind::Int)),
+            "tagallmon" => Some((Jwm::tagallmon, ArgumentKind::Int)),
+            "swapmon" => Some((Jwm::swapmon, ArgumentKind::Int)),
+            "greedyview" => Some((Jwm::greedyview, ArgumentKind::TagMask)),
+            "loopview" => Some((Jwm::loopview, ArgumentKind::Int)),
 
             // 鼠标相关
-            "movemouse" => Some(Jwm::movemouse),
-            "resizemouse" => Some(Jwm::resizemouse),
+            "movemouse" => Some((Jwm::movemouse, ArgumentKind::Ignored)),
+            "resizemouse" => Some((Jwm::resizemouse, ArgumentKind::Ignored)),
 
-            _ => {
-                eprintln!("Unknown function: {}", func_name);
-                None
-            }
+            _ => None,
         }
     }
 
+    // 按 xkbcommon 的 keysym 名解析（如 "Return"、"minus"、"XF86AudioRaiseVolume"）；
+    // 先精确匹配，失败后再忽略大小写匹配，以保证 "a" 与 "A" 仍能区分
     fn parse_keysym(&self, key: &str) -> Option<KeySym> {
-        let ks: KeySym = match key {
-            // 特殊键
-            "Return" => k::KEY_Return,
-            "Tab" => k::KEY_Tab,
-            "space" => k::KEY_space,
-            "Page_Up" => k::KEY_Page_Up,
-            "Page_Down" => k::KEY_Page_Down,
-            "comma" => k::KEY_comma,
-            "period" => k::KEY_period,
-
-            // 字母键
-            "a" => k::KEY_a,
-            "b" => k::KEY_b,
-            "c" => k::KEY_c,
-            "d" => k::KEY_d,
-            "e" => k::KEY_e,
-            "f" => k::KEY_f,
-            "g" => k::KEY_g,
-            "h" => k::KEY_h,
-            "i" => k::KEY_i,
-            "j" => k::KEY_j,
-            "k" => k::KEY_k,
-            "l" => k::KEY_l,
-            "m" => k::KEY_m,
-            "n" => k::KEY_n,
-            "o" => k::KEY_o,
-            "p" => k::KEY_p,
-            "q" => k::KEY_q,
-            "r" => k::KEY_r,
-            "s" => k::KEY_s,
-            "t" => k::KEY_t,
-            "u" => k::KEY_u,
-            "v" => k::KEY_v,
-            "w" => k::KEY_w,
-            "x" => k::KEY_x,
-            "y" => k::KEY_y,
-            "z" => k::KEY_z,
-
-            // 数字键
-            "0" => k::KEY_0,
-            "1" => k::KEY_1,
-            "2" => k::KEY_2,
-            "3" => k::KEY_3,
-            "4" => k::KEY_4,
-            "5" => k::KEY_5,
-            "6" => k::KEY_6,
-            "7" => k::KEY_7,
-            "8" => k::KEY_8,
-            "9" => k::KEY_9,
-
-            // 功能键
-            "F1" => k::KEY_F1,
-            "F2" => k::KEY_F2,
-            "F3" => k::KEY_F3,
-            "F4" => k::KEY_F4,
-            "F5" => k::KEY_F5,
-            "F6" => k::KEY_F6,
-            "F7" => k::KEY_F7,
-            "F8" => k::KEY_F8,
-            "F9" => k::KEY_F9,
-            "F10" => k::KEY_F10,
-            "F11" => k::KEY_F11,
-            "F12" => k::KEY_F12,
-
-            // 方向键
-            "Left" => k::KEY_Left,
-            "Right" => k::KEY_Right,
-            "Up" => k::KEY_Up,
-            "Down" => k::KEY_Down,
-
-            // 其他常用键
-            "Escape" => k::KEY_Escape,
-            "BackSpace" => k::KEY_BackSpace,
-            "Delete" => k::KEY_Delete,
-            "Home" => k::KEY_Home,
-            "End" => k::KEY_End,
-            _ => {
-                eprintln!("Unknown key: {}", key);
-                return None;
-            }
-        };
-        Some(ks)
+        if key.is_empty() || key.contains('\0') {
+            return None;
+        }
+        [k::KEYSYM_NO_FLAGS, k::KEYSYM_CASE_INSENSITIVE]
+            .into_iter()
+            .map(|flags| k::keysym_from_name(key, flags).raw())
+            .find(|&ks| ks != k::KEY_NoSymbol)
     }
 
-    fn parse_modifiers(&self, modifiers: &[String]) -> Mods {
-        let mut mask = Mods::empty();
-        for modifier in modifiers {
-            match modifier.as_str() {
-                "Mod1" | "Alt" => mask |= Mods::ALT,
-                "Mod2" => mask |= Mods::MOD2,
-                "Mod3" => mask |= Mods::MOD3,
-                "Mod4" | "Super" | "Win" => mask |= Mods::SUPER,
-                "Mod5" => mask |= Mods::MOD5,
-                "Control" | "Ctrl" => mask |= Mods::CONTROL,
-                "Shift" => mask |= Mods::SHIFT,
-                "Lock" | "CapsLock" => mask |= Mods::CAPS,
-                _ => {
-                    eprintln!("Unknown modifier: {}", modifier);
-                }
-            };
+    fn parse_modifier(&self, modifier: &str) -> Option<Mods> {
+        match modifier {
+            "Mod1" | "Alt" => Some(Mods::ALT),
+            "Mod2" => Some(Mods::MOD2),
+            "Mod3" => Some(Mods::MOD3),
+            "Mod4" | "Super" | "Win" => Some(Mods::SUPER),
+            "Mod5" => Some(Mods::MOD5),
+            "Control" | "Ctrl" => Some(Mods::CONTROL),
+            "Shift" => Some(Mods::SHIFT),
+            "Lock" | "CapsLock" => Some(Mods::CAPS),
+            _ => None,
         }
-        mask
+    }
+
+    fn parse_modifiers(&self, modifiers: &[String]) -> Mods {
+        modifiers
+            .iter()
+            .filter_map(|m| self.parse_modifier(m))
+            .fold(Mods::empty(), |mask, m| mask | m)
     }
 
     // 扩展 convert_argument 以支持布局参数
-    fn convert_argument(&self, arg: &ArgumentConfig) -> jwm::WMArgEnum {
+    fn convert_argument(&self, arg: &ArgumentConfig, kind: ArgumentKind) -> jwm::WMArgEnum {
         match arg {
+            ArgumentConfig::Int(i) if kind == ArgumentKind::TagMask && *i >= 0 => {
+                jwm::WMArgEnum::UInt(*i as u32)
+            }
             ArgumentConfig::Int(i) => jwm::WMArgEnum::Int(*i),
             ArgumentConfig::UInt(u) => jwm::WMArgEnum::UInt(*u),
             ArgumentConfig::Float(f) => jwm::WMArgEnum::Float(*f),
             ArgumentConfig::StringVec(v) => jwm::WMArgEnum::StringVec(v.clone()),
             ArgumentConfig::String(s) => {
-                // 特殊处理布局字符串
-                match s.as_str() {
-                    "tile" => jwm::WMArgEnum::Layout(Rc::new(LayoutEnum::TILE)),
-                    "float" => jwm::WMArgEnum::Layout(Rc::new(LayoutEnum::FLOAT)),
-                    "monocle" => jwm::WMArgEnum::Layout(Rc::new(LayoutEnum::MONOCLE)),
+                // 布局函数按注册名查找布局，其余作为单元素字符串数组
+                match LAYOUTS.get(s) {
+                    Some(layout) if kind == ArgumentKind::Layout => jwm::WMArgEnum::Layout(layout),
                     _ => jwm::WMArgEnum::StringVec(vec![s.clone()]),
                 }
             }
@@ -838,6 +1478,7 @@ impl Config {
                 click_type: "ClkClientWin".to_string(),
                 modifier: vec![self.inner.keybindings.modkey.clone()],
                 button: 1,
+                on: TriggerEvent::Press,
                 function: "movemouse".to_string(),
                 argument: ArgumentConfig::Int(0),
             },
@@ -845,6 +1486,7 @@ impl Config {
                 click_type: "ClkClientWin".to_string(),
                 modifier: vec![self.inner.keybindings.modkey.clone()],
                 button: 2,
+                on: TriggerEvent::Press,
                 function: "togglefloating".to_string(),
                 argument: ArgumentConfig::Int(0),
             },
@@ -852,9 +1494,42 @@ impl Config {
                 click_type: "ClkClientWin".to_string(),
                 modifier: vec![self.inner.keybindings.modkey.clone()],
                 button: 3,
+                on: TriggerEvent::Press,
                 function: "resizemouse".to_string(),
                 argument: ArgumentConfig::Int(0),
             },
+            ButtonConfig {
+                click_type: "ClkClientWin".to_string(),
+                modifier: vec![self.inner.keybindings.modkey.clone()],
+                button: 4,
+                on: TriggerEvent::Press,
+                function: "loopview".to_string(),
+                argument: ArgumentConfig::Int(-1),
+            },
+            ButtonConfig {
+                click_type: "ClkClientWin".to_string(),
+                modifier: vec![self.inner.keybindings.modkey.clone()],
+                button: 5,
+                on: TriggerEvent::Press,
+                function: "loopview".to_string(),
+                argument: ArgumentConfig::Int(1),
+            },
+            ButtonConfig {
+                click_type: "ClkRootWin".to_string(),
+                modifier: vec![self.inner.keybindings.modkey.clone()],
+                button: 4,
+                on: TriggerEvent::Press,
+                function: "loopview".to_string(),
+                argument: ArgumentConfig::Int(-1),
+            },
+            ButtonConfig {
+                click_type: "ClkRootWin".to_string(),
+                modifier: vec![self.inner.keybindings.modkey.clone()],
+                button: 5,
+                on: TriggerEvent::Press,
+                function: "loopview".to_string(),
+                argument: ArgumentConfig::Int(1),
+            },
         ]
     }
 
@@ -872,25 +1547,58 @@ impl Config {
     }
 
     fn convert_key_config(&self, key_config: &KeyConfig) -> Option<WMKey> {
+        let keysym = self.parse_keysym(&key_config.key);
+        let lookup = self.lookup_function(&key_config.function);
+        let (keysym, (function, kind)) = match (keysym, lookup) {
+            (Some(keysym), Some(lookup)) => (keysym, lookup),
+            _ => {
+                eprintln!(
+                    "Ignoring key binding {} -> {}: unknown key or function",
+                    key_config.key, key_config.function
+                );
+                return None;
+            }
+        };
         let modifiers = self.parse_modifiers(&key_config.modifier);
-        let keysym = self.parse_keysym(&key_config.key)?;
-        let function = self.parse_function(&key_config.function)?;
-        let arg = self.convert_argument(&key_config.argument);
+        let arg = self.convert_argument(&key_config.argument, kind);
 
-        Some(WMKey::new(modifiers, keysym, Some(function), arg))
+        Some(
+            WMKey::new(modifiers, keysym, Some(function), arg)
+                .with_release(key_config.on == TriggerEvent::Release),
+        )
+    }
+
+    fn convert_chord_config(&self, chord: &ChordConfig) -> Option<WMKey> {
+        let steps: Option<Vec<(Mods, KeySym)>> = chord
+            .keys
+            .iter()
+            .map(|step| {
+                self.parse_keysym(&step.key)
+                    .map(|keysym| (self.parse_modifiers(&step.modifier), keysym))
+            })
+            .collect();
+        let lookup = self.lookup_function(&chord.function);
+        match (steps, lookup) {
+            (Some(steps), Some((function, kind))) if !steps.is_empty() => {
+                let (mask, keysym) = steps[0];
+                let arg = self.convert_argument(&chord.argument, kind);
+                Some(WMKey::new(mask, keysym, Some(function), arg).with_chord(steps[1..].to_vec()))
+            }
+            _ => {
+                eprintln!(
+                    "Ignoring key chord -> {}: empty sequence, unknown key or function",
+                    chord.function
+                );
+                None
+            }
+        }
     }
 
+    // 前 9 个 tag 对应数字键 1-9（0 用于全标签视图），之后依次对应 F1、F2……
     fn generate_tag_keys(&self, tag: usize) -> Vec<WMKey> {
         let key = match tag {
-            0 => k::KEY_1,
-            1 => k::KEY_2,
-            2 => k::KEY_3,
-            3 => k::KEY_4,
-            4 => k::KEY_5,
-            5 => k::KEY_6,
-            6 => k::KEY_7,
-            7 => k::KEY_8,
-            8 => k::KEY_9,
+            0..=8 => k::KEY_1 + tag as u32,
+            9..=31 => k::KEY_F1 + (tag - 9) as u32,
             _ => return vec![],
         };
 
@@ -918,6 +1626,11 @@ impl Config {
         ]
     }
 
+    /// 当前配置的 JSON 表示（供 IPC get_config 使用）
+    pub fn to_json(&self) -> Result<serde_json::Value, serde_json::Error> {
+        serde_json::to_value(&self.inner)
+    }
+
     /// 保存当前配置到指定文件
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), ConfigError> {
         let toml_string =
@@ -992,9 +1705,511 @@ impl Config {
         Ok(())
     }
 
+    /// 校验配置，警告以外的任何问题都视为无效
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let messages: Vec<String> = self
+            .issues()
+            .iter()
+            .filter(|i| !i.warning)
+            .map(|i| i.to_string())
+            .collect();
+        if messages.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(messages.join("; ")))
+        }
+    }
+
+    /// 收集配置中的所有问题
+    pub fn issues(&self) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+        self.check_keys(&mut issues);
+        self.check_buttons(&mut issues);
+        self.check_colors(&mut issues);
+        self.check_layout(&mut issues);
+        self.check_rules(&mut issues);
+        self.check_monitors(&mut issues);
+        self.check_scratchpads(&mut issues);
+        issues
+    }
+
+    fn check_modifiers(&self, path: &str, modifiers: &[String], issues: &mut Vec<ConfigIssue>) {
+        for (i, modifier) in modifiers.iter().enumerate() {
+            if self.parse_modifier(modifier).is_none() {
+                issues.push(ConfigIssue::new(
+                    format!("{}.modifier[{}]", path, i),
+                    format!("unknown modifier '{}'", modifier),
+                ));
+            }
+        }
+    }
+
+    // 检查参数类型是否符合函数要求，以及标签掩码是否越界
+    fn check_argument(&self, kind: ArgumentKind, arg: &ArgumentConfig) -> Option<String> {
+        let mismatch = |expected: &str| {
+            Some(format!(
+                "expects {} argument, got {}",
+                expected,
+                arg.type_name()
+            ))
+        };
+        match (kind, arg) {
+            (ArgumentKind::Ignored, _) => None,
+            (ArgumentKind::Int, ArgumentConfig::Int(_)) => None,
+            (ArgumentKind::Int, _) => mismatch("an integer"),
+            (ArgumentKind::TagMask, ArgumentConfig::Int(v)) if *v < 0 => {
+                Some(format!("tag mask {} is negative", v))
+            }
+            (ArgumentKind::TagMask, ArgumentConfig::Int(v)) => self.check_tag_mask(*v as u64),
+            (ArgumentKind::TagMask, ArgumentConfig::UInt(v)) => self.check_tag_mask(*v as u64),
+            (ArgumentKind::TagMask, _) => mismatch("a tag mask (integer)"),
+            (ArgumentKind::Float, ArgumentConfig::Float(_)) => None,
+            (ArgumentKind::Float, _) => mismatch("a float (e.g. 0.05)"),
+            (ArgumentKind::Strings, ArgumentConfig::String(v)) if v.is_empty() => {
+                Some("empty string argument".to_string())
+            }
+            (ArgumentKind::Strings, ArgumentConfig::StringVec(v)) if v.is_empty() => {
+                Some("empty string array argument".to_string())
+            }
+            (ArgumentKind::Strings, ArgumentConfig::String(_) | ArgumentConfig::StringVec(_)) => {
+                None
+            }
+            (ArgumentKind::Strings, _) => mismatch("a string or string array"),
+            (ArgumentKind::Layout, ArgumentConfig::String(name)) if LAYOUTS.get(name).is_none() => {
+                Some(format!("unknown layout '{}'", name))
+            }
+            (ArgumentKind::Layout, _) => None,
+            (ArgumentKind::Mode, ArgumentConfig::String(name)) => self.check_mode_name(name),
+            (ArgumentKind::Mode, ArgumentConfig::StringVec(names)) => match names.first() {
+                Some(name) => self.check_mode_name(name),
+                None => Some("empty string array argument".to_string()),
+            },
+            (ArgumentKind::Mode, _) => mismatch("a mode name (string)"),
+        }
+    }
+
+    fn check_mode_name(&self, name: &str) -> Option<String> {
+        if name == "default" || self.key_mode(name).is_some() {
+            None
+        } else {
+            Some(format!("unknown mode '{}'", name))
+        }
+    }
+
+    fn check_tag_mask(&self, mask: u64) -> Option<String> {
+        // u32::MAX 表示全部标签
+        if mask == u32::MAX as u64 {
+            return None;
+        }
+        if mask == 0 {
+            return Some("tag mask is 0".to_string());
+        }
+        if mask & !(self.tagmask() as u64) != 0 {
+            return Some(format!(
+                "tag mask {:#x} is out of range for {} tags",
+                mask,
+                self.tags_length()
+            ));
+        }
+        None
+    }
+
+    fn check_keys(&self, issues: &mut Vec<ConfigIssue>) {
+        // 默认模式包含自动生成的标签键
+        let mut bound = Vec::new();
+        for tag in 0..self.tags_length() {
+            for key in self.generate_tag_keys(tag) {
+                bound.push((
+                    vec![(key.mask.bits(), key.key_sym)],
+                    format!("the generated key for tag {}", self.tag_name(tag)),
+                ));
+            }
+        }
+        let keybindings = &self.inner.keybindings;
+        self.check_key_set(
+            "keybindings",
+            &keybindings.keys,
+            &keybindings.chords,
+            bound,
+            issues,
+        );
+
+        let mut names: HashMap<&str, String> = HashMap::new();
+        for (i, mode) in keybindings.modes.iter().enumerate() {
+            let path = format!("keybindings.modes[{}]", i);
+            if mode.name.is_empty() || mode.name == "default" {
+                issues.push(ConfigIssue::new(
+                    format!("{}.name", path),
+                    format!("mode name '{}' is reserved", mode.name),
+                ));
+            } else if let Some(previous) = names.get(mode.name.as_str()) {
+                issues.push(ConfigIssue::new(
+                    format!("{}.name", path),
+                    format!(
+                        "duplicate mode '{}', already defined by {}",
+                        mode.name, previous
+                    ),
+                ));
+            } else {
+                names.insert(&mode.name, path.clone());
+            }
+            self.check_key_set(&path, &mode.keys, &mode.chords, Vec::new(), issues);
+        }
+    }
+
+    // 检查一组按键绑定；bound 为已占用的按键序列及其来源
+    fn check_key_set(
+        &self,
+        path: &str,
+        keys: &[KeyConfig],
+        chords: &[ChordConfig],
+        mut bound: Vec<(Vec<(u16, KeySym)>, String)>,
+        issues: &mut Vec<ConfigIssue>,
+    ) {
+        // 松开绑定与按下绑定互不冲突，单独记录
+        let mut released = Vec::new();
+        for (i, key) in keys.iter().enumerate() {
+            let key_path = format!("{}.keys[{}]", path, i);
+            self.check_modifiers(&key_path, &key.modifier, issues);
+            self.check_function(&key_path, &key.function, &key.argument, issues);
+
+            match self.parse_keysym(&key.key) {
+                Some(keysym) => {
+                    if key.on == TriggerEvent::Release && jwm::is_modifier_keysym(keysym) {
+                        issues.push(ConfigIssue::warning(
+                            format!("{}.key", key_path),
+                            format!(
+                                "release binding on modifier '{}' grabs it, so while it is held \
+                                 combinations not bound in jwm never reach applications",
+                                key.key
+                            ),
+                        ));
+                    }
+                    let sequence = vec![(self.parse_modifiers(&key.modifier).bits(), keysym)];
+                    let bound = match key.on {
+                        TriggerEvent::Press => &mut bound,
+                        TriggerEvent::Release => &mut released,
+                    };
+                    self.check_sequence_conflict(&key_path, "key", sequence, bound, issues);
+                }
+                None => issues.push(ConfigIssue::new(
+                    format!("{}.key", key_path),
+                    unknown_key_message(&key.key),
+                )),
+            }
+        }
+
+        for (i, chord) in chords.iter().enumerate() {
+            let chord_path = format!("{}.chords[{}]", path, i);
+            self.check_function(&chord_path, &chord.function, &chord.argument, issues);
+            if chord.keys.is_empty() {
+                issues.push(ConfigIssue::new(
+                    format!("{}.keys", chord_path),
+                    "key chord has no keys".to_string(),
+                ));
+                continue;
+            }
+
+            let mut sequence = Some(Vec::new());
+            for (j, step) in chord.keys.iter().enumerate() {
+                let step_path = format!("{}.keys[{}]", chord_path, j);
+                self.check_modifiers(&step_path, &step.modifier, issues);
+                match self.parse_keysym(&step.key) {
+                    Some(keysym) => {
+                        if let Some(sequence) = sequence.as_mut() {
+                            sequence.push((self.parse_modifiers(&step.modifier).bits(), keysym));
+                        }
+                    }
+                    None => {
+                        issues.push(ConfigIssue::new(
+                            format!("{}.key", step_path),
+                            unknown_key_message(&step.key),
+                        ));
+                        sequence = None;
+                    }
+                }
+            }
+            if let Some(sequence) = sequence {
+                self.check_sequence_conflict(&chord_path, "keys", sequence, &mut bound, issues);
+            }
+        }
+    }
+
+    // 相同序列为重复绑定；一个序列是另一个的前缀时，较长的那个永远不会触发
+    fn check_sequence_conflict(
+        &self,
+        path: &str,
+        field: &str,
+        sequence: Vec<(u16, KeySym)>,
+        bound: &mut Vec<(Vec<(u16, KeySym)>, String)>,
+        issues: &mut Vec<ConfigIssue>,
+    ) {
+        for (other, owner) in bound.iter() {
+            let n = other.len().min(sequence.len());
+            let message = if *other == sequence {
+                format!("duplicate binding, already bound by {}", owner)
+            } else if other[..n] == sequence[..n] {
+                format!(
+                    "conflicts with {}: one key sequence is a prefix of the other, \
+                     so the longer one can never fire",
+                    owner
+                )
+            } else {
+                continue;
+            };
+            issues.push(ConfigIssue::new(format!("{}.{}", path, field), message));
+            return;
+        }
+        bound.push((sequence, path.to_string()));
+    }
+
+    fn check_function(
+        &self,
+        path: &str,
+        function: &str,
+        argument: &ArgumentConfig,
+        issues: &mut Vec<ConfigIssue>,
+    ) {
+        match self.lookup_function(function) {
+            Some((_, kind)) => {
+                if let Some(message) = self.check_argument(kind, argument) {
+                    issues.push(ConfigIssue::new(
+                        format!("{}.argument", path),
+                        format!("{}: {}", function, message),
+                    ));
+                }
+            }
+            None => issues.push(ConfigIssue::new(
+                format!("{}.function", path),
+                format!("unknown function '{}'", function),
+            )),
+        }
+    }
+
+    fn check_buttons(&self, issues: &mut Vec<ConfigIssue>) {
+        let mut bound: HashMap<(String, u16, u8, TriggerEvent), String> = HashMap::new();
+        for (i, button) in self.inner.mouse_bindings.buttons.iter().enumerate() {
+            let path = format!("mouse_bindings.buttons[{}]", i);
+            if self.parse_click_type(&button.click_type).is_none() {
+                issues.push(ConfigIssue::new(
+                    format!("{}.click_type", path),
+                    format!("unknown click type '{}'", button.click_type),
+                ));
+            }
+            if !(1..=MAX_MOUSE_BUTTON).contains(&button.button) {
+                issues.push(ConfigIssue::new(
+                    format!("{}.button", path),
+                    format!(
+                        "button {} out of range 1..={} (4-7 are the scroll wheel)",
+                        button.button, MAX_MOUSE_BUTTON
+                    ),
+                ));
+            }
+            self.check_modifiers(&path, &button.modifier, issues);
+            self.check_function(&path, &button.function, &button.argument, issues);
+            // 拖动类函数依赖按下时开始的指针抓取
+            if button.on == TriggerEvent::Release
+                && matches!(button.function.as_str(), "movemouse" | "resizemouse")
+            {
+                issues.push(ConfigIssue::new(
+                    format!("{}.on", path),
+                    format!("{} can only be bound on press", button.function),
+                ));
+            }
+
+            let combo = (
+                button.click_type.clone(),
+                self.parse_modifiers(&button.modifier).bits(),
+                button.button,
+                button.on,
+            );
+            match bound.get(&combo) {
+                Some(previous) => issues.push(ConfigIssue::new(
+                    format!("{}.button", path),
+                    format!("duplicate binding, already bound by {}", previous),
+                )),
+                None => {
+                    bound.insert(combo, path);
+                }
+            }
+        }
+    }
+
+    fn check_colors(&self, issues: &mut Vec<ConfigIssue>) {
+        let colors = &self.inner.colors;
+        for (name, hex) in [
+            ("dark_sea_green1", &colors.dark_sea_green1),
+            ("dark_sea_green2", &colors.dark_sea_green2),
+            ("pale_turquoise1", &colors.pale_turquoise1),
+            ("light_sky_blue1", &colors.light_sky_blue1),
+            ("grey84", &colors.grey84),
+            ("cyan", &colors.cyan),
+            ("black", &colors.black),
+            ("white", &colors.white),
+        ] {
+            if ArgbColor::from_hex(hex, colors.opaque).is_err() {
+                issues.push(ConfigIssue::new(
+                    format!("colors.{}", name),
+                    format!("invalid color '{}'", hex),
+                ));
+            }
+        }
+    }
+
+    fn check_layout(&self, issues: &mut Vec<ConfigIssue>) {
+        let m_fact = self.inner.layout.m_fact;
+        if !(0.05..=0.95).contains(&m_fact) {
+            issues.push(ConfigIssue::new(
+                "layout.m_fact".to_string(),
+                format!("m_fact {} is out of range 0.05..=0.95", m_fact),
+            ));
+        }
+        if self.inner.tags.is_empty() && !(1..=MAX_TAGS).contains(&self.inner.layout.tags_length) {
+            issues.push(ConfigIssue::new(
+                "layout.tags_length".to_string(),
+                format!(
+                    "tags_length {} is out of range 1..={}",
+                    self.inner.layout.tags_length, MAX_TAGS
+                ),
+            ));
+        }
+        if self.inner.tags.len() > MAX_TAGS {
+            issues.push(ConfigIssue::new(
+                format!("tags[{}]", MAX_TAGS),
+                format!("at most {} tags are supported", MAX_TAGS),
+            ));
+        }
+    }
+
+    fn check_rules(&self, issues: &mut Vec<ConfigIssue>) {
+        for (i, (rule, compiled)) in self.inner.rules.iter().zip(&self.rules).enumerate() {
+            let path = format!("rules[{}]", i);
+            for (field, pattern, matcher) in [
+                ("class", &rule.class, &compiled.class),
+                ("instance", &rule.instance, &compiled.instance),
+                ("name", &rule.name, &compiled.name),
+                ("role", &rule.role, &compiled.role),
+            ] {
+                if matches!(matcher, RulePattern::Invalid) {
+                    issues.push(ConfigIssue::new(
+                        format!("{}.{}", path, field),
+                        format!("invalid pattern '{}', rule can never match", pattern),
+                    ));
+                }
+            }
+
+            let window_type = rule.window_type.to_lowercase();
+            if !window_type.is_empty() && !WINDOW_TYPE_NAMES.contains(&window_type.as_str()) {
+                issues.push(ConfigIssue::new(
+                    format!("{}.window_type", path),
+                    format!(
+                        "unknown window type '{}', rule can never match",
+                        rule.window_type
+                    ),
+                ));
+            }
+
+            if rule.tags_mask as u64 & !(self.tagmask() as u64) != 0 {
+                issues.push(ConfigIssue::new(
+                    format!("{}.tags_mask", path),
+                    format!(
+                        "tag mask {:#x} is out of range for {} tags",
+                        rule.tags_mask,
+                        self.tags_length()
+                    ),
+                ));
+            }
+
+            if let Some(opacity) = rule.opacity {
+                if !(0.0..=1.0).contains(&opacity) {
+                    issues.push(ConfigIssue::new(
+                        format!("{}.opacity", path),
+                        format!("opacity {} is out of range 0.0..=1.0", opacity),
+                    ));
+                }
+            }
+        }
+    }
+
+    fn check_scratchpads(&self, issues: &mut Vec<ConfigIssue>) {
+        for (i, scratchpad) in self.inner.scratchpads.iter().enumerate() {
+            let path = format!("scratchpads[{}]", i);
+            if scratchpad.command.is_empty() {
+                issues.push(ConfigIssue::new(
+                    format!("{}.command", path),
+                    "command is empty".to_string(),
+                ));
+            }
+            if scratchpad.class.is_empty() && scratchpad.instance.is_empty() {
+                issues.push(ConfigIssue::new(
+                    path,
+                    "neither class nor instance is set, window can never be claimed".to_string(),
+                ));
+            }
+        }
+    }
+
+    fn check_monitors(&self, issues: &mut Vec<ConfigIssue>) {
+        for (i, monitor) in self.inner.monitors.iter().enumerate() {
+            let path = format!("monitors[{}]", i);
+            if monitor.output.is_none() && monitor.index.is_none() {
+                issues.push(ConfigIssue::new(
+                    path.clone(),
+                    "neither output nor index is set, entry can never match".to_string(),
+                ));
+            }
+
+            if let Some(name) = &monitor.layout {
+                if LAYOUTS.get(name).is_none() {
+                    issues.push(ConfigIssue::new(
+                        format!("{}.layout", path),
+                        format!("unknown layout '{}'", name),
+                    ));
+                }
+            }
+            for (j, name) in monitor.layouts.iter().enumerate() {
+                if LAYOUTS.get(name).is_none() {
+                    issues.push(ConfigIssue::new(
+                        format!("{}.layouts[{}]", path, j),
+                        format!("unknown layout '{}'", name),
+                    ));
+                }
+            }
+            if monitor.layouts.len() > self.tags_length() {
+                issues.push(ConfigIssue::new(
+                    format!("{}.layouts", path),
+                    format!(
+                        "{} layouts given for {} tags",
+                        monitor.layouts.len(),
+                        self.tags_length()
+                    ),
+                ));
+            }
+
+            if let Some(m_fact) = monitor.m_fact {
+                if !(0.05..=0.95).contains(&m_fact) {
+                    issues.push(ConfigIssue::new(
+                        format!("{}.m_fact", path),
+                        format!("m_fact {} is out of range 0.05..=0.95", m_fact),
+                    ));
+                }
+            }
+
+            if let Some(tag) = monitor.tag {
+                if !(1..=self.tags_length()).contains(&tag) {
+                    issues.push(ConfigIssue::new(
+                        format!("{}.tag", path),
+                        format!("tag {} is out of range 1..={}", tag, self.tags_length()),
+                    ));
+                }
+            }
+        }
+    }
+
     /// 合并配置（用于部分更新）
     pub fn merge_config(&mut self, other: TomlConfig) {
         // 这里可以实现选择性合并逻辑
+        self.rules = Self::compile_rules(&other.rules);
         self.inner = other;
     }
 
@@ -1004,6 +2219,7 @@ impl Config {
         if config_path.exists() {
             let new_config = Self::load_from_file(&config_path)?;
             self.inner = new_config.inner;
+            self.rules = new_config.rules;
         }
         Ok(())
     }
@@ -1027,6 +2243,7 @@ pub enum ConfigError {
     Io(std::io::Error),
     Parse(toml::de::Error),
     Serialize(toml::ser::Error),
+    Invalid(String),
 }
 
 impl fmt::Display for ConfigError {
@@ -1035,6 +2252,7 @@ impl fmt::Display for ConfigError {
             ConfigError::Io(err) => write!(f, "IO error: {}", err),
             ConfigError::Parse(err) => write!(f, "Parse error: {}", err),
             ConfigError::Serialize(err) => write!(f, "Serialize error: {}", err),
+            ConfigError::Invalid(msg) => write!(f, "Invalid config: {}", msg),
         }
     }
 }
@@ -1045,6 +2263,7 @@ impl std::error::Error for ConfigError {
             ConfigError::Io(err) => Some(err),
             ConfigError::Parse(err) => Some(err),
             ConfigError::Serialize(err) => Some(err),
+            ConfigError::Invalid(_) => None,
         }
     }
 }
@@ -1068,36 +2287,125 @@ impl From<toml::ser::Error> for ConfigError {
     }
 }
 
-// 全局配置实例
-pub static CONFIG: Lazy<Config> = Lazy::new(|| {
-    if !LOAD_LOCAL_CONFIG {
-        return Config::default();
+// 全局配置实例：热重载时整体替换，读取方持有的旧快照不受影响
+pub struct ConfigStore {
+    current: RwLock<Arc<Config>>,
+}
+
+impl ConfigStore {
+    fn new(config: Config) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(config)),
+        }
     }
 
-    // 加载配置
-    let config = Config::load_default();
-    // 生成配置文件模板（如果不存在）
-    if !Config::config_exists() {
-        Config::generate_template(Config::get_default_config_path()).unwrap();
-        println!(
-            "Generated default config file at: {:?}",
-            Config::get_default_config_path()
-        );
+    /// 获取当前配置快照
+    pub fn load(&self) -> Arc<Config> {
+        self.current
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// 替换当前配置
+    pub fn store(&self, config: Config) {
+        *self.current.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(config);
     }
 
-    // Usage test case:
-    // // 备份现有配置
-    // let backup_path = Config::backup_config(Config::get_default_config_path()).unwrap();
-    // println!("Backup created at: {:?}", backup_path);
-    // // 保存当前配置
-    // config.save_default().unwrap();
-    // println!("Configuration saved successfully!");
-    // // 验证配置文件
-    // Config::validate_config_file(Config::get_default_config_path()).unwrap();
-    // println!("Configuration file is valid!");
-    // // 重新加载配置
-    // config.reload().unwrap();
-    println!("Configuration reloaded!");
-
-    return config;
+    /// 重新读取并校验配置文件，出错时保留旧配置
+    pub fn reload_from<P: AsRef<Path>>(&self, path: P) -> Result<(), ConfigError> {
+        let config = Config::load_validated(path)?;
+        self.store(config);
+        Ok(())
+    }
+}
+
+pub static CONFIG: Lazy<ConfigStore> = Lazy::new(|| {
+    let config_path = Config::get_default_config_path();
+    if !config_path.exists() {
+        if GENERATE_CONFIG_TEMPLATE {
+            match Config::generate_template(&config_path) {
+                Ok(()) => println!("Generated default config file at: {:?}", config_path),
+                Err(e) => eprintln!("Failed to generate config template: {}", e),
+            }
+        }
+        return ConfigStore::new(Config::default());
+    }
+
+    // 启动时只有解析失败才回退到默认配置，其余问题仅报告，无效条目在使用时被忽略
+    match Config::load_from_file(&config_path) {
+        Ok(config) => {
+            for issue in config.issues() {
+                eprintln!("Config issue in {:?}: {}", config_path, issue);
+            }
+            ConfigStore::new(config)
+        }
+        Err(e) => {
+            eprintln!(
+                "Failed to load config {:?}: {}, using defaults",
+                config_path, e
+            );
+            ConfigStore::new(Config::default())
+        }
+    }
 });
+
+/// 监听配置文件所在目录，兼容“写临时文件再 rename”的保存方式；
+/// 配置文件是符号链接时同时监听链接目标所在目录
+pub struct ConfigWatcher {
+    inotify: Inotify,
+    // (监听的目录, 该目录下的配置文件名)
+    watches: Vec<(WatchDescriptor, OsString)>,
+}
+
+impl ConfigWatcher {
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let (dir, file_name) = match (path.parent(), path.file_name()) {
+            (Some(dir), Some(name)) => (dir, name.to_os_string()),
+            _ => {
+                return Err(ConfigError::Invalid(format!(
+                    "cannot watch config path {:?}",
+                    path
+                )))
+            }
+        };
+        fs::create_dir_all(dir)?;
+
+        let inotify = Inotify::init(InitFlags::IN_NONBLOCK | InitFlags::IN_CLOEXEC)
+            .map_err(std::io::Error::from)?;
+        let flags = AddWatchFlags::IN_CLOSE_WRITE | AddWatchFlags::IN_MOVED_TO;
+        let wd = inotify
+            .add_watch(dir, flags)
+            .map_err(std::io::Error::from)?;
+        let mut watches = vec![(wd, file_name)];
+
+        // 编辑符号链接指向的文件时，事件发生在目标所在目录
+        if let Ok(real_path) = fs::canonicalize(path) {
+            if let (Some(real_dir), Some(real_name)) = (real_path.parent(), real_path.file_name()) {
+                let wd = inotify
+                    .add_watch(real_dir, flags)
+                    .map_err(std::io::Error::from)?;
+                watches.push((wd, real_name.to_os_string()));
+            }
+        }
+
+        Ok(Self { inotify, watches })
+    }
+
+    /// 非阻塞地取出所有待处理事件，配置文件有变化时返回 true
+    pub fn changed(&self) -> bool {
+        let mut changed = false;
+        while let Ok(events) = self.inotify.read_events() {
+            if events.is_empty() {
+                break;
+            }
+            changed |= events.iter().any(|ev| {
+                self.watches
+                    .iter()
+                    .any(|(wd, name)| ev.wd == *wd && ev.name.as_deref() == Some(name.as_os_str()))
+            });
+        }
+        changed
+    }
+}
@@ -0,0 +1,271 @@
+// `jwm --check-config [path]`：检查配置文件并按行列报告所有问题
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use toml::de::{DeTable, DeValue};
+use toml::Spanned;
+
+use crate::config::{Config, ConfigError};
+
+/// 定位到源文件行列的问题
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+    pub warning: bool,
+}
+
+impl Diagnostic {
+    fn at(source: &str, offset: usize, message: String) -> Self {
+        let before = &source[..offset.min(source.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+        Self {
+            line,
+            column,
+            message,
+            warning: false,
+        }
+    }
+
+    fn from_toml_error(source: &str, err: &toml::de::Error) -> Self {
+        let offset = err.span().map_or(0, |span| span.start);
+        Self::at(source, offset, err.message().trim_end().to_string())
+    }
+}
+
+/// 检查配置文件，返回按位置排序的问题；读取失败时返回错误
+pub fn check_config_file<P: AsRef<Path>>(path: P) -> Result<Vec<Diagnostic>, ConfigError> {
+    let source = fs::read_to_string(path)?;
+
+    // 语法错误
+    let root = match DeTable::parse(&source) {
+        Ok(root) => root,
+        Err(e) => return Ok(vec![Diagnostic::from_toml_error(&source, &e)]),
+    };
+
+    // 字段缺失或类型不符
+    let config = match Config::from_toml_str(&source) {
+        Ok(config) => config,
+        Err(ConfigError::Parse(e)) => return Ok(vec![Diagnostic::from_toml_error(&source, &e)]),
+        Err(e) => return Err(e),
+    };
+
+    let mut diagnostics: Vec<Diagnostic> = config
+        .issues()
+        .into_iter()
+        .map(|issue| {
+            let span = find_span(&root, &issue.path);
+            Diagnostic {
+                warning: issue.warning,
+                ..Diagnostic::at(&source, span.start, issue.to_string())
+            }
+        })
+        .collect();
+    diagnostics.sort_by_key(|d| (d.line, d.column));
+    Ok(diagnostics)
+}
+
+// 按 `a.b[2].c` 形式的路径查找节点位置；字段缺失（使用默认值）时退回到最近的父节点
+fn find_span(root: &Spanned<DeTable<'_>>, path: &str) -> Range<usize> {
+    let mut span = root.span();
+    let mut current: Option<&DeValue<'_>> = None;
+
+    for part in path.split('.') {
+        let (key, index) = match part.split_once('[') {
+            Some((key, rest)) => (key, rest.trim_end_matches(']').parse::<usize>().ok()),
+            None => (part, None),
+        };
+        let table = match current {
+            None => Some(root.get_ref()),
+            Some(value) => value.as_table(),
+        };
+        let value = match table.and_then(|t| t.get(key)) {
+            Some(value) => value,
+            None => break,
+        };
+        span = value.span();
+        current = Some(value.get_ref());
+
+        if let Some(index) = index {
+            match value.get_ref().as_array().and_then(|a| a.get(index)) {
+                Some(item) => {
+                    span = item.span();
+                    current = Some(item.get_ref());
+                }
+                None => break,
+            }
+        }
+    }
+    span
+}
+
+/// 打印检查结果并返回进程退出码：0 无问题或只有警告，1 有问题，2 无法读取
+pub fn run_check_config(path: Option<PathBuf>) -> i32 {
+    let path = path.unwrap_or_else(Config::get_default_config_path);
+    match check_config_file(&path) {
+        Ok(diagnostics) if diagnostics.is_empty() => {
+            println!("{}: OK", path.display());
+            0
+        }
+        Ok(diagnostics) => {
+            for d in &diagnostics {
+                eprintln!("{}:{}:{}: {}", path.display(), d.line, d.column, d.message);
+            }
+            eprintln!("{} problem(s) found", diagnostics.len());
+            if diagnostics.iter().any(|d| !d.warning) {
+                1
+            } else {
+                0
+            }
+        }
+        Err(e) => {
+            eprintln!("{}: {}", path.display(), e);
+            2
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 不含任何问题的最小配置，按键绑定在文件末尾（第 42 行起）
+    const BASE: &str = r##"rules = []
+
+[appearance]
+border_px = 3
+snap = 32
+dmenu_font = "monospace 11"
+status_bar_padding = 5
+status_bar_height = 42
+
+[behavior]
+focus_follows_new_window = false
+resize_hints = true
+lock_fullscreen = true
+
+[status_bar]
+name = "egui_bar"
+show_bar = true
+
+[colors]
+dark_sea_green1 = "#afffd7"
+dark_sea_green2 = "#afffaf"
+pale_turquoise1 = "#afffff"
+light_sky_blue1 = "#afd7ff"
+grey84 = "#d7d7d7"
+cyan = "#00ffd7"
+white = "#ffffff"
+black = "#000000"
+transparent = 0
+opaque = 255
+
+[layout]
+m_fact = 0.55
+n_master = 1
+tags_length = 9
+
+[mouse_bindings]
+buttons = []
+
+[keybindings]
+modkey = "Mod1"
+
+[[keybindings.keys]]
+modifier = ["Mod1"]
+key = "Return"
+function = "zoom"
+argument = 0
+"##;
+
+    // 写入临时文件后检查，返回诊断与退出码
+    fn check(name: &str, source: &str) -> (Vec<Diagnostic>, i32) {
+        let path =
+            std::env::temp_dir().join(format!("jwm-check-{}-{}.toml", std::process::id(), name));
+        fs::write(&path, source).unwrap();
+        let diagnostics = check_config_file(&path).unwrap();
+        let code = run_check_config(Some(path.clone()));
+        fs::remove_file(&path).unwrap();
+        (diagnostics, code)
+    }
+
+    fn positions(diagnostics: &[Diagnostic]) -> Vec<(usize, usize)> {
+        diagnostics.iter().map(|d| (d.line, d.column)).collect()
+    }
+
+    #[test]
+    fn test_valid_config() {
+        let (diagnostics, code) = check("valid", BASE);
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        assert_eq!(code, 0);
+    }
+
+    #[test]
+    fn test_unknown_function() {
+        let source = BASE.replace(r#"function = "zoom""#, r#"function = "nosuch""#);
+        let (diagnostics, code) = check("function", &source);
+        assert_eq!(positions(&diagnostics), vec![(45, 12)]);
+        assert!(diagnostics[0].message.contains("unknown function 'nosuch'"));
+        assert!(!diagnostics[0].warning);
+        assert_eq!(code, 1);
+    }
+
+    #[test]
+    fn test_bad_keysym() {
+        let source = BASE.replace(r#"key = "Return""#, r#"key = "NoSuchKey""#);
+        let (diagnostics, code) = check("keysym", &source);
+        assert_eq!(positions(&diagnostics), vec![(44, 7)]);
+        assert!(diagnostics[0].message.contains("unknown key 'NoSuchKey'"));
+        assert_eq!(code, 1);
+    }
+
+    #[test]
+    fn test_tags_out_of_range() {
+        let source = BASE.replace("tags_length = 9", "tags_length = 40");
+        let (diagnostics, code) = check("tags", &source);
+        assert_eq!(positions(&diagnostics), vec![(34, 15)]);
+        assert!(diagnostics[0].message.starts_with("layout.tags_length:"));
+        assert_eq!(code, 1);
+    }
+
+    #[test]
+    fn test_sorted_by_position() {
+        let source = BASE
+            .replace(r#"function = "zoom""#, r#"function = "nosuch""#)
+            .replace("tags_length = 9", "tags_length = 0");
+        let (diagnostics, code) = check("sorted", &source);
+        assert_eq!(positions(&diagnostics), vec![(34, 15), (45, 12)]);
+        assert_eq!(code, 1);
+    }
+
+    #[test]
+    fn test_warning_does_not_fail() {
+        let source = BASE
+            .replace(r#"modifier = ["Mod1"]"#, "modifier = []")
+            .replace(r#"key = "Return""#, "key = \"Super_L\"\non = \"release\"");
+        let (diagnostics, code) = check("warning", &source);
+        assert_eq!(positions(&diagnostics), vec![(44, 7)]);
+        assert!(diagnostics[0].warning);
+        assert_eq!(code, 0);
+    }
+
+    #[test]
+    fn test_syntax_error() {
+        let source = BASE.replace("snap = 32", "snap = ");
+        let (diagnostics, code) = check("syntax", &source);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, 5);
+        assert_eq!(code, 1);
+    }
+
+    #[test]
+    fn test_unreadable_file() {
+        let path =
+            std::env::temp_dir().join(format!("jwm-check-{}-missing.toml", std::process::id()));
+        assert!(check_config_file(&path).is_err());
+        assert_eq!(run_check_config(Some(path)), 2);
+    }
+}
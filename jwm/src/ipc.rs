@@ -0,0 +1,484 @@
+// Unix socket IPC：客户端每行发送一个 JSON 请求，jwm 每行返回一个 JSON 响应。
+//
+// 请求示例：
+//   {"command": "run", "function": "view", "argument": 4}
+//   {"command": "get_tree"}
+//   {"command": "get_config"}
+//   {"command": "subscribe", "events": ["focus", "title_changed"]}
+//
+// 响应：{"success": true, "data": ...} 或 {"success": false, "error": "..."}
+// 订阅成功后该连接持续收到事件，每行一个 {"event": "<topic>", ...}
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, ErrorKind, Read, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+
+use crate::config::ArgumentConfig;
+
+// 单行请求的上限，超过后断开连接
+const MAX_REQUEST_LEN: usize = 1 << 20;
+// 订阅者积压未读的事件上限，超过后断开，避免拖慢 WM
+const MAX_PENDING_OUTPUT: usize = 4 << 20;
+
+/// socket 路径：$JWM_IPC_SOCKET，否则 $XDG_RUNTIME_DIR/jwm/ipc-<display>.sock，
+/// 没有 XDG_RUNTIME_DIR 时退回 /tmp/jwm-<uid>/ipc-<display>.sock
+pub fn socket_path() -> PathBuf {
+    if let Some(path) = std::env::var_os("JWM_IPC_SOCKET") {
+        return PathBuf::from(path);
+    }
+    let dir = match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(runtime) => PathBuf::from(runtime).join("jwm"),
+        None => std::env::temp_dir().join(format!("jwm-{}", unsafe { libc::getuid() })),
+    };
+    // 同一用户在不同 display 上运行的实例互不冲突
+    let display = std::env::var("DISPLAY")
+        .unwrap_or_default()
+        .replace([':', '/'], "");
+    if display.is_empty() {
+        dir.join("ipc.sock")
+    } else {
+        dir.join(format!("ipc-{}.sock", display))
+    }
+}
+
+// socket 所在目录必须是当前用户所有、权限 0700 的真实目录，
+// 否则其他用户可以预先创建或替换它来劫持 socket
+fn ensure_private_dir(dir: &Path) -> io::Result<()> {
+    fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(dir)?;
+    let metadata = fs::symlink_metadata(dir)?;
+    let uid = unsafe { libc::getuid() };
+    if !metadata.file_type().is_dir() || metadata.uid() != uid || metadata.mode() & 0o777 != 0o700 {
+        return Err(io::Error::new(
+            ErrorKind::PermissionDenied,
+            format!(
+                "{} must be a directory owned by uid {} with mode 0700",
+                dir.display(),
+                uid
+            ),
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum IpcRequest {
+    /// 执行一个绑定函数，函数名与参数与配置文件中的写法相同
+    Run {
+        function: String,
+        #[serde(default)]
+        argument: Option<ArgumentConfig>,
+    },
+    /// 显示器/标签/客户端树
+    GetTree,
+    /// 当前加载的配置
+    GetConfig,
+    /// 订阅事件，events 为空时订阅全部
+    Subscribe {
+        #[serde(default)]
+        events: Vec<IpcTopic>,
+    },
+}
+
+/// 可订阅的事件主题，与事件的 "event" 字段相同
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IpcTopic {
+    Focus,
+    ClientManaged,
+    ClientUnmanaged,
+    TitleChanged,
+    TagView,
+    LayoutChanged,
+    MonitorAdded,
+    MonitorRemoved,
+    Urgent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum IpcEvent {
+    /// 焦点窗口变化，window 为 None 表示没有焦点窗口
+    Focus {
+        window: Option<u32>,
+        monitor: Option<i32>,
+        class: String,
+        title: String,
+    },
+    ClientManaged {
+        window: u32,
+        monitor: Option<i32>,
+        class: String,
+        instance: String,
+        title: String,
+    },
+    ClientUnmanaged {
+        window: u32,
+        class: String,
+    },
+    TitleChanged {
+        window: u32,
+        title: String,
+    },
+    TagView {
+        monitor: i32,
+        selected_tags: u32,
+    },
+    LayoutChanged {
+        monitor: i32,
+        layout: String,
+        symbol: String,
+    },
+    MonitorAdded {
+        monitor: i32,
+        geometry: Rect,
+    },
+    MonitorRemoved {
+        monitor: i32,
+    },
+    /// 非焦点窗口请求注意
+    Urgent {
+        window: u32,
+        monitor: Option<i32>,
+        tags: u32,
+    },
+}
+
+impl IpcEvent {
+    pub fn topic(&self) -> IpcTopic {
+        match self {
+            IpcEvent::Focus { .. } => IpcTopic::Focus,
+            IpcEvent::ClientManaged { .. } => IpcTopic::ClientManaged,
+            IpcEvent::ClientUnmanaged { .. } => IpcTopic::ClientUnmanaged,
+            IpcEvent::TitleChanged { .. } => IpcTopic::TitleChanged,
+            IpcEvent::TagView { .. } => IpcTopic::TagView,
+            IpcEvent::LayoutChanged { .. } => IpcTopic::LayoutChanged,
+            IpcEvent::MonitorAdded { .. } => IpcTopic::MonitorAdded,
+            IpcEvent::MonitorRemoved { .. } => IpcTopic::MonitorRemoved,
+            IpcEvent::Urgent { .. } => IpcTopic::Urgent,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpcResponse {
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl IpcResponse {
+    pub fn ok(data: Option<serde_json::Value>) -> Self {
+        Self {
+            success: true,
+            data,
+            error: None,
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            data: None,
+            error: Some(message.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreeInfo {
+    pub focused_monitor: Option<i32>,
+    pub focused_window: Option<u32>,
+    pub monitors: Vec<MonitorInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitorInfo {
+    pub num: i32,
+    // RandR 输出名，未知时为空
+    pub output: String,
+    pub focused: bool,
+    pub geometry: Rect,
+    pub work_area: Rect,
+    pub selected_tags: u32,
+    pub layout: String,
+    pub layout_symbol: String,
+    pub m_fact: f32,
+    pub n_master: u32,
+    pub tags: Vec<TagInfo>,
+    // 按管理顺序排列
+    pub clients: Vec<ClientInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagInfo {
+    pub index: usize,
+    pub name: String,
+    pub mask: u32,
+    pub selected: bool,
+    pub occupied: bool,
+    pub urgent: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientInfo {
+    pub window: u32,
+    pub pid: u32,
+    pub name: String,
+    pub class: String,
+    pub instance: String,
+    pub tags: u32,
+    pub geometry: Rect,
+    pub border_width: i32,
+    pub focused: bool,
+    pub floating: bool,
+    pub fullscreen: bool,
+    pub sticky: bool,
+    pub urgent: bool,
+    pub fixed: bool,
+}
+
+struct IpcConnection {
+    id: u64,
+    stream: UnixStream,
+    incoming: Vec<u8>,
+    outgoing: Vec<u8>,
+    // 对端已关闭写端；待发送数据写完后关闭连接
+    eof: bool,
+    broken: bool,
+    // 已订阅的主题，None 表示普通请求连接
+    subscriptions: Option<Vec<IpcTopic>>,
+}
+
+impl IpcConnection {
+    fn read_available(&mut self) -> Vec<Result<IpcRequest, String>> {
+        let mut requests = Vec::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            match self.stream.read(&mut buf) {
+                Ok(0) => {
+                    self.eof = true;
+                    break;
+                }
+                Ok(n) => self.incoming.extend_from_slice(&buf[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => {
+                    self.broken = true;
+                    break;
+                }
+            }
+        }
+
+        while let Some(pos) = self.incoming.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.incoming.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim();
+            if !line.is_empty() {
+                requests.push(serde_json::from_str(line).map_err(|e| e.to_string()));
+            }
+        }
+        if self.incoming.len() > MAX_REQUEST_LEN {
+            warn!("[ipc] Request too long, closing connection {}", self.id);
+            self.broken = true;
+        }
+        requests
+    }
+
+    fn flush(&mut self) {
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => {
+                    self.broken = true;
+                    return;
+                }
+                Ok(n) => {
+                    self.outgoing.drain(..n);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => {
+                    self.broken = true;
+                    return;
+                }
+            }
+        }
+    }
+
+    fn send(&mut self, line: &[u8]) {
+        self.outgoing.extend_from_slice(line);
+        self.flush();
+        if self.outgoing.len() > MAX_PENDING_OUTPUT {
+            warn!("[ipc] Connection {} is not reading, closing it", self.id);
+            self.broken = true;
+        }
+    }
+
+    fn is_subscribed(&self, topic: IpcTopic) -> bool {
+        self.subscriptions
+            .as_ref()
+            .is_some_and(|topics| topics.is_empty() || topics.contains(&topic))
+    }
+
+    // 订阅连接在对端关闭写端后仍保留，直到写入失败
+    fn is_finished(&self) -> bool {
+        self.broken || (self.eof && self.outgoing.is_empty() && self.subscriptions.is_none())
+    }
+}
+
+/// 非阻塞的 IPC 服务端，由主循环每个 tick 调用 poll
+pub struct IpcServer {
+    listener: UnixListener,
+    path: PathBuf,
+    connections: Vec<IpcConnection>,
+    next_id: u64,
+}
+
+impl IpcServer {
+    pub fn bind(path: &Path) -> io::Result<Self> {
+        if let Some(dir) = path.parent() {
+            ensure_private_dir(dir)?;
+        }
+        if path.exists() {
+            // 能连上说明另一个实例仍在运行，否则是上次遗留的 socket
+            if UnixStream::connect(path).is_ok() {
+                return Err(io::Error::new(
+                    ErrorKind::AddrInUse,
+                    format!("{} is in use by another instance", path.display()),
+                ));
+            }
+            fs::remove_file(path)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        listener.set_nonblocking(true)?;
+        info!("[ipc] Listening on {}", path.display());
+        Ok(Self {
+            listener,
+            path: path.to_path_buf(),
+            connections: Vec::new(),
+            next_id: 0,
+        })
+    }
+
+    /// 接受新连接并读取已到达的请求，返回 (连接 id, 请求或解析错误)
+    pub fn poll(&mut self) -> Vec<(u64, Result<IpcRequest, String>)> {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    if stream.set_nonblocking(true).is_err() {
+                        continue;
+                    }
+                    self.next_id += 1;
+                    self.connections.push(IpcConnection {
+                        id: self.next_id,
+                        stream,
+                        incoming: Vec::new(),
+                        outgoing: Vec::new(),
+                        eof: false,
+                        broken: false,
+                        subscriptions: None,
+                    });
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    warn!("[ipc] accept failed: {}", e);
+                    break;
+                }
+            }
+        }
+
+        let mut requests = Vec::new();
+        for conn in self.connections.iter_mut() {
+            conn.flush();
+            if conn.eof || conn.broken {
+                continue;
+            }
+            for request in conn.read_available() {
+                match request {
+                    // 订阅只影响连接自身，直接在这里应答
+                    Ok(IpcRequest::Subscribe { events }) => {
+                        info!("[ipc] Connection {} subscribed to {:?}", conn.id, events);
+                        conn.subscriptions = Some(events);
+                        if let Some(line) = encode_line(&IpcResponse::ok(None)) {
+                            conn.send(&line);
+                        }
+                    }
+                    request => requests.push((conn.id, request)),
+                }
+            }
+        }
+        // 仍有请求待回复的连接即使已 EOF 也要保留到回复写完
+        let pending: Vec<u64> = requests.iter().map(|(id, _)| *id).collect();
+        self.connections
+            .retain(|conn| !conn.is_finished() || pending.contains(&conn.id));
+        requests
+    }
+
+    pub fn reply(&mut self, id: u64, response: &IpcResponse) {
+        let conn = match self.connections.iter_mut().find(|conn| conn.id == id) {
+            Some(conn) => conn,
+            None => return,
+        };
+        if let Some(line) = encode_line(response) {
+            conn.send(&line);
+        }
+    }
+
+    pub fn has_subscribers(&self, topic: IpcTopic) -> bool {
+        self.connections
+            .iter()
+            .any(|conn| !conn.broken && conn.is_subscribed(topic))
+    }
+
+    /// 把事件发给所有订阅了该主题的连接
+    pub fn broadcast(&mut self, event: &IpcEvent) {
+        let topic = event.topic();
+        if !self.has_subscribers(topic) {
+            return;
+        }
+        if let Some(line) = encode_line(event) {
+            for conn in self.connections.iter_mut() {
+                if !conn.broken && conn.is_subscribed(topic) {
+                    conn.send(&line);
+                }
+            }
+        }
+    }
+}
+
+// 序列化为一行 JSON（带换行）
+fn encode_line<T: Serialize>(value: &T) -> Option<Vec<u8>> {
+    match serde_json::to_vec(value) {
+        Ok(mut bytes) => {
+            bytes.push(b'\n');
+            Some(bytes)
+        }
+        Err(e) => {
+            warn!("[ipc] Failed to serialize message: {}", e);
+            None
+        }
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
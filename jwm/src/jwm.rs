@@ -15,26 +15,35 @@ use std::env;
 use std::fmt;
 use std::io::Write;
 use std::process::{Child, Command};
-use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 use std::usize;
 
 use crate::backend::api::AllowMode;
 use crate::backend::api::BackendEvent;
+use crate::backend::api::ColorAllocator;
 use crate::backend::api::EwmhFeature;
 use crate::backend::api::Geometry;
+use crate::backend::api::MoveResizeAction;
 use crate::backend::api::NetWmAction;
 use crate::backend::api::NetWmState;
+use crate::backend::api::OutputInfo;
 use crate::backend::api::PropertyKind;
-use crate::backend::api::{Backend, WindowId};
+use crate::backend::api::Strut;
+use crate::backend::api::{Backend, WindowId, ALL_DESKTOPS};
+use crate::backend::common_define::keys as k;
 use crate::backend::common_define::ArgbColor;
 use crate::backend::common_define::ColorScheme;
 use crate::backend::common_define::ConfigWindowBits;
 use crate::backend::common_define::EventMaskBits;
 use crate::backend::common_define::SchemeType;
 use crate::backend::common_define::{KeySym, Mods, MouseButton, StdCursorKind};
-use crate::config::CONFIG;
+use crate::config::{
+    ArgumentConfig, ColorsConfig, Config, ConfigWatcher, GeometryConfig, RuleMatchMode, CONFIG,
+};
+use crate::ipc::{self, IpcEvent, IpcRequest, IpcResponse, IpcServer};
+use crate::layout::{self, Layout, LayoutArea, LayoutParams, TiledClient, LAYOUTS};
+use crate::miscellaneous;
 
 use shared_structures::CommandType;
 use shared_structures::SharedCommand;
@@ -73,6 +82,12 @@ pub struct RestartSnapshot {
 
     // Window -> WMClient（保留状态、tags、is_floating、client_fact、is_fullscreen、geometry 等）
     pub clients: HashMap<u32, WMClient>,
+
+    // scratchpad 名称 -> Window
+    pub scratchpads: HashMap<String, u32>,
+
+    // 被吞噬的终端：子窗口 Window -> 终端 Window
+    pub swallowed: HashMap<u32, u32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Decode, Encode)]
@@ -97,8 +112,9 @@ pub struct PertagSnapshot {
     pub prev_tag: usize,
     pub n_masters: Vec<u32>,
     pub m_facts: Vec<f32>,
+    pub gaps: Vec<layout::Gaps>,
     pub sel_lts: Vec<usize>,
-    pub lt_pairs: Vec<[u32; 2]>, // 每 tag 两个 layout 的编号：0=TILE,1=FLOAT,2=MONOCLE
+    pub lt_pairs: Vec<[String; 2]>, // 每 tag 两个 layout 的注册名
     pub show_bars: Vec<bool>,
     pub sel_by_tag: Vec<Option<u32>>, // 每个 tag 的选中窗口（Window）
 }
@@ -110,6 +126,8 @@ pub struct WMClient {
     pub class: String,
     pub instance: String,
     pub win: u32,
+    // _NET_WM_PID，0 表示未知
+    pub pid: u32,
 
     // === 几何信息 ===
     pub geometry: ClientGeometry,
@@ -170,6 +188,19 @@ pub struct ClientState {
     pub never_focus: bool,
     pub old_state: bool,
     pub is_fullscreen: bool,
+    // 在所有 tag 上可见（tags 仍保留原值，为 0 时视为隐藏）
+    pub is_sticky: bool,
+    // 终端吞噬：is_terminal 可吞噬子进程窗口，no_swallow 的窗口不会被吞噬
+    pub is_terminal: bool,
+    pub no_swallow: bool,
+    // _NET_WM_STATE：above/below 决定层级，maximized 只作用于浮动窗口，
+    // hidden 的窗口不参与布局，skip_taskbar 仅回写给任务栏
+    pub is_above: bool,
+    pub is_below: bool,
+    pub is_maximized_vert: bool,
+    pub is_maximized_horz: bool,
+    pub is_hidden: bool,
+    pub skip_taskbar: bool,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -193,14 +224,65 @@ pub struct WMMonitor {
     pub sel: Option<ClientKey>,
 
     // === 布局和扩展 ===
-    pub lt: [Rc<LayoutEnum>; 2],
+    pub lt: [&'static dyn Layout; 2],
     pub pertag: Option<Pertag>,
+
+    // 对应的输出
+    pub output: OutputIdentity,
+}
+
+/// 显示器身份：输出名 + EDID 摘要，用于插拔后识别同一台显示器
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct OutputIdentity {
+    pub name: String,
+    pub edid: Option<u64>,
+}
+
+impl OutputIdentity {
+    pub fn of(output: &OutputInfo) -> Self {
+        Self {
+            name: output.name.clone(),
+            edid: output.edid,
+        }
+    }
+
+    /// 后端无法提供输出信息（如没有 RandR）
+    pub fn is_unknown(&self) -> bool {
+        self.name.is_empty() && self.edid.is_none()
+    }
+}
+
+impl fmt::Display for OutputIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.edid {
+            Some(edid) => write!(f, "{} ({:016x})", self.name, edid),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// 被拔掉的显示器及其原有客户端：(客户端, tags, 相对显示器原点的位置)
+#[derive(Debug, Clone)]
+pub struct DetachedMonitor {
+    pub monitor: WMMonitor,
+    pub clients: Vec<(ClientKey, u32, i32, i32)>,
+}
+
+impl DetachedMonitor {
+    /// 标签数量变化后 pertag 不再适用
+    fn is_compatible(&self, tags_length: usize) -> bool {
+        self.monitor
+            .pertag
+            .as_ref()
+            .map_or(false, |p| p.m_facts.len() == tags_length + 1)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct MonitorLayout {
     pub m_fact: f32,
     pub n_master: u32,
+    pub gaps: layout::Gaps,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -264,6 +346,15 @@ impl Default for ClientState {
             never_focus: false,
             old_state: false,
             is_fullscreen: false,
+            is_sticky: false,
+            is_terminal: false,
+            no_swallow: false,
+            is_above: false,
+            is_below: false,
+            is_maximized_vert: false,
+            is_maximized_horz: false,
+            is_hidden: false,
+            skip_taskbar: false,
         }
     }
 }
@@ -273,6 +364,7 @@ impl Default for MonitorLayout {
         Self {
             m_fact: 0.55, // 默认主区域比例
             n_master: 1,  // 默认主窗口数量
+            gaps: layout::Gaps::default(),
         }
     }
 }
@@ -299,6 +391,7 @@ impl WMClient {
             class: String::new(),
             instance: String::new(),
             win: 0,
+            pid: 0,
             geometry: ClientGeometry::default(),
             size_hints: SizeHints::default(),
             state: ClientState::default(),
@@ -317,9 +410,48 @@ impl WMClient {
         self.geometry.h + 2 * self.geometry.border_w
     }
 
+    /// 在给定 tag 集下是否可见（sticky 窗口忽略 tag 集，hidden 窗口总是不可见）
+    pub fn is_visible_on_tagset(&self, tagset: u32) -> bool {
+        !self.state.is_hidden
+            && ((self.state.tags & tagset) > 0 || (self.state.is_sticky && self.state.tags != 0))
+    }
+
+    /// 当前是否处于某个 _NET_WM_STATE
+    pub fn has_net_wm_state(&self, state: NetWmState) -> bool {
+        match state {
+            NetWmState::Fullscreen => self.state.is_fullscreen,
+            NetWmState::Sticky => self.state.is_sticky,
+            NetWmState::Above => self.state.is_above,
+            NetWmState::Below => self.state.is_below,
+            NetWmState::MaximizedVert => self.state.is_maximized_vert,
+            NetWmState::MaximizedHorz => self.state.is_maximized_horz,
+            NetWmState::Hidden => self.state.is_hidden,
+            NetWmState::DemandsAttention => self.state.is_urgent,
+            NetWmState::SkipTaskbar => self.state.skip_taskbar,
+        }
+    }
+
+    /// 需要回写到 _NET_WM_STATE 的全部状态
+    pub fn net_wm_states(&self) -> Vec<NetWmState> {
+        [
+            NetWmState::Fullscreen,
+            NetWmState::Sticky,
+            NetWmState::Above,
+            NetWmState::Below,
+            NetWmState::MaximizedVert,
+            NetWmState::MaximizedHorz,
+            NetWmState::Hidden,
+            NetWmState::DemandsAttention,
+            NetWmState::SkipTaskbar,
+        ]
+        .into_iter()
+        .filter(|&state| self.has_net_wm_state(state))
+        .collect()
+    }
+
     /// 检查是否为状态栏
     pub fn is_status_bar(&self) -> bool {
-        self.name == CONFIG.status_bar_name()
+        self.name == CONFIG.load().status_bar_name()
     }
 
     /// 获取客户端矩形区域
@@ -365,8 +497,9 @@ impl WMMonitor {
             sel_lt: 0,
             tag_set: [0; 2],
             sel: None,
-            lt: [Rc::new(LayoutEnum::TILE), Rc::new(LayoutEnum::TILE)],
+            lt: [layout::TILE, layout::TILE],
             pertag: None,
+            output: OutputIdentity::default(),
         }
     }
 
@@ -481,7 +614,7 @@ pub enum WMArgEnum {
     UInt(u32),
     Float(f32),
     StringVec(Vec<String>),
-    Layout(Rc<LayoutEnum>),
+    Layout(&'static dyn Layout),
 }
 
 #[derive(Debug, Clone)]
@@ -491,6 +624,8 @@ pub struct WMButton {
     pub button: MouseButton,
     pub func: Option<WMFuncType>,
     pub arg: WMArgEnum,
+    // 松开按键时触发
+    pub release: bool,
 }
 impl WMButton {
     pub fn new(
@@ -506,17 +641,56 @@ impl WMButton {
             button,
             func,
             arg: arg_enum,
+            release: false,
         }
     }
+
+    pub fn with_release(mut self, release: bool) -> Self {
+        self.release = release;
+        self
+    }
 }
 
 pub type WMFuncType = fn(&mut Jwm, &WMArgEnum) -> Result<(), Box<dyn std::error::Error>>;
+
+// 比较绑定时忽略 CapsLock/NumLock
+fn binding_mask(mask: Mods) -> Mods {
+    mask & (Mods::SHIFT
+        | Mods::CONTROL
+        | Mods::ALT
+        | Mods::SUPER
+        | Mods::MOD2
+        | Mods::MOD3
+        | Mods::MOD5)
+}
+
+// 单独的修饰键（Shift_L ... Hyper_R、AltGr）
+pub fn is_modifier_keysym(keysym: KeySym) -> bool {
+    (k::KEY_Shift_L..=k::KEY_Hyper_R).contains(&keysym)
+        || keysym == k::KEY_ISO_Level3_Shift
+        || keysym == k::KEY_Mode_switch
+}
+
+// 修饰键自身对应的修饰位；松开修饰键时事件 state 中仍带有该位
+fn modifier_of_keysym(keysym: KeySym) -> Mods {
+    match keysym {
+        k::KEY_Shift_L | k::KEY_Shift_R => Mods::SHIFT,
+        k::KEY_Control_L | k::KEY_Control_R => Mods::CONTROL,
+        k::KEY_Alt_L | k::KEY_Alt_R | k::KEY_Meta_L | k::KEY_Meta_R => Mods::ALT,
+        k::KEY_Super_L | k::KEY_Super_R | k::KEY_Hyper_L | k::KEY_Hyper_R => Mods::SUPER,
+        _ => Mods::empty(),
+    }
+}
 #[derive(Debug, Clone)]
 pub struct WMKey {
     pub mask: Mods,
     pub key_sym: KeySym,
     pub func_opt: Option<WMFuncType>,
     pub arg: WMArgEnum,
+    // 按键序列中第一个键之后还需依次按下的键，空表示单键绑定
+    pub chord: Vec<(Mods, KeySym)>,
+    // 松开按键时触发（仅单键绑定）
+    pub release: bool,
 }
 impl WMKey {
     pub fn new(mod0: Mods, keysym: KeySym, func: Option<WMFuncType>, arg: WMArgEnum) -> Self {
@@ -525,8 +699,33 @@ impl WMKey {
             key_sym: keysym,
             func_opt: func,
             arg,
+            chord: Vec::new(),
+            release: false,
+        }
+    }
+
+    pub fn with_chord(mut self, chord: Vec<(Mods, KeySym)>) -> Self {
+        self.chord = chord;
+        self
+    }
+
+    pub fn with_release(mut self, release: bool) -> Self {
+        self.release = release;
+        self
+    }
+
+    // 序列中第 index 个键
+    fn step(&self, index: usize) -> Option<(Mods, KeySym)> {
+        if index == 0 {
+            Some((self.mask, self.key_sym))
+        } else {
+            self.chord.get(index - 1).copied()
         }
     }
+
+    fn sequence_len(&self) -> usize {
+        self.chord.len() + 1
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -539,10 +738,12 @@ pub struct Pertag {
     pub n_masters: Vec<u32>,
     // mfacts per tag
     pub m_facts: Vec<f32>,
+    // gaps per tag
+    pub gaps: Vec<layout::Gaps>,
     // selected layouts
     pub sel_lts: Vec<usize>,
     // matrix of tags and layouts indexes
-    lt_idxs: Vec<Vec<Option<Rc<LayoutEnum>>>>,
+    lt_idxs: Vec<Vec<Option<&'static dyn Layout>>>,
     // display bar for the current tag
     pub show_bars: Vec<bool>,
     // selected client
@@ -550,92 +751,170 @@ pub struct Pertag {
 }
 impl Pertag {
     pub fn new(show_bar: bool) -> Self {
+        let len = CONFIG.load().tags_length() + 1;
         Self {
             cur_tag: 0,
             prev_tag: 0,
-            n_masters: vec![0; CONFIG.tags_length() + 1],
-            m_facts: vec![0.; CONFIG.tags_length() + 1],
-            sel_lts: vec![0; CONFIG.tags_length() + 1],
-            lt_idxs: vec![vec![None; 2]; CONFIG.tags_length() + 1],
-            show_bars: vec![show_bar; CONFIG.tags_length() + 1],
-            sel: vec![None; CONFIG.tags_length() + 1],
+            n_masters: vec![0; len],
+            m_facts: vec![0.; len],
+            gaps: vec![layout::Gaps::default(); len],
+            sel_lts: vec![0; len],
+            lt_idxs: vec![vec![None; 2]; len],
+            show_bars: vec![show_bar; len],
+            sel: vec![None; len],
         }
     }
-}
 
-pub const DEFAULT_TILE_SYMBOL: &'static str = "[]=";
-pub const DEFAULT_FLOAT_SYMBOL: &'static str = "><>";
-pub const DEFAULT_MONOCLE_SYMBOL: &'static str = "[M]";
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct LayoutEnum(&'static str);
-impl LayoutEnum {
-    pub const ANY: Self = Self("");
-    pub const TILE: Self = Self("tile");
-    pub const FLOAT: Self = Self("float");
-    pub const MONOCLE: Self = Self("monocle");
-    pub fn symbol(&self) -> &str {
-        match self {
-            &LayoutEnum::TILE => DEFAULT_TILE_SYMBOL,
-            &LayoutEnum::FLOAT => DEFAULT_FLOAT_SYMBOL,
-            &LayoutEnum::MONOCLE => DEFAULT_MONOCLE_SYMBOL,
-            _ => "",
+    // 标签数量变化（配置重载）后调整各数组长度，新增标签沿用显示器当前布局参数
+    pub fn resize(
+        &mut self,
+        len: usize,
+        layout: &MonitorLayout,
+        lts: [&'static dyn Layout; 2],
+    ) {
+        self.n_masters.resize(len, layout.n_master);
+        self.m_facts.resize(len, layout.m_fact);
+        self.gaps.resize(len, layout.gaps);
+        self.sel_lts.resize(len, 0);
+        self.lt_idxs.resize(len, vec![Some(lts[0]), Some(lts[1])]);
+        let show_bar = self.show_bars.first().copied().unwrap_or(true);
+        self.show_bars.resize(len, show_bar);
+        self.sel.resize(len, None);
+        if self.cur_tag >= len {
+            self.cur_tag = 1;
+        }
+        if self.prev_tag >= len {
+            self.prev_tag = 1;
         }
     }
-    pub fn is_tile(&self) -> bool {
-        self == &LayoutEnum::TILE
+
+    // 视图移到另一显示器时，带上该 tag 的布局参数与选中窗口
+    pub fn copy_tag_from(&mut self, other: &Pertag, tag: usize) {
+        if tag >= self.n_masters.len() || tag >= other.n_masters.len() {
+            return;
+        }
+        self.n_masters[tag] = other.n_masters[tag];
+        self.m_facts[tag] = other.m_facts[tag];
+        self.gaps[tag] = other.gaps[tag];
+        self.sel_lts[tag] = other.sel_lts[tag];
+        self.lt_idxs[tag] = other.lt_idxs[tag].clone();
+        self.sel[tag] = other.sel[tag];
     }
-    pub fn is_float(&self) -> bool {
-        self == &LayoutEnum::FLOAT
+}
+
+/// 规则中单个字段的匹配器，空模式表示忽略该字段
+#[derive(Debug, Clone)]
+pub enum RulePattern {
+    Any,
+    Contains(String),
+    Glob(glob::Pattern),
+    Regex(regex::Regex),
+    // 模式编译失败：永不匹配
+    Invalid,
+}
+
+impl RulePattern {
+    pub fn new(mode: RuleMatchMode, pattern: &str) -> Self {
+        if pattern.is_empty() {
+            return RulePattern::Any;
+        }
+        match mode {
+            RuleMatchMode::Contains => RulePattern::Contains(pattern.to_string()),
+            RuleMatchMode::Glob => match glob::Pattern::new(pattern) {
+                Ok(p) => RulePattern::Glob(p),
+                Err(e) => {
+                    warn!("[RulePattern] Invalid glob '{}': {}", pattern, e);
+                    RulePattern::Invalid
+                }
+            },
+            RuleMatchMode::Regex => match regex::Regex::new(pattern) {
+                Ok(re) => RulePattern::Regex(re),
+                Err(e) => {
+                    warn!("[RulePattern] Invalid regex '{}': {}", pattern, e);
+                    RulePattern::Invalid
+                }
+            },
+        }
     }
-    pub fn is_monocle(&self) -> bool {
-        self == &LayoutEnum::MONOCLE
+
+    pub fn is_any(&self) -> bool {
+        matches!(self, RulePattern::Any)
     }
-}
 
-impl From<u32> for LayoutEnum {
-    #[inline]
-    fn from(value: u32) -> Self {
-        match value {
-            0 => LayoutEnum::TILE,
-            1 => LayoutEnum::FLOAT,
-            2 => LayoutEnum::MONOCLE,
-            _ => LayoutEnum::ANY,
+    pub fn matches(&self, value: &str) -> bool {
+        match self {
+            RulePattern::Any => true,
+            RulePattern::Contains(s) => value.contains(s.as_str()),
+            RulePattern::Glob(p) => p.matches(value),
+            RulePattern::Regex(re) => re.is_match(value),
+            RulePattern::Invalid => false,
         }
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct WMRule {
-    pub class: String,
-    pub instance: String,
-    pub name: String,
+    pub class: RulePattern,
+    pub instance: RulePattern,
+    pub name: RulePattern,
+    pub role: RulePattern,
+    // 小写的窗口类型短名，空表示忽略
+    pub window_type: String,
     pub tags: usize,
     pub is_floating: bool,
+    pub is_sticky: bool,
+    pub is_terminal: bool,
+    pub no_swallow: bool,
     pub monitor: i32,
+    pub geometry: Option<GeometryConfig>,
+    pub center: bool,
+    pub fullscreen: bool,
+    pub no_focus: bool,
+    pub border_width: Option<u32>,
+    pub opacity: Option<f32>,
+    pub switch_to_tag: bool,
 }
-impl WMRule {
-    pub fn new(
-        class: String,
-        instance: String,
-        name: String,
-        tags: usize,
-        is_floating: bool,
-        monitor: i32,
-    ) -> Self {
-        WMRule {
-            class,
-            instance,
-            name,
-            tags,
-            is_floating,
-            monitor,
-        }
-    }
+
+// 规则匹配所需的窗口属性
+#[derive(Debug, Clone, Default)]
+struct WindowProps {
+    name: String,
+    class: String,
+    instance: String,
+    role: String,
+    window_types: Vec<String>,
 }
 
 pub type MonitorIndex = i32;
 
+/// 一个状态栏实例：ring buffer、子进程与窗口
+pub struct StatusBar {
+    // 传给状态栏进程的 ring buffer 路径
+    pub shared_path: String,
+    pub shmem: Option<SharedRingBuffer>,
+    pub child: Option<Child>,
+    pub client: Option<ClientKey>,
+    pub window: Option<u32>,
+
+    // 去抖/差异更新
+    pub last_payload: Option<Vec<u8>>,
+    pub last_update_at: Option<std::time::Instant>,
+}
+
+impl StatusBar {
+    pub fn new(shared_path: String) -> Self {
+        Self {
+            shared_path,
+            shmem: None,
+            child: None,
+            client: None,
+            window: None,
+            last_payload: None,
+            last_update_at: None,
+        }
+    }
+}
+
 pub struct Jwm {
     // 基础/环境
     pub s_w: i32,
@@ -660,20 +939,9 @@ pub struct Jwm {
     pub monitor_clients: SecondaryMap<MonitorKey, Vec<ClientKey>>,
     pub monitor_stack: SecondaryMap<MonitorKey, Vec<ClientKey>>,
 
-    // ——— 单实例状态栏（Single Bar）———
-    // 共享内存与子进程（单实例）
-    pub status_bar_shmem: Option<SharedRingBuffer>, // 全局唯一 ring buffer（例如 /dev/shm/jwm_bar_global）
-    pub status_bar_child: Option<Child>,            // 单个状态栏进程
-    pub status_bar_pid: Option<u32>,                // 子进程 PID（可选）
-
-    // 状态栏窗口（单实例）
-    pub status_bar_client: Option<ClientKey>, // 唯一的 bar 客户端
-    pub status_bar_window: Option<u32>,       // 唯一的 bar 窗口
-    pub current_bar_monitor_id: Option<i32>,  // bar 当前所在显示器的编号（monitor.num）
-
-    // 去抖/差异更新
-    pub last_bar_payload: Option<Vec<u8>>,
-    pub last_bar_update_at: Option<std::time::Instant>,
+    // ——— 状态栏 ———
+    // 显示器 -> 状态栏；单实例模式下只有一项，随选中显示器移动
+    pub status_bars: SecondaryMap<MonitorKey, StatusBar>,
     pub bar_min_interval: std::time::Duration,
 
     // per-monitor 的待刷新集合（仍按显示器维度存）
@@ -681,9 +949,55 @@ pub struct Jwm {
 
     pub suppress_mouse_focus_until: Option<std::time::Instant>,
 
+    // 最近一次输出变化通知的时间，安静一段时间后才重新计算显示器
+    pub pending_output_change: Option<std::time::Instant>,
+
     pub restoring_from_snapshot: bool,
+    // 启动时接管已有窗口期间为 true，此时不做终端吞噬（吞噬关系由快照恢复）
+    pub scanning: bool,
 
     pub last_stacking: SecondaryMap<MonitorKey, Vec<u32>>,
+
+    // 间隙总开关（togglegaps）
+    pub gaps_enabled: bool,
+
+    // scratchpad 名称 -> 客户端；隐藏时 tags 为 0
+    pub scratchpads: HashMap<String, ClientKey>,
+    // 已启动但窗口尚未出现的 scratchpad 及其启动时间
+    pub pending_scratchpads: HashMap<String, Instant>,
+
+    // 终端吞噬：子窗口 -> 被隐藏的终端
+    pub swallowed: HashMap<ClientKey, ClientKey>,
+
+    // 客户端当前命中的规则下标，标题变化时据此判断是否需要重新应用
+    pub client_rules: SecondaryMap<ClientKey, usize>,
+
+    // 被拔掉的显示器，同一输出重新接入时恢复
+    pub detached_monitors: HashMap<OutputIdentity, DetachedMonitor>,
+
+    // 第三方 dock/panel 窗口及其 strut，不作为客户端管理
+    pub docks: HashMap<u32, Strut>,
+
+    // 最大化前的浮动几何，取消最大化时恢复
+    pub maximize_restore: SecondaryMap<ClientKey, (i32, i32, i32, i32)>,
+
+    // 配置文件监听，文件变化时自动重载
+    pub config_watcher: Option<ConfigWatcher>,
+    // IPC socket 服务端
+    pub ipc_server: Option<IpcServer>,
+    // 最近一次发给订阅者的焦点窗口，以及每个显示器（num）的 (选中标签, 布局名)
+    pub ipc_focused_window: Option<u32>,
+    pub ipc_view_state: HashMap<i32, (u32, &'static str)>,
+
+    // 最近发布的 (_NET_CURRENT_DESKTOP, 工作区)
+    pub ewmh_desktop_state: Option<(u32, [u32; 4])>,
+
+    // 当前按键模式，None 为默认模式
+    pub key_mode: Option<String>,
+    // 进行中的按键序列（已按下的键），非空时键盘处于主动抓取状态
+    pub chord_prefix: Vec<(Mods, KeySym)>,
+    // 最近一次按下的键，松开绑定只在中间没有按其他键时触发
+    pub last_key_press: Option<KeySym>,
 }
 
 impl Jwm {
@@ -702,26 +1016,7 @@ impl Jwm {
             s_h,
             backend.root_window().0
         );
-        let alloc = backend.color_allocator();
-        let colors = crate::config::CONFIG.colors();
-        alloc.set_scheme(
-            SchemeType::Norm,
-            ColorScheme::new(
-                ArgbColor::from_hex(&colors.dark_sea_green1, colors.opaque)?,
-                ArgbColor::from_hex(&colors.light_sky_blue1, colors.opaque)?,
-                ArgbColor::from_hex(&colors.light_sky_blue1, colors.opaque)?,
-            ),
-        );
-        alloc.set_scheme(
-            SchemeType::Sel,
-            ColorScheme::new(
-                ArgbColor::from_hex(&colors.dark_sea_green2, colors.opaque)?,
-                ArgbColor::from_hex(&colors.pale_turquoise1, colors.opaque)?,
-                ArgbColor::from_hex(&colors.cyan, colors.opaque)?,
-            ),
-        );
-        // 预分配
-        backend.color_allocator().allocate_schemes_pixels()?;
+        Self::apply_color_schemes(backend.color_allocator(), CONFIG.load().colors())?;
         info!("[new] JWM initialization completed successfully");
         Ok(Jwm {
             s_w,
@@ -742,39 +1037,77 @@ impl Jwm {
             monitor_clients: SecondaryMap::new(),
             monitor_stack: SecondaryMap::new(),
 
-            status_bar_shmem: None,
-            status_bar_child: None,
+            status_bars: SecondaryMap::new(),
             message: SharedMessage::default(),
-            status_bar_client: None,
-            status_bar_window: None,
-            current_bar_monitor_id: None,
-            last_bar_payload: None,
-            last_bar_update_at: None,
             bar_min_interval: std::time::Duration::from_millis(10),
-            status_bar_pid: None,
             pending_bar_updates: HashSet::new(),
 
             suppress_mouse_focus_until: None,
 
             restoring_from_snapshot: false,
+            scanning: false,
+            pending_output_change: None,
             last_stacking: SecondaryMap::new(),
+            gaps_enabled: true,
+            scratchpads: HashMap::new(),
+            pending_scratchpads: HashMap::new(),
+            swallowed: HashMap::new(),
+            client_rules: SecondaryMap::new(),
+            maximize_restore: SecondaryMap::new(),
+            docks: HashMap::new(),
+            detached_monitors: HashMap::new(),
+            config_watcher: None,
+            ipc_server: None,
+            ipc_focused_window: None,
+            ipc_view_state: HashMap::new(),
+            ewmh_desktop_state: None,
+            key_mode: None,
+            chord_prefix: Vec::new(),
+            last_key_press: None,
         })
     }
 
+    // 根据配置颜色设置 Norm/Sel 方案并预分配像素
+    fn apply_color_schemes(
+        alloc: &mut dyn ColorAllocator,
+        colors: &ColorsConfig,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        alloc.set_scheme(
+            SchemeType::Norm,
+            ColorScheme::new(
+                ArgbColor::from_hex(&colors.dark_sea_green1, colors.opaque)?,
+                ArgbColor::from_hex(&colors.light_sky_blue1, colors.opaque)?,
+                ArgbColor::from_hex(&colors.light_sky_blue1, colors.opaque)?,
+            ),
+        );
+        alloc.set_scheme(
+            SchemeType::Sel,
+            ColorScheme::new(
+                ArgbColor::from_hex(&colors.dark_sea_green2, colors.opaque)?,
+                ArgbColor::from_hex(&colors.pale_turquoise1, colors.opaque)?,
+                ArgbColor::from_hex(&colors.cyan, colors.opaque)?,
+            ),
+        );
+        // 预分配
+        alloc.allocate_schemes_pixels()
+    }
+
     fn clean_mask(&self, raw: u16) -> Mods {
         // 使用 KeyOps 将后端原始修饰位转换为通用 Mods 并去掉 NUMLOCK/CAPS
         let mods_all = self
             .backend
             .key_ops()
             .mods_from_raw_mask(raw, self.numlock_mask_bits);
-        mods_all
-            & (Mods::SHIFT
-                | Mods::CONTROL
-                | Mods::ALT
-                | Mods::SUPER
-                | Mods::MOD2
-                | Mods::MOD3
-                | Mods::MOD5)
+        binding_mask(mods_all)
+    }
+
+    // 当前模式下生效的按键绑定
+    fn active_keys(&self) -> Vec<WMKey> {
+        let config = CONFIG.load();
+        match self.key_mode.as_deref() {
+            Some(mode) => config.get_mode_keys(mode).unwrap_or_default(),
+            None => config.get_keys(),
+        }
     }
 
     fn on_key_press(
@@ -784,25 +1117,141 @@ impl Jwm {
     ) -> Result<(), Box<dyn std::error::Error>> {
         let keysym = self.backend.key_ops_mut().keysym_from_keycode(keycode)?;
         let clean_state = self.clean_mask(state_bits);
-        for key_config in CONFIG.get_keys().iter() {
-            let kc_mask = key_config.mask
-                & (Mods::SHIFT
-                    | Mods::CONTROL
-                    | Mods::ALT
-                    | Mods::SUPER
-                    | Mods::MOD2
-                    | Mods::MOD3
-                    | Mods::MOD5);
-            if keysym == key_config.key_sym && kc_mask == clean_state {
-                if let Some(func) = key_config.func_opt {
-                    let _ = func(self, &key_config.arg);
+        self.last_key_press = Some(keysym);
+        // 序列进行中键盘被主动抓取，单独按下的修饰键不打断序列
+        if !self.chord_prefix.is_empty() && is_modifier_keysym(keysym) {
+            return Ok(());
+        }
+
+        let depth = self.chord_prefix.len();
+        let keys = self.active_keys();
+        let candidates: Vec<&WMKey> = keys
+            .iter()
+            .filter(|key| {
+                !key.release
+                    && key.sequence_len() > depth
+                    && (0..depth).all(|i| {
+                        key.step(i).map_or(false, |(mask, sym)| {
+                            (binding_mask(mask), sym) == self.chord_prefix[i]
+                        })
+                    })
+                    && key.step(depth).map_or(false, |(mask, sym)| {
+                        binding_mask(mask) == clean_state && sym == keysym
+                    })
+            })
+            .collect();
+
+        if candidates.is_empty() {
+            if depth > 0 {
+                // 序列中按错键或 Escape：放弃整个序列
+                self.cancel_chord()?;
+            } else if self.key_mode.is_some() && keysym == k::KEY_Escape {
+                self.set_key_mode(None)?;
+            }
+            return Ok(());
+        }
+
+        // 完整匹配优先触发，否则等待下一个键
+        match candidates
+            .iter()
+            .find(|key| key.sequence_len() == depth + 1)
+        {
+            Some(key) => {
+                let key = (*key).clone();
+                self.cancel_chord()?;
+                self.run_key_binding(&key)?;
+            }
+            None => {
+                if depth == 0 && !self.backend.input_ops().grab_keyboard()? {
+                    warn!("[on_key_press] Failed to grab keyboard for key chord");
+                    return Ok(());
                 }
-                break;
+                self.chord_prefix.push((clean_state, keysym));
             }
         }
         Ok(())
     }
 
+    // 松开绑定：只在按下与松开之间没有按过其他键时触发，
+    // 因此可以把单独轻按的修饰键绑定到动作上而不影响它作为修饰键使用
+    fn on_key_release(
+        &mut self,
+        keycode: u8,
+        state_bits: u16,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let keysym = self.backend.key_ops_mut().keysym_from_keycode(keycode)?;
+        let tapped = self.last_key_press.take() == Some(keysym);
+        if !tapped || !self.chord_prefix.is_empty() {
+            return Ok(());
+        }
+        // 松开修饰键时 state 中仍包含它自身的修饰位
+        let clean_state = self.clean_mask(state_bits) & !modifier_of_keysym(keysym);
+        let key = self.active_keys().into_iter().find(|key| {
+            key.release
+                && key.sequence_len() == 1
+                && key.key_sym == keysym
+                && binding_mask(key.mask) & !modifier_of_keysym(keysym) == clean_state
+        });
+        if let Some(key) = key {
+            self.run_key_binding(&key)?;
+        }
+        Ok(())
+    }
+
+    fn run_key_binding(&mut self, key: &WMKey) -> Result<(), Box<dyn std::error::Error>> {
+        let mode_before = self.key_mode.clone();
+        if let Some(func) = key.func_opt {
+            let _ = func(self, &key.arg);
+        }
+        // oneshot 模式触发后返回默认模式（绑定自身切换了模式时除外）
+        let oneshot = mode_before
+            .as_deref()
+            .and_then(|mode| CONFIG.load().key_mode(mode).map(|m| m.oneshot))
+            .unwrap_or(false);
+        if oneshot && self.key_mode == mode_before {
+            self.set_key_mode(None)?;
+        }
+        Ok(())
+    }
+
+    // 结束进行中的按键序列并释放键盘
+    fn cancel_chord(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.chord_prefix.is_empty() {
+            return Ok(());
+        }
+        self.chord_prefix.clear();
+        self.backend.input_ops().ungrab_keyboard()?;
+        Ok(())
+    }
+
+    /// 切换按键模式；参数为模式名，"default" 返回默认模式
+    pub fn mode(&mut self, arg: &WMArgEnum) -> Result<(), Box<dyn std::error::Error>> {
+        let name = match arg {
+            WMArgEnum::StringVec(v) => v.first().cloned().unwrap_or_default(),
+            _ => String::new(),
+        };
+        if name.is_empty() || name == "default" {
+            return self.set_key_mode(None);
+        }
+        if CONFIG.load().key_mode(&name).is_none() {
+            warn!("[mode] Unknown key mode '{}'", name);
+            return Ok(());
+        }
+        self.set_key_mode(Some(name))
+    }
+
+    fn set_key_mode(&mut self, mode: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+        if self.key_mode == mode {
+            return Ok(());
+        }
+        info!("[set_key_mode] {:?} -> {:?}", self.key_mode, mode);
+        self.cancel_chord()?;
+        self.key_mode = mode;
+        self.grabkeys()?;
+        self.mark_bar_update_needed_if_visible(None);
+        Ok(())
+    }
+
     fn on_button_press(
         &mut self,
         window: u32,
@@ -833,26 +1282,21 @@ impl Jwm {
         let event_mask = self.clean_mask(state_bits);
         let mouse_button = MouseButton::from_u8(detail_btn);
 
-        let mut handled_by_wm = false;
-        for config in CONFIG.get_buttons().iter() {
-            let kc_mask = config.mask
-                & (Mods::SHIFT
-                    | Mods::CONTROL
-                    | Mods::ALT
-                    | Mods::SUPER
-                    | Mods::MOD2
-                    | Mods::MOD3
-                    | Mods::MOD5);
-            if config.click_type == click_type
-                && config.func.is_some()
-                && config.button == mouse_button
-                && kc_mask == event_mask
-            {
-                handled_by_wm = true;
-                if let Some(ref func) = config.func {
-                    let _ = func(self, &config.arg);
-                }
-                break;
+        // 只有松开绑定时按下也由 WM 消费，避免客户端收到不成对的事件
+        let buttons = CONFIG.load().get_buttons();
+        let matching: Vec<&WMButton> = buttons
+            .iter()
+            .filter(|config| {
+                config.click_type == click_type
+                    && config.func.is_some()
+                    && config.button == mouse_button
+                    && binding_mask(config.mask) == event_mask
+            })
+            .collect();
+        let handled_by_wm = !matching.is_empty();
+        if let Some(config) = matching.iter().find(|config| !config.release) {
+            if let Some(ref func) = config.func {
+                let _ = func(self, &config.arg);
             }
         }
 
@@ -870,6 +1314,35 @@ impl Jwm {
         Ok(())
     }
 
+    fn on_button_release(
+        &mut self,
+        window: u32,
+        state_bits: u16,
+        detail_btn: u8,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let click_type = if self.wintoclient(window).is_some() {
+            WMClickType::ClickClientWin
+        } else {
+            WMClickType::ClickRootWin
+        };
+        // 松开事件的 state 中包含该按键自身的按钮位，clean_mask 只保留键盘修饰位
+        let event_mask = self.clean_mask(state_bits);
+        let mouse_button = MouseButton::from_u8(detail_btn);
+
+        let buttons = CONFIG.load().get_buttons();
+        if let Some(config) = buttons.iter().find(|config| {
+            config.release
+                && config.click_type == click_type
+                && config.button == mouse_button
+                && binding_mask(config.mask) == event_mask
+        }) {
+            if let Some(func) = config.func {
+                let _ = func(self, &config.arg);
+            }
+        }
+        Ok(())
+    }
+
     fn on_motion_notify(
         &mut self,
         window: u32,
@@ -905,9 +1378,18 @@ impl Jwm {
         stack_mode: u8,
     ) -> Result<(), Box<dyn std::error::Error>> {
         // 优先判断是否为状态栏
-        if Some(window) == self.status_bar_window {
+        if let Some(bar_mon_key) = self.bar_monitor_of_window(window) {
             return self.handle_statusbar_configure_request_params(
-                window, mask_bits, x, y, w, h, border, sibling, stack_mode,
+                bar_mon_key,
+                window,
+                mask_bits,
+                x,
+                y,
+                w,
+                h,
+                border,
+                sibling,
+                stack_mode,
             );
         }
 
@@ -927,6 +1409,7 @@ impl Jwm {
 
     fn handle_statusbar_configure_request_params(
         &mut self,
+        bar_mon_key: MonitorKey,
         window: u32,
         mask_bits: u16,
         x: i16,
@@ -937,15 +1420,17 @@ impl Jwm {
         _sibling: Option<u32>,
         _stack_mode: u8,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        if self.status_bar_client.is_none() {
-            error!("[handle_statusbar_configure_request] StatusBar not found");
-            return self.handle_unmanaged_configure_request_params(
-                window, mask_bits, x, y, 0, h, 0, None, 0,
-            );
-        }
+        let bar_key = match self.status_bars.get(bar_mon_key).and_then(|bar| bar.client) {
+            Some(k) => k,
+            None => {
+                error!("[handle_statusbar_configure_request] StatusBar not found");
+                return self.handle_unmanaged_configure_request_params(
+                    window, mask_bits, x, y, 0, h, 0, None, 0,
+                );
+            }
+        };
         let mask = ConfigWindowBits::from_bits_truncate(mask_bits);
         {
-            let bar_key = self.status_bar_client.unwrap();
             let statusbar_mut = self.clients.get_mut(bar_key).unwrap();
 
             if mask.contains(ConfigWindowBits::X) {
@@ -955,7 +1440,7 @@ impl Jwm {
                 statusbar_mut.geometry.y = y as i32;
             }
             if mask.contains(ConfigWindowBits::HEIGHT) {
-                statusbar_mut.geometry.h = (h.max(CONFIG.status_bar_height() as u16)) as i32;
+                statusbar_mut.geometry.h = (h.max(CONFIG.load().status_bar_height() as u16)) as i32;
             }
 
             self.backend.window_ops().configure_xywh_border(
@@ -967,8 +1452,7 @@ impl Jwm {
                 None,
             )?;
         }
-        let monitor_key = self.get_monitor_by_id(self.current_bar_monitor_id.unwrap());
-        self.arrange(monitor_key);
+        self.arrange(Some(bar_mon_key));
         if let Some(client_key) = self.wintoclient(window) {
             self.configure_client(client_key)?;
         }
@@ -1191,6 +1675,7 @@ impl Jwm {
                 stack_mode,
             ),
             BackendEvent::KeyPress { keycode, state } => self.on_key_press(keycode, state),
+            BackendEvent::KeyRelease { keycode, state } => self.on_key_release(keycode, state),
             BackendEvent::ConfigureNotify { window, x, y, w, h } => {
                 self.configurenotify(window.0 as u32, x, y, w, h)
             }
@@ -1209,6 +1694,10 @@ impl Jwm {
                 from_configure,
             } => self.unmapnotify(window.0 as u32, from_configure),
 
+            BackendEvent::OutputsChanged => {
+                self.pending_output_change = Some(Instant::now());
+                Ok(())
+            }
             BackendEvent::MappingNotify { request: _ } => {
                 // 统一处理：键盘映射变化，清缓存+重新抓取
                 self.backend.key_ops_mut().clear_cache();
@@ -1220,6 +1709,12 @@ impl Jwm {
                 kind,
                 deleted,
             } => {
+                // strut 被删除同样需要刷新工作区
+                if matches!(kind, PropertyKind::Strut)
+                    && self.docks.contains_key(&(window.0 as u32))
+                {
+                    return self.update_dock_strut(window.0 as u32);
+                }
                 if deleted {
                     return Ok(());
                 }
@@ -1238,7 +1733,7 @@ impl Jwm {
                         PropertyKind::NetWmWindowType => {
                             self.handle_window_type_change(client_key)?
                         }
-                        PropertyKind::Other => {}
+                        PropertyKind::Strut | PropertyKind::Other => {}
                     }
                 }
                 Ok(())
@@ -1248,31 +1743,58 @@ impl Jwm {
                 action,
                 states,
             } => {
-                let fullscreen_requested = states
-                    .iter()
-                    .flatten()
-                    .any(|s| matches!(s, NetWmState::Fullscreen));
-                if fullscreen_requested {
-                    if let Some(ck) = self.wintoclient(window.0 as u32) {
-                        let is_fullscreen = self
-                            .clients
-                            .get(ck)
-                            .map(|c| c.state.is_fullscreen)
-                            .unwrap_or(false);
-                        let fullscreen = match action {
-                            NetWmAction::Add => true,
-                            NetWmAction::Remove => false,
-                            NetWmAction::Toggle => !is_fullscreen,
-                        };
-                        self.setfullscreen(ck, fullscreen)?;
+                let client_key = match self.wintoclient(window.0 as u32) {
+                    Some(ck) => ck,
+                    None => return Ok(()),
+                };
+                for state in states.into_iter().flatten() {
+                    let current = match self.clients.get(client_key) {
+                        Some(c) => c.has_net_wm_state(state),
+                        None => return Ok(()),
+                    };
+                    let on = match action {
+                        NetWmAction::Add => true,
+                        NetWmAction::Remove => false,
+                        NetWmAction::Toggle => !current,
+                    };
+                    if on != current {
+                        self.set_net_wm_state(client_key, state, on)?;
                     }
                 }
                 Ok(())
             }
-            BackendEvent::ActiveWindowMessage { window } => {
-                if let Some(ck) = self.wintoclient(window.0 as u32) {
-                    let is_urgent = self
-                        .clients
+            BackendEvent::CurrentDesktopMessage { desktop } => {
+                self.handle_current_desktop_message(desktop)
+            }
+            BackendEvent::WmDesktopMessage { window, desktop } => {
+                self.handle_wm_desktop_message(window.0 as u32, desktop)
+            }
+            BackendEvent::MoveResizeMessage { window, action } => {
+                self.handle_moveresize_message(window.0 as u32, action)
+            }
+            BackendEvent::MoveResizeWindowMessage { window, x, y, w, h } => {
+                self.handle_moveresize_window_message(window.0 as u32, x, y, w, h)
+            }
+            BackendEvent::CloseWindowMessage { window } => {
+                self.handle_close_window_message(window.0 as u32)
+            }
+            BackendEvent::RestackWindowMessage {
+                window,
+                sibling,
+                above,
+            } => self.handle_restack_window_message(
+                window.0 as u32,
+                sibling.map(|s| s.0 as u32),
+                above,
+            ),
+            BackendEvent::ActiveWindowMessage { window } => {
+                if let Some(ck) = self.wintoclient(window.0 as u32) {
+                    // 任务栏激活被隐藏的窗口时先恢复显示
+                    if self.clients.get(ck).map_or(false, |c| c.state.is_hidden) {
+                        self.sethidden(ck, false)?;
+                    }
+                    let is_urgent = self
+                        .clients
                         .get(ck)
                         .map(|c| c.state.is_urgent)
                         .unwrap_or(false);
@@ -1283,20 +1805,21 @@ impl Jwm {
                 Ok(())
             }
             BackendEvent::ClientMessage { .. } | BackendEvent::PropertyNotify { .. } => Ok(()),
-            BackendEvent::ButtonRelease { .. } => Ok(()),
+            BackendEvent::ButtonRelease {
+                window,
+                state,
+                detail,
+                ..
+            } => self.on_button_release(window.0 as u32, state, detail),
         }
     }
 
-    fn layout_to_id(l: &LayoutEnum) -> u32 {
-        match *l {
-            LayoutEnum::TILE => 0,
-            LayoutEnum::FLOAT => 1,
-            LayoutEnum::MONOCLE => 2,
-            _ => 0,
-        }
-    }
-    fn id_to_layout(id: u32) -> Rc<LayoutEnum> {
-        Rc::new(LayoutEnum::from(id))
+    // 快照中的布局名找不到时（例如布局被移除）退回默认值
+    fn layout_from_name(name: &str, fallback: &'static dyn Layout) -> &'static dyn Layout {
+        LAYOUTS.get(name).unwrap_or_else(|| {
+            warn!("[layout_from_name] Unknown layout '{}', falling back", name);
+            fallback
+        })
     }
 
     fn atomic_write(path: &str, data: &[u8]) -> std::io::Result<()> {
@@ -1319,15 +1842,31 @@ impl Jwm {
 
     fn save_restart_snapshot(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         let mut snapshot = RestartSnapshot {
-            version: 1,
+            version: 6,
             timestamp: Self::unix_ts(),
             sel_monitor_num: self
                 .sel_mon
                 .and_then(|k| self.monitors.get(k))
                 .map(|m| m.num),
-            current_bar_monitor_id: self.current_bar_monitor_id,
+            current_bar_monitor_id: self
+                .status_bars
+                .keys()
+                .find_map(|k| self.monitors.get(k))
+                .map(|m| m.num),
             monitors: Vec::new(),
             clients: HashMap::new(),
+            scratchpads: self
+                .scratchpads
+                .iter()
+                .filter_map(|(name, &ck)| self.clients.get(ck).map(|c| (name.clone(), c.win)))
+                .collect(),
+            swallowed: self
+                .swallowed
+                .iter()
+                .filter_map(|(&child, &term)| {
+                    Some((self.clients.get(child)?.win, self.clients.get(term)?.win))
+                })
+                .collect(),
         };
 
         // 监视器快照
@@ -1338,15 +1877,9 @@ impl Jwm {
             let pertag_snap = if let Some(p) = m.pertag.as_ref() {
                 let mut lt_pairs = Vec::with_capacity(p.lt_idxs.len());
                 for i in 0..p.lt_idxs.len() {
-                    let id0 = p.lt_idxs[i][0]
-                        .as_ref()
-                        .map(|rc| Self::layout_to_id(&*rc))
-                        .unwrap_or(0);
-                    let id1 = p.lt_idxs[i][1]
-                        .as_ref()
-                        .map(|rc| Self::layout_to_id(&*rc))
-                        .unwrap_or(1);
-                    lt_pairs.push([id0, id1]);
+                    let name0 = p.lt_idxs[i][0].unwrap_or(layout::TILE).name();
+                    let name1 = p.lt_idxs[i][1].unwrap_or(layout::FLOAT).name();
+                    lt_pairs.push([name0.to_string(), name1.to_string()]);
                 }
                 let sel_by_tag = p
                     .sel
@@ -1359,6 +1892,7 @@ impl Jwm {
                     prev_tag: p.prev_tag,
                     n_masters: p.n_masters.clone(),
                     m_facts: p.m_facts.clone(),
+                    gaps: p.gaps.clone(),
                     sel_lts: p.sel_lts.clone(),
                     lt_pairs,
                     show_bars: p.show_bars.clone(),
@@ -1366,14 +1900,15 @@ impl Jwm {
                 }
             } else {
                 // fallback：按 tags_length()+1 填入基本值
-                let len = CONFIG.tags_length() + 1;
+                let len = CONFIG.load().tags_length() + 1;
                 PertagSnapshot {
                     cur_tag: 1,
                     prev_tag: 1,
                     n_masters: vec![m.layout.n_master; len],
                     m_facts: vec![m.layout.m_fact; len],
+                    gaps: vec![m.layout.gaps; len],
                     sel_lts: vec![m.sel_lt; len],
-                    lt_pairs: vec![[0, 1]; len],
+                    lt_pairs: vec![[m.lt[0].name().to_string(), m.lt[1].name().to_string()]; len],
                     show_bars: vec![true; len],
                     sel_by_tag: vec![None; len],
                 }
@@ -1463,25 +1998,30 @@ impl Jwm {
                     m.sel_tags = ms.sel_tags;
 
                     if let Some(p) = m.pertag.as_mut() {
+                        let len = p.n_masters.len();
                         p.cur_tag = ms.pertag.cur_tag;
                         p.prev_tag = ms.pertag.prev_tag;
                         p.n_masters = ms.pertag.n_masters.clone();
                         p.m_facts = ms.pertag.m_facts.clone();
+                        p.gaps = ms.pertag.gaps.clone();
                         p.sel_lts = ms.pertag.sel_lts.clone();
                         p.show_bars = ms.pertag.show_bars.clone();
+                        // 重启前后 tag 数量可能不同，按当前配置补齐或截断
+                        p.resize(len, &m.layout, m.lt);
                         // 重建 lt_idxs
                         for i in 0..p.lt_idxs.len().min(ms.pertag.lt_pairs.len()) {
-                            let [id0, id1] = ms.pertag.lt_pairs[i];
-                            p.lt_idxs[i][0] = Some(Self::id_to_layout(id0));
-                            p.lt_idxs[i][1] = Some(Self::id_to_layout(id1));
+                            let [name0, name1] = &ms.pertag.lt_pairs[i];
+                            p.lt_idxs[i][0] = Some(Self::layout_from_name(name0, layout::TILE));
+                            p.lt_idxs[i][1] = Some(Self::layout_from_name(name1, layout::FLOAT));
                         }
                         // 应用当前 tag 的选择到 WMMonitor
                         let cur = p.cur_tag;
                         m.layout.n_master = p.n_masters[cur];
                         m.layout.m_fact = p.m_facts[cur];
+                        m.layout.gaps = p.gaps[cur];
                         m.sel_lt = p.sel_lts[cur];
-                        m.lt[0] = p.lt_idxs[cur][0].as_ref().unwrap().clone();
-                        m.lt[1] = p.lt_idxs[cur][1].as_ref().unwrap().clone();
+                        m.lt[0] = p.lt_idxs[cur][0].unwrap_or(layout::TILE);
+                        m.lt[1] = p.lt_idxs[cur][1].unwrap_or(layout::FLOAT);
                     }
                 }
             }
@@ -1539,16 +2079,32 @@ impl Jwm {
             }
         }
 
-        // 4) 恢复 sel_mon 与 bar monitor
+        // 4) 恢复 scratchpad 归属（隐藏状态即 tags == 0，已随 client 状态恢复）
+        self.scratchpads = snap
+            .scratchpads
+            .iter()
+            .filter_map(|(name, &win)| self.wintoclient(win).map(|ck| (name.clone(), ck)))
+            .collect();
+        // 被吞噬的终端不在 monitor 顺序中，保持隐藏
+        self.swallowed = snap
+            .swallowed
+            .iter()
+            .filter_map(|(&child, &term)| Some((self.wintoclient(child)?, self.wintoclient(term)?)))
+            .collect();
+
+        // 5) 恢复 sel_mon 与 bar monitor
         if let Some(id) = snap.sel_monitor_num {
             self.sel_mon = self.get_monitor_by_id(id);
         }
         if let Some(id) = snap.current_bar_monitor_id {
-            self.current_bar_monitor_id = Some(id);
-            let _ = self.position_statusbar_on_monitor(id);
+            if !CONFIG.load().status_bar_per_monitor() {
+                if let Some(mon_key) = self.get_monitor_by_id(id) {
+                    let _ = self.move_status_bar(mon_key);
+                }
+            }
         }
 
-        // 5) 一次性更新“可见性 + 叠放 + 焦点”，不要触发布局计算以免改动几何
+        // 6) 一次性更新“可见性 + 叠放 + 焦点”，不要触发布局计算以免改动几何
         // self.arrange(None);
         for &mon_key in self.monitor_order.clone().iter() {
             self.showhide_monitor(mon_key); // 只根据 tag 显示/隐藏，不改变尺寸
@@ -1618,6 +2174,14 @@ impl Jwm {
             .unwrap_or(&[])
     }
 
+    // 显示器上是否有可见的全屏窗口
+    fn has_visible_fullscreen(&self, mon_key: MonitorKey) -> bool {
+        self.get_monitor_clients(mon_key).iter().any(|&ck| {
+            self.clients.get(ck).is_some_and(|c| c.state.is_fullscreen)
+                && self.is_client_visible_on_monitor(ck, mon_key)
+        })
+    }
+
     fn get_sel_mon(&self) -> Option<&WMMonitor> {
         self.sel_mon
             .and_then(|sel_mon_key| self.monitors.get(sel_mon_key))
@@ -1721,7 +2285,7 @@ impl Jwm {
         if let (Some(client), Some(monitor)) =
             (self.clients.get(client_key), self.monitors.get(mon_key))
         {
-            (client.state.tags & monitor.tag_set[monitor.sel_tags]) > 0
+            client.is_visible_on_tagset(monitor.tag_set[monitor.sel_tags])
         } else {
             false
         }
@@ -1732,7 +2296,7 @@ impl Jwm {
         if let Some(client) = self.clients.get(client_key) {
             if let Some(mon_key) = client.mon {
                 if let Some(monitor) = self.monitors.get(mon_key) {
-                    return (client.state.tags & monitor.tag_set[monitor.sel_tags]) > 0;
+                    return client.is_visible_on_tagset(monitor.tag_set[monitor.sel_tags]);
                 }
             }
         }
@@ -1782,11 +2346,9 @@ impl Jwm {
     }
 
     fn wintoclient(&self, win: u32) -> Option<ClientKey> {
-        // 先检查是否为单实例状态栏窗口
-        if let Some(bar_win) = self.status_bar_window {
-            if bar_win == win {
-                return self.status_bar_client;
-            }
+        // 先检查是否为状态栏窗口
+        if let Some(bar_mon_key) = self.bar_monitor_of_window(win) {
+            return self.status_bars.get(bar_mon_key).and_then(|bar| bar.client);
         }
 
         // 再查找常规客户端
@@ -1837,6 +2399,327 @@ impl Jwm {
         Ok(())
     }
 
+    /// 重新加载配置文件；参数非零时对现有窗口重新应用规则
+    pub fn reload(&mut self, arg: &WMArgEnum) -> Result<(), Box<dyn std::error::Error>> {
+        let reapply_rules = matches!(arg, WMArgEnum::Int(v) if *v != 0);
+        self.reload_config(reapply_rules)
+    }
+
+    fn reload_config(&mut self, reapply_rules: bool) -> Result<(), Box<dyn std::error::Error>> {
+        let config_path = Config::get_default_config_path();
+        info!("[reload_config] Reloading {:?}", config_path);
+        let was_per_monitor = CONFIG.load().status_bar_per_monitor();
+        // 解析或校验失败时保留旧配置
+        if let Err(e) = CONFIG.reload_from(&config_path) {
+            error!("[reload_config] Keeping previous config: {}", e);
+            return Err(e.into());
+        }
+
+        // 重新分配颜色并刷新边框
+        self.backend.color_allocator().free_all_theme_pixels()?;
+        Self::apply_color_schemes(self.backend.color_allocator(), CONFIG.load().colors())?;
+        let sel_client = self.get_selected_client_key();
+        let client_keys: Vec<ClientKey> = self.clients.keys().collect();
+        for &client_key in &client_keys {
+            let win = self.clients[client_key].win;
+            let focused = Some(client_key) == sel_client;
+            self.set_window_border_color(win, focused)?;
+            self.grabbuttons(client_key, focused)?;
+        }
+        // 重载后不存在的模式回到默认模式
+        self.cancel_chord()?;
+        if let Some(mode) = self.key_mode.as_deref() {
+            if CONFIG.load().key_mode(mode).is_none() {
+                self.key_mode = None;
+            }
+        }
+        self.grabkeys()?;
+
+        self.resize_tags();
+        for &client_key in &client_keys {
+            if self.clients.contains_key(client_key) {
+                let _ = self.setclienttagprop(client_key);
+            }
+        }
+        self.update_ewmh_desktops()?;
+
+        // 状态栏模式切换：关闭现有状态栏，下次刷新时按新模式启动
+        if CONFIG.load().status_bar_per_monitor() != was_per_monitor {
+            let bar_monitors: Vec<MonitorKey> = self.status_bars.keys().collect();
+            for mon_key in bar_monitors {
+                self.remove_status_bar(mon_key);
+            }
+        }
+
+        if reapply_rules {
+            self.client_rules.clear();
+            for &client_key in &client_keys {
+                if self.clients.contains_key(client_key) {
+                    self.reapply_rules(client_key)?;
+                }
+            }
+        }
+
+        self.arrange(None);
+        self.mark_bar_update_needed_if_visible(None);
+        info!("[reload_config] Config reloaded");
+        Ok(())
+    }
+
+    // 标签数量变化后同步 pertag 数组，并把超出范围的标签收回到有效掩码内
+    fn resize_tags(&mut self) {
+        let len = CONFIG.load().tags_length() + 1;
+        let tagmask = CONFIG.load().tagmask();
+        for (_, monitor) in self.monitors.iter_mut() {
+            for tags in monitor.tag_set.iter_mut() {
+                *tags &= tagmask;
+                if *tags == 0 {
+                    *tags = 1;
+                }
+            }
+            let lts = monitor.lt;
+            if let Some(pertag) = monitor.pertag.as_mut() {
+                pertag.resize(len, &monitor.layout, lts);
+            }
+        }
+        for (_, client) in self.clients.iter_mut() {
+            // 隐藏的 scratchpad 以 tags 为 0 表示，保持不变
+            if client.state.tags != 0 && client.state.tags & tagmask == 0 {
+                client.state.tags = client
+                    .mon
+                    .and_then(|m| self.monitors.get(m))
+                    .map_or(1, |m| m.tag_set[m.sel_tags]);
+            } else {
+                client.state.tags &= tagmask;
+            }
+        }
+    }
+
+    // 主循环每个 tick 调用：配置文件变化时自动重载
+    fn check_config_changes(&mut self) {
+        let changed = self
+            .config_watcher
+            .as_ref()
+            .map_or(false, |watcher| watcher.changed());
+        if changed {
+            let _ = self.reload_config(false);
+        }
+    }
+
+    fn process_ipc_requests(&mut self) {
+        let requests = match self.ipc_server.as_mut() {
+            Some(server) => server.poll(),
+            None => return,
+        };
+        for (id, request) in requests {
+            let response = match request {
+                Ok(request) => self.handle_ipc_request(request),
+                Err(e) => IpcResponse::error(format!("invalid request: {}", e)),
+            };
+            if let Some(server) = self.ipc_server.as_mut() {
+                server.reply(id, &response);
+            }
+        }
+    }
+
+    fn handle_ipc_request(&mut self, request: IpcRequest) -> IpcResponse {
+        match request {
+            IpcRequest::Run { function, argument } => {
+                info!("[ipc] run {} {:?}", function, argument);
+                let argument = argument.unwrap_or(ArgumentConfig::Int(0));
+                let resolved = CONFIG.load().resolve_function(&function, &argument);
+                match resolved {
+                    Ok((func, arg)) => match func(self, &arg) {
+                        Ok(()) => IpcResponse::ok(None),
+                        Err(e) => IpcResponse::error(e.to_string()),
+                    },
+                    Err(e) => IpcResponse::error(e),
+                }
+            }
+            IpcRequest::GetTree => match serde_json::to_value(self.ipc_tree()) {
+                Ok(tree) => IpcResponse::ok(Some(tree)),
+                Err(e) => IpcResponse::error(e.to_string()),
+            },
+            IpcRequest::GetConfig => match CONFIG.load().to_json() {
+                Ok(config) => IpcResponse::ok(Some(config)),
+                Err(e) => IpcResponse::error(e.to_string()),
+            },
+            // 订阅由 IpcServer::poll 直接处理，不会到达这里
+            IpcRequest::Subscribe { .. } => IpcResponse::ok(None),
+        }
+    }
+
+    fn emit_ipc_event(&mut self, event: IpcEvent) {
+        if let Some(server) = self.ipc_server.as_mut() {
+            server.broadcast(&event);
+        }
+    }
+
+    fn client_mon_num(&self, client_key: ClientKey) -> Option<i32> {
+        self.clients
+            .get(client_key)
+            .and_then(|c| c.mon)
+            .and_then(|mon_key| self.monitors.get(mon_key))
+            .map(|m| m.num)
+    }
+
+    // 标签和布局可能由按键、状态栏、IPC 等多处修改，统一在主循环中比较后发送
+    fn emit_view_events(&mut self) {
+        let mut events = Vec::new();
+        for &mon_key in &self.monitor_order {
+            let mon = match self.monitors.get(mon_key) {
+                Some(mon) => mon,
+                None => continue,
+            };
+            let state = (mon.tag_set[mon.sel_tags], mon.lt[mon.sel_lt].name());
+            // 新出现的显示器已有 monitor_added 事件，只记录不发送
+            let previous = match self.ipc_view_state.insert(mon.num, state) {
+                Some(previous) => previous,
+                None => continue,
+            };
+            if previous.0 != state.0 {
+                events.push(IpcEvent::TagView {
+                    monitor: mon.num,
+                    selected_tags: state.0,
+                });
+            }
+            if previous.1 != state.1 {
+                events.push(IpcEvent::LayoutChanged {
+                    monitor: mon.num,
+                    layout: state.1.to_string(),
+                    symbol: mon.lt_symbol.clone(),
+                });
+            }
+        }
+        for event in events {
+            self.emit_ipc_event(event);
+        }
+    }
+
+    fn emit_focus_event(&mut self, client_key_opt: Option<ClientKey>) {
+        let client = client_key_opt.and_then(|ck| self.clients.get(ck));
+        let window = client.map(|c| c.win);
+        if window == self.ipc_focused_window {
+            return;
+        }
+        self.ipc_focused_window = window;
+        let event = IpcEvent::Focus {
+            window,
+            monitor: self.get_sel_mon().map(|m| m.num),
+            class: client.map(|c| c.class.clone()).unwrap_or_default(),
+            title: client.map(|c| c.name.clone()).unwrap_or_default(),
+        };
+        self.emit_ipc_event(event);
+    }
+
+    fn emit_urgent_event(&mut self, client_key: ClientKey) {
+        let event = match self.clients.get(client_key) {
+            Some(c) => IpcEvent::Urgent {
+                window: c.win,
+                monitor: self.client_mon_num(client_key),
+                tags: c.state.tags,
+            },
+            None => return,
+        };
+        self.emit_ipc_event(event);
+    }
+
+    fn ipc_tree(&self) -> ipc::TreeInfo {
+        let config = CONFIG.load();
+        let focused_client = self.get_selected_client_key();
+        let monitors = self
+            .monitor_order
+            .iter()
+            .filter_map(|&mon_key| {
+                let mon = self.monitors.get(mon_key)?;
+                let clients: Vec<ipc::ClientInfo> = self
+                    .monitor_clients
+                    .get(mon_key)
+                    .map(|keys| {
+                        keys.iter()
+                            .filter_map(|&ck| {
+                                let c = self.clients.get(ck)?;
+                                Some(ipc::ClientInfo {
+                                    window: c.win,
+                                    pid: c.pid,
+                                    name: c.name.clone(),
+                                    class: c.class.clone(),
+                                    instance: c.instance.clone(),
+                                    tags: c.state.tags,
+                                    geometry: ipc::Rect {
+                                        x: c.geometry.x,
+                                        y: c.geometry.y,
+                                        width: c.geometry.w,
+                                        height: c.geometry.h,
+                                    },
+                                    border_width: c.geometry.border_w,
+                                    focused: focused_client == Some(ck),
+                                    floating: c.state.is_floating,
+                                    fullscreen: c.state.is_fullscreen,
+                                    sticky: c.state.is_sticky,
+                                    urgent: c.state.is_urgent,
+                                    fixed: c.state.is_fixed,
+                                })
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let selected_tags = mon.tag_set[mon.sel_tags];
+                // 与状态栏使用同一规则（sticky 窗口不算占用）
+                let (occupied_tags, urgent_tags) = self.calculate_tag_masks(mon_key);
+                let tags = (0..config.tags_length())
+                    .map(|i| {
+                        let mask = 1 << i;
+                        ipc::TagInfo {
+                            index: i,
+                            name: config.tag_name(i),
+                            mask,
+                            selected: selected_tags & mask != 0,
+                            occupied: occupied_tags & mask != 0,
+                            urgent: urgent_tags & mask != 0,
+                        }
+                    })
+                    .collect();
+                let g = &mon.geometry;
+                Some(ipc::MonitorInfo {
+                    num: mon.num,
+                    output: mon.output.name.clone(),
+                    focused: self.sel_mon == Some(mon_key),
+                    geometry: ipc::Rect {
+                        x: g.m_x,
+                        y: g.m_y,
+                        width: g.m_w,
+                        height: g.m_h,
+                    },
+                    work_area: ipc::Rect {
+                        x: g.w_x,
+                        y: g.w_y,
+                        width: g.w_w,
+                        height: g.w_h,
+                    },
+                    selected_tags,
+                    layout: mon.lt[mon.sel_lt].name().to_string(),
+                    layout_symbol: mon.lt_symbol.clone(),
+                    m_fact: mon.layout.m_fact,
+                    n_master: mon.layout.n_master,
+                    tags,
+                    clients,
+                })
+            })
+            .collect();
+
+        ipc::TreeInfo {
+            focused_monitor: self
+                .sel_mon
+                .and_then(|k| self.monitors.get(k))
+                .map(|m| m.num),
+            focused_window: focused_client
+                .and_then(|ck| self.clients.get(ck))
+                .map(|c| c.win),
+            monitors,
+        }
+    }
+
     fn is_bar_visible_on_mon(&self, mon_key: MonitorKey) -> bool {
         if let Some(m) = self.monitors.get(mon_key) {
             if let Some(p) = m.pertag.as_ref() {
@@ -1998,7 +2881,7 @@ impl Jwm {
             .unwrap_or(false);
 
         // 只有在需要时才应用尺寸提示
-        if !CONFIG.behavior().resize_hints && !is_floating {
+        if !CONFIG.load().behavior().resize_hints && !is_floating {
             return Ok(false);
         }
 
@@ -2173,6 +3056,9 @@ impl Jwm {
         // 终止状态栏进程
         self.cleanup_statusbar_processes()?;
 
+        // 关闭 IPC socket（Drop 时删除 socket 文件）
+        self.ipc_server = None;
+
         // 清理共享内存（如果需要显式清理）
         self.cleanup_shared_memory_resources()?;
 
@@ -2259,13 +3145,23 @@ impl Jwm {
         Ok(())
     }
 
-    /// 清理状态栏进程
+    /// 清理所有状态栏进程
     fn cleanup_statusbar_processes(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let mut child = if let Some(child) = self.status_bar_child.take() {
-            child
-        } else {
-            return Ok(());
-        };
+        for bar in self.status_bars.values_mut() {
+            if let Some(child) = bar.child.take() {
+                if let Err(e) = Self::terminate_bar_process(child) {
+                    error!(
+                        "[cleanup_statusbar_processes] {} failed: {}",
+                        bar.shared_path, e
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 终止单个状态栏进程：先 SIGTERM，超时后 SIGKILL
+    fn terminate_bar_process(mut child: Child) -> Result<(), Box<dyn std::error::Error>> {
         // 获取进程 ID
         let pid = child.id();
         let nix_pid = Pid::from_raw(pid as i32);
@@ -2301,7 +3197,6 @@ impl Jwm {
             warn!("Graceful termination timeout, forcing kill");
         }
         // 强制终止
-        self.status_bar_pid = None;
         signal::kill(nix_pid, Signal::SIGKILL)?;
 
         Ok(())
@@ -2309,14 +3204,16 @@ impl Jwm {
 
     /// 清理共享内存资源
     fn cleanup_shared_memory_resources(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(rb) = self.status_bar_shmem.take() {
-            drop(rb);
-        }
-        #[cfg(unix)]
-        {
-            if std::path::Path::new(&SHARED_PATH).exists() {
-                if let Err(e) = std::fs::remove_file(&SHARED_PATH) {
-                    warn!("Failed to remove {}: {}", SHARED_PATH, e);
+        for bar in self.status_bars.values_mut() {
+            if let Some(rb) = bar.shmem.take() {
+                drop(rb);
+            }
+            #[cfg(unix)]
+            {
+                if std::path::Path::new(&bar.shared_path).exists() {
+                    if let Err(e) = std::fs::remove_file(&bar.shared_path) {
+                        warn!("Failed to remove {}: {}", bar.shared_path, e);
+                    }
                 }
             }
         }
@@ -2370,12 +3267,46 @@ impl Jwm {
         Ok(())
     }
 
+    // 插拔显示器时 RandR 会连续发出多条通知，等安静后统一处理一次
+    fn process_output_changes(&mut self) {
+        const OUTPUT_CHANGE_DEBOUNCE: Duration = Duration::from_millis(250);
+        match self.pending_output_change {
+            Some(at) if at.elapsed() >= OUTPUT_CHANGE_DEBOUNCE => {
+                self.pending_output_change = None;
+            }
+            _ => return,
+        }
+        if let Err(e) = self.apply_output_change() {
+            error!("[process_output_changes] {}", e);
+        }
+    }
+
+    fn apply_output_change(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        // 根窗口尺寸可能随之变化，单显示器时 updategeom 依赖它
+        let root = self.backend.root_window();
+        let geom = self.backend.window_ops().get_geometry_translated(root)?;
+        let resized = self.s_w != geom.w as i32 || self.s_h != geom.h as i32;
+        self.s_w = geom.w as i32;
+        self.s_h = geom.h as i32;
+        if self.updategeom() || resized {
+            info!(
+                "[apply_output_change] Outputs changed, {} monitor(s)",
+                self.monitor_order.len()
+            );
+            self.handle_screen_geometry_change()?;
+        }
+        Ok(())
+    }
+
     fn handle_screen_geometry_change(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         // info!("[handle_screen_geometry_change]");
         // 遍历所有显示器
         for &mon_key in self.monitor_order.clone().iter() {
             self.update_fullscreen_clients_on_monitor(mon_key)?;
         }
+        // 状态栏所在显示器可能已被移除，或几何发生变化
+        self.sync_status_bars_with_monitors()?;
+        self.mark_bar_update_needed_if_visible(None);
         // 重新聚焦和排列
         self.focus(None)?;
         self.arrange(None);
@@ -2469,25 +3400,56 @@ impl Jwm {
         // 探测 NumLock（KeyOps）
         self.setup_modifier_masks()?;
 
-        // 清除旧的抓取
-        self.backend
-            .key_ops()
-            .clear_key_grabs(self.backend.root_window())?;
-
-        // 构造绑定列表（通用 Mods + KeySym）
-        let bindings: Vec<(Mods, KeySym)> = CONFIG
-            .get_keys()
+        // 构造绑定列表（通用 Mods + KeySym）：只抓取序列的第一个键，后续键在序列进行中主动抓取键盘
+        let mut bindings: Vec<(Mods, KeySym)> = self
+            .active_keys()
             .iter()
-            .map(|k| (k.mask, k.key_sym))
+            .map(|key| (key.mask, key.key_sym))
             .collect();
+        if self.key_mode.is_some() {
+            // 非默认模式下 Escape 返回默认模式
+            bindings.push((Mods::empty(), k::KEY_Escape));
+        }
 
-        // 抓取
-        self.backend.key_ops().grab_keys(
-            self.backend.root_window(),
-            &bindings,
-            self.numlock_mask_bits,
-        )?;
+        // 在服务器抓取内清除旧的抓取并重新抓取，切换模式时不会漏掉或误投按键
+        let root = self.backend.root_window();
+        self.backend.window_ops().grab_server()?;
+        let result = self.backend.key_ops().clear_key_grabs(root).and_then(|_| {
+            self.backend
+                .key_ops()
+                .grab_keys(root, &bindings, self.numlock_mask_bits)
+        });
+        self.backend.window_ops().ungrab_server()?;
+        self.backend.window_ops().flush()?;
+        result
+    }
+
+    fn setsticky(
+        &mut self,
+        client_key: ClientKey,
+        sticky: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (win, mon_key) = match self.clients.get_mut(client_key) {
+            Some(client) if client.state.is_sticky != sticky => {
+                client.state.is_sticky = sticky;
+                (client.win, client.mon)
+            }
+            Some(_) => return Ok(()),
+            None => return Err("Client not found".into()),
+        };
+        info!("[setsticky] 0x{:x} sticky: {}", win, sticky);
 
+        self.sync_net_wm_state(client_key)?;
+
+        // 取消 sticky 后窗口可能不再属于当前 tag
+        self.focus(None)?;
+        if let Some(mon_key) = mon_key {
+            self.arrange(Some(mon_key));
+        }
+        let _ = self.setclienttagprop(client_key);
+        // sticky 窗口不计入 tag 占用，刷新状态栏
+        let mon_num = mon_key.and_then(|k| self.monitors.get(k)).map(|m| m.num);
+        self.mark_bar_update_needed_if_visible(mon_num);
         Ok(())
     }
 
@@ -2509,11 +3471,6 @@ impl Jwm {
             .unwrap_or(false);
 
         if fullscreen && !is_fullscreen {
-            // 设置全屏逻辑
-            self.backend
-                .property_ops()
-                .set_fullscreen_state(WindowId(win.into()), fullscreen)?;
-
             // 更新客户端状态
             if let Some(client) = self.clients.get_mut(client_key) {
                 client.state.is_fullscreen = true;
@@ -2522,6 +3479,7 @@ impl Jwm {
                 client.geometry.border_w = 0;
                 client.state.is_floating = true;
             }
+            self.sync_net_wm_state(client_key)?;
 
             // 获取监视器信息并调整窗口大小
             if let Some(mon_key) = self.clients.get(client_key).and_then(|c| c.mon) {
@@ -2543,10 +3501,6 @@ impl Jwm {
             self.backend.window_ops().flush()?;
         } else if !fullscreen && is_fullscreen {
             // 取消全屏逻辑
-            self.backend
-                .property_ops()
-                .set_fullscreen_state(WindowId(win.into()), fullscreen)?;
-
             if let Some(client) = self.clients.get_mut(client_key) {
                 client.state.is_fullscreen = false;
                 client.state.is_floating = client.state.old_state;
@@ -2556,6 +3510,7 @@ impl Jwm {
                 client.geometry.w = client.geometry.old_w;
                 client.geometry.h = client.geometry.old_h;
             }
+            self.sync_net_wm_state(client_key)?;
 
             // 恢复窗口大小
             let (x, y, w, h) = if let Some(client) = self.clients.get(client_key) {
@@ -2571,8 +3526,9 @@ impl Jwm {
 
             self.resizeclient(client_key, x, y, w, h)?;
 
-            // 重新排列
+            // 重新排列；叠放顺序可能不变，清除缓存以便 dock 重新置顶
             if let Some(mon_key) = self.clients.get(client_key).and_then(|c| c.mon) {
+                self.last_stacking.remove(mon_key);
                 self.arrange(Some(mon_key));
             }
         }
@@ -2580,55 +3536,235 @@ impl Jwm {
         Ok(())
     }
 
-    /// 更新 seturgent 方法签名
-    fn seturgent(
+    /// 把客户端状态整体回写到 _NET_WM_STATE
+    fn sync_net_wm_state(&self, client_key: ClientKey) -> Result<(), Box<dyn std::error::Error>> {
+        let client = self.clients.get(client_key).ok_or("Client not found")?;
+        self.backend
+            .property_ops()
+            .set_net_wm_states(WindowId(client.win.into()), &client.net_wm_states())
+    }
+
+    /// 处理客户端请求的单个 _NET_WM_STATE 变化
+    fn set_net_wm_state(
         &mut self,
         client_key: ClientKey,
-        urgent: bool,
+        state: NetWmState,
+        on: bool,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        // 更新客户端状态
-        if let Some(client) = self.clients.get_mut(client_key) {
-            client.state.is_urgent = urgent;
-        } else {
-            return Err("Client not found".into());
+        match state {
+            NetWmState::Fullscreen => self.setfullscreen(client_key, on),
+            NetWmState::Sticky => self.setsticky(client_key, on),
+            NetWmState::Above | NetWmState::Below => self.setlayer(client_key, state, on),
+            NetWmState::MaximizedVert | NetWmState::MaximizedHorz => {
+                self.setmaximized(client_key, state, on)
+            }
+            NetWmState::Hidden => self.sethidden(client_key, on),
+            // 已聚焦的窗口无需提醒
+            NetWmState::DemandsAttention => {
+                if on && self.is_client_selected(client_key) {
+                    return Ok(());
+                }
+                self.seturgent(client_key, on)
+            }
+            NetWmState::SkipTaskbar => {
+                if let Some(client) = self.clients.get_mut(client_key) {
+                    client.state.skip_taskbar = on;
+                }
+                self.sync_net_wm_state(client_key)
+            }
         }
-
-        // 获取窗口ID
-        let win = self
-            .clients
-            .get(client_key)
-            .map(|client| client.win)
-            .ok_or("Client not found after update")?;
-
-        self.set_urgent_flag(win, urgent)?;
-
-        Ok(())
-    }
-
-    fn set_urgent_flag(&self, win: u32, urgent: bool) -> Result<(), Box<dyn std::error::Error>> {
-        self.backend
-            .property_ops()
-            .set_urgent_hint(WindowId(win as u64), urgent)
     }
 
-    /// 显示/隐藏指定监视器上的窗口
-    fn showhide_monitor(&mut self, mon_key: MonitorKey) {
-        // 获取该监视器的堆栈顺序客户端列表
-        if let Some(stack_clients) = self.monitor_stack.get(mon_key).cloned() {
-            for client_key in stack_clients {
-                self.showhide_client(client_key, mon_key);
+    /// above/below 互斥，restack 时分别放在最上层和最下层
+    fn setlayer(
+        &mut self,
+        client_key: ClientKey,
+        state: NetWmState,
+        on: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mon_key = match self.clients.get_mut(client_key) {
+            Some(client) => {
+                let above = state == NetWmState::Above;
+                if above {
+                    client.state.is_above = on;
+                } else {
+                    client.state.is_below = on;
+                }
+                if on {
+                    if above {
+                        client.state.is_below = false;
+                    } else {
+                        client.state.is_above = false;
+                    }
+                }
+                client.mon
             }
+            None => return Err("Client not found".into()),
+        };
+        self.sync_net_wm_state(client_key)?;
+        if mon_key.is_some() {
+            self.restack(mon_key)?;
         }
+        Ok(())
     }
 
-    /// 显示/隐藏单个客户端
-    fn showhide_client(&mut self, client_key: ClientKey, mon_key: MonitorKey) {
-        let is_visible = self.is_client_visible_on_monitor(client_key, mon_key);
+    /// 浮动窗口按方向铺满工作区，平铺窗口只记录状态，大小由布局决定
+    fn setmaximized(
+        &mut self,
+        client_key: ClientKey,
+        state: NetWmState,
+        on: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (was_vert, was_horz) = match self.clients.get_mut(client_key) {
+            Some(client) => {
+                let before = (
+                    client.state.is_maximized_vert,
+                    client.state.is_maximized_horz,
+                );
+                if state == NetWmState::MaximizedVert {
+                    client.state.is_maximized_vert = on;
+                } else {
+                    client.state.is_maximized_horz = on;
+                }
+                before
+            }
+            None => return Err("Client not found".into()),
+        };
+        self.sync_net_wm_state(client_key)?;
 
-        if is_visible {
-            self.show_client(client_key);
-        } else {
-            self.hide_client(client_key);
+        let client = &self.clients[client_key];
+        if !client.state.is_floating || client.state.is_fullscreen {
+            return Ok(());
+        }
+        let (vert, horz) = (
+            client.state.is_maximized_vert,
+            client.state.is_maximized_horz,
+        );
+        let bw = client.geometry.border_w;
+        let (mut x, mut y, mut w, mut h) = (
+            client.geometry.x,
+            client.geometry.y,
+            client.geometry.w,
+            client.geometry.h,
+        );
+        let area = match client.mon.and_then(|mon_key| self.monitors.get(mon_key)) {
+            Some(mon) => (
+                mon.geometry.w_x,
+                mon.geometry.w_y,
+                mon.geometry.w_w,
+                mon.geometry.w_h,
+            ),
+            None => return Ok(()),
+        };
+        if !was_vert && !was_horz {
+            self.maximize_restore.insert(client_key, (x, y, w, h));
+        }
+        let restore = self
+            .maximize_restore
+            .get(client_key)
+            .copied()
+            .unwrap_or((x, y, w, h));
+        if horz {
+            x = area.0;
+            w = area.2 - 2 * bw;
+        } else if was_horz {
+            x = restore.0;
+            w = restore.2;
+        }
+        if vert {
+            y = area.1;
+            h = area.3 - 2 * bw;
+        } else if was_vert {
+            y = restore.1;
+            h = restore.3;
+        }
+        if !vert && !horz {
+            self.maximize_restore.remove(client_key);
+        }
+        self.resizeclient(client_key, x, y, w.max(1), h.max(1))
+    }
+
+    /// 最小化：窗口移出布局并设为 IconicState，任务栏激活或移除状态时恢复
+    fn sethidden(
+        &mut self,
+        client_key: ClientKey,
+        hidden: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (win, mon_key) = match self.clients.get_mut(client_key) {
+            Some(client) if client.state.is_hidden != hidden => {
+                client.state.is_hidden = hidden;
+                (client.win, client.mon)
+            }
+            Some(_) => return Ok(()),
+            None => return Err("Client not found".into()),
+        };
+        info!("[sethidden] 0x{:x} hidden: {}", win, hidden);
+        self.sync_net_wm_state(client_key)?;
+        let state = if hidden { ICONIC_STATE } else { NORMAL_STATE };
+        self.setclientstate(win, state.into())?;
+
+        self.focus(None)?;
+        if let Some(mon_key) = mon_key {
+            self.arrange(Some(mon_key));
+        }
+        let mon_num = mon_key.and_then(|k| self.monitors.get(k)).map(|m| m.num);
+        self.mark_bar_update_needed_if_visible(mon_num);
+        Ok(())
+    }
+
+    /// 更新 seturgent 方法签名
+    fn seturgent(
+        &mut self,
+        client_key: ClientKey,
+        urgent: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // 更新客户端状态
+        let was_urgent = if let Some(client) = self.clients.get_mut(client_key) {
+            std::mem::replace(&mut client.state.is_urgent, urgent)
+        } else {
+            return Err("Client not found".into());
+        };
+        if urgent && !was_urgent {
+            self.emit_urgent_event(client_key);
+        }
+
+        // 获取窗口ID
+        let win = self
+            .clients
+            .get(client_key)
+            .map(|client| client.win)
+            .ok_or("Client not found after update")?;
+
+        self.set_urgent_flag(win, urgent)?;
+        self.sync_net_wm_state(client_key)?;
+
+        Ok(())
+    }
+
+    fn set_urgent_flag(&self, win: u32, urgent: bool) -> Result<(), Box<dyn std::error::Error>> {
+        self.backend
+            .property_ops()
+            .set_urgent_hint(WindowId(win as u64), urgent)
+    }
+
+    /// 显示/隐藏指定监视器上的窗口
+    fn showhide_monitor(&mut self, mon_key: MonitorKey) {
+        // 获取该监视器的堆栈顺序客户端列表
+        if let Some(stack_clients) = self.monitor_stack.get(mon_key).cloned() {
+            for client_key in stack_clients {
+                self.showhide_client(client_key, mon_key);
+            }
+        }
+    }
+
+    /// 显示/隐藏单个客户端
+    fn showhide_client(&mut self, client_key: ClientKey, mon_key: MonitorKey) {
+        let is_visible = self.is_client_visible_on_monitor(client_key, mon_key);
+
+        if is_visible {
+            self.show_client(client_key);
+        } else {
+            self.hide_client(client_key);
         }
     }
 
@@ -2757,30 +3893,48 @@ impl Jwm {
         Ok(())
     }
 
-    fn createmon(&mut self, show_bar: bool) -> WMMonitor {
+    // output/index 用于匹配配置中的 [[monitors]] 默认值
+    fn createmon(&mut self, output: &str, index: usize) -> WMMonitor {
         // info!("[createmon]");
         let mut m: WMMonitor = WMMonitor::new();
-        m.tag_set[0] = 1;
-        m.tag_set[1] = 1;
-        m.layout.m_fact = CONFIG.m_fact();
-        m.layout.n_master = CONFIG.n_master();
-        m.lt[0] = Rc::new(LayoutEnum::TILE);
-        m.lt[1] = Rc::new(LayoutEnum::FLOAT);
-        m.lt_symbol = m.lt[0].symbol().to_string();
+        let config = CONFIG.load();
+        let mon_config = config.monitor_config(output, index);
+        let tag = mon_config
+            .and_then(|mc| mc.tag)
+            .filter(|tag| (1..=config.tags_length()).contains(tag))
+            .unwrap_or(1);
+        m.tag_set[0] = 1 << (tag - 1);
+        m.tag_set[1] = 1 << (tag - 1);
+        m.layout.m_fact = mon_config
+            .and_then(|mc| mc.m_fact)
+            .unwrap_or(config.m_fact());
+        m.layout.n_master = mon_config
+            .and_then(|mc| mc.n_master)
+            .unwrap_or(config.n_master());
+        m.layout.gaps = config.gaps();
+        let show_bar = mon_config
+            .and_then(|mc| mc.show_bar)
+            .unwrap_or(config.show_bar());
         m.pertag = Some(Pertag::new(show_bar));
         let ref_pertag = m.pertag.as_mut().unwrap();
-        ref_pertag.cur_tag = 1;
-        ref_pertag.prev_tag = 1;
-        let default_layout_0 = m.lt[0].clone();
-        let default_layout_1 = m.lt[1].clone();
-        for i in 0..=CONFIG.tags_length() {
+        ref_pertag.cur_tag = tag;
+        ref_pertag.prev_tag = tag;
+        for i in 0..=config.tags_length() {
             ref_pertag.n_masters[i] = m.layout.n_master;
             ref_pertag.m_facts[i] = m.layout.m_fact;
-
-            ref_pertag.lt_idxs[i][0] = Some(default_layout_0.clone());
-            ref_pertag.lt_idxs[i][1] = Some(default_layout_1.clone());
+            ref_pertag.gaps[i] = m.layout.gaps;
+
+            let tag_layout = mon_config
+                .and_then(|mc| mc.tag_layout(i))
+                .and_then(|name| LAYOUTS.get(name))
+                .unwrap_or(layout::TILE);
+            ref_pertag.lt_idxs[i][0] = Some(tag_layout);
+            ref_pertag.lt_idxs[i][1] = Some(layout::FLOAT);
             ref_pertag.sel_lts[i] = m.sel_lt;
         }
+        m.lt[0] = ref_pertag.lt_idxs[tag][0].unwrap_or(layout::TILE);
+        m.lt[1] = layout::FLOAT;
+        m.lt_symbol = m.lt[0].symbol().to_string();
         info!("[createmon]: {}", m);
         return m;
     }
@@ -2808,16 +3962,14 @@ impl Jwm {
         &mut self,
         event_window: u32,
     ) -> Result<bool, Box<dyn std::error::Error>> {
-        if Some(event_window) == self.status_bar_window {
-            if let Some(cur_bar_mon_id) = self.current_bar_monitor_id {
-                if let Some(target_monitor_key) = self.get_monitor_by_id(cur_bar_mon_id) {
-                    if Some(target_monitor_key) != self.sel_mon {
-                        let current_sel = self.get_selected_client_key();
-                        self.unfocus_client_opt(current_sel, true)?;
-                        self.sel_mon = Some(target_monitor_key);
-                        self.focus(None)?;
-                    }
-                }
+        if let Some(target_monitor_key) = self.bar_monitor_of_window(event_window) {
+            if Some(target_monitor_key) != self.sel_mon
+                && self.monitors.contains_key(target_monitor_key)
+            {
+                let current_sel = self.get_selected_client_key();
+                self.unfocus_client_opt(current_sel, true)?;
+                self.sel_mon = Some(target_monitor_key);
+                self.focus(None)?;
             }
             return Ok(true);
         }
@@ -2849,6 +4001,9 @@ impl Jwm {
     }
 
     fn destroynotify(&mut self, window: u32) -> Result<(), Box<dyn std::error::Error>> {
+        if self.docks.contains_key(&window) {
+            return self.remove_dock(window);
+        }
         let c = self.wintoclient(window);
         if c.is_some() {
             self.unmanage(c, true)?;
@@ -2859,33 +4014,74 @@ impl Jwm {
     fn arrangemon(&mut self, mon_key: MonitorKey) {
         info!("[arrangemon]");
 
-        // 获取布局类型和更新符号
-        let (layout_type, layout_symbol) = if let Some(monitor) = self.monitors.get(mon_key) {
-            let sel_lt = monitor.sel_lt;
-            let layout = &monitor.lt[sel_lt];
-            (layout.clone(), layout.symbol().to_string())
+        // 获取当前布局与工作区
+        let (layout, area, params) = if let Some(monitor) = self.monitors.get(mon_key) {
+            let client_y_offset = self.get_client_y_offset(monitor);
+            (
+                monitor.lt[monitor.sel_lt],
+                LayoutArea {
+                    x: monitor.geometry.w_x,
+                    y: monitor.geometry.w_y + client_y_offset,
+                    w: monitor.geometry.w_w,
+                    h: monitor.geometry.w_h - client_y_offset,
+                },
+                LayoutParams {
+                    n_master: monitor.layout.n_master,
+                    m_fact: monitor.layout.m_fact,
+                    gaps: monitor.layout.gaps,
+                },
+            )
         } else {
             warn!("[arrangemon] Monitor {:?} not found", mon_key);
             return;
         };
 
         // 更新布局符号
+        let visible_count = self.count_visible_clients(mon_key);
         if let Some(monitor) = self.monitors.get_mut(mon_key) {
-            monitor.lt_symbol = layout_symbol;
+            monitor.lt_symbol = layout.bar_symbol(visible_count);
             info!(
-                "[arrangemon] sel_lt: {}, ltsymbol: {:?}",
-                monitor.sel_lt, monitor.lt_symbol
+                "[arrangemon] sel_lt: {}, layout: {}, ltsymbol: {:?}",
+                monitor.sel_lt,
+                layout.name(),
+                monitor.lt_symbol
             );
         }
 
         // 应用布局
-        match *layout_type {
-            LayoutEnum::TILE => self.tile(mon_key),
-            LayoutEnum::MONOCLE => self.monocle(mon_key),
-            LayoutEnum::FLOAT | _ => {}
+        if layout.is_floating() {
+            return;
+        }
+        let clients = self.collect_tileable_clients(mon_key);
+        if clients.is_empty() {
+            return;
+        }
+        // 关闭间隙，或开启 smart_gaps 且只有一个平铺窗口时不留间隙
+        let gaps = if !self.gaps_enabled || (CONFIG.load().smart_gaps() && clients.len() == 1) {
+            layout::Gaps::NONE
+        } else {
+            params.gaps
+        };
+        let params = LayoutParams { gaps, ..params };
+        let rects = layout.arrange(gaps.shrink(area), &clients, params);
+        for (client, rect) in clients.iter().zip(rects) {
+            self.resize_client(client.key, rect.x, rect.y, rect.w, rect.h, false);
         }
     }
 
+    // 统计显示器上可见的客户端数量（含浮动）
+    fn count_visible_clients(&self, mon_key: MonitorKey) -> usize {
+        self.monitor_clients
+            .get(mon_key)
+            .map(|client_keys| {
+                client_keys
+                    .iter()
+                    .filter(|&&ck| self.is_client_visible_on_monitor(ck, mon_key))
+                    .count()
+            })
+            .unwrap_or(0)
+    }
+
     fn dirtomon(&mut self, dir: &i32) -> Option<MonitorKey> {
         let selected_monitor_key = self.sel_mon?; // Return None if sel_mon is None
         if self.monitor_order.is_empty() {
@@ -2911,27 +4107,34 @@ impl Jwm {
         }
     }
 
-    fn ensure_bar_is_running(&mut self, shared_path: &str) {
-        if let Some(child) = self.status_bar_child.as_mut() {
+    fn ensure_bar_is_running(&mut self, mon_key: MonitorKey) {
+        let bar = match self.status_bars.get_mut(mon_key) {
+            Some(bar) => bar,
+            None => return,
+        };
+        if let Some(child) = bar.child.as_mut() {
             if child.try_wait().ok().flatten().is_none() {
                 return; // 仍在运行
             }
-            self.status_bar_child = None;
-            self.status_bar_pid = None;
+            bar.child = None;
         }
 
         let mut command = if cfg!(feature = "nixgl") {
             let mut cmd = Command::new("nixGL");
-            cmd.arg(CONFIG.status_bar_name());
+            cmd.arg(CONFIG.load().status_bar_name());
             cmd
         } else {
-            Command::new(CONFIG.status_bar_name())
+            Command::new(CONFIG.load().status_bar_name())
         };
-        command.arg(shared_path);
+        command.arg(&bar.shared_path);
 
         if let Ok(child) = command.spawn() {
-            self.status_bar_pid = Some(child.id());
-            self.status_bar_child = Some(child);
+            info!(
+                "[ensure_bar_is_running] Spawned bar {} for {}",
+                child.id(),
+                bar.shared_path
+            );
+            bar.child = Some(child);
         }
     }
 
@@ -2948,16 +4151,22 @@ impl Jwm {
         // 1) 从顶部到下的栈
         let stack = self.get_monitor_stack(mon_key);
 
-        // 2) 分离 tiled 与 floating（仅可见）
+        // 2) 分层（仅可见）：below < tiled < floating < above
+        let mut below_bottom_to_top: Vec<u32> = Vec::new();
         let mut tiled_bottom_to_top: Vec<u32> = Vec::new();
         let mut floating_bottom_to_top: Vec<u32> = Vec::new();
+        let mut above_bottom_to_top: Vec<u32> = Vec::new();
 
         for &ck in stack.iter().rev() {
             if let Some(c) = self.clients.get(ck) {
                 if !self.is_client_visible_on_monitor(ck, mon_key) {
                     continue;
                 }
-                if c.state.is_floating {
+                if c.state.is_above {
+                    above_bottom_to_top.push(c.win);
+                } else if c.state.is_below {
+                    below_bottom_to_top.push(c.win);
+                } else if c.state.is_floating {
                     floating_bottom_to_top.push(c.win);
                 } else {
                     tiled_bottom_to_top.push(c.win);
@@ -2965,23 +4174,35 @@ impl Jwm {
             }
         }
 
-        // 3) 选中的浮动窗口置顶
+        // 3) 选中的浮动窗口在所在层内置顶
         if let Some(sel_ck) = monitor.sel {
             if let Some(sel_c) = self.clients.get(sel_ck) {
                 if sel_c.state.is_floating {
-                    if let Some(idx) = floating_bottom_to_top.iter().position(|&w| w == sel_c.win) {
-                        let w = floating_bottom_to_top.remove(idx);
-                        floating_bottom_to_top.push(w);
+                    for layer in [
+                        &mut below_bottom_to_top,
+                        &mut floating_bottom_to_top,
+                        &mut above_bottom_to_top,
+                    ] {
+                        if let Some(idx) = layer.iter().position(|&w| w == sel_c.win) {
+                            let w = layer.remove(idx);
+                            layer.push(w);
+                        }
                     }
                 }
             }
         }
 
         // 4) 最终顺序（底->顶）
-        let mut final_bottom_to_top: Vec<u32> =
-            Vec::with_capacity(tiled_bottom_to_top.len() + floating_bottom_to_top.len());
-        final_bottom_to_top.extend(tiled_bottom_to_top.into_iter());
-        final_bottom_to_top.extend(floating_bottom_to_top.into_iter());
+        let mut final_bottom_to_top: Vec<u32> = Vec::with_capacity(
+            below_bottom_to_top.len()
+                + tiled_bottom_to_top.len()
+                + floating_bottom_to_top.len()
+                + above_bottom_to_top.len(),
+        );
+        final_bottom_to_top.extend(below_bottom_to_top);
+        final_bottom_to_top.extend(tiled_bottom_to_top);
+        final_bottom_to_top.extend(floating_bottom_to_top);
+        final_bottom_to_top.extend(above_bottom_to_top);
 
         // 5) 如果顺序未变化，跳过
         let need_restack_windows = match self.last_stacking.get(mon_key) {
@@ -3006,21 +4227,35 @@ impl Jwm {
         }
 
         // 6) bar 置顶（若显示）
-        if self.current_bar_monitor_id == Some(monitor_num) {
-            if let Some(bar_key) = self.status_bar_client {
-                if let Some(bar_client) = self.clients.get(bar_key) {
-                    let show_bar = monitor
-                        .pertag
-                        .as_ref()
-                        .and_then(|p| p.show_bars.get(p.cur_tag))
-                        .copied()
-                        .unwrap_or(true);
-                    if show_bar {
-                        self.backend
-                            .window_ops()
-                            .configure_stack_above(WindowId(bar_client.win.into()), None)?;
-                    }
+        if let Some(bar_key) = self.status_bars.get(mon_key).and_then(|bar| bar.client) {
+            if let Some(bar_client) = self.clients.get(bar_key) {
+                let show_bar = monitor
+                    .pertag
+                    .as_ref()
+                    .and_then(|p| p.show_bars.get(p.cur_tag))
+                    .copied()
+                    .unwrap_or(true);
+                if show_bar {
+                    self.backend
+                        .window_ops()
+                        .configure_stack_above(WindowId(bar_client.win.into()), None)?;
+                }
+            }
+        }
+
+        // 7) dock 保持在客户端之上，但不遮挡所在显示器上可见的全屏窗口
+        if need_restack_windows {
+            let docks: Vec<u32> = self.docks.keys().copied().collect();
+            for dock in docks {
+                let wid = WindowId(dock.into());
+                let dock_mon = match self.backend.window_ops().get_geometry_translated(wid) {
+                    Ok(g) => self.recttomon(g.x.into(), g.y.into(), g.w.into(), g.h.into()),
+                    Err(_) => None,
+                };
+                if dock_mon.is_some_and(|m| self.has_visible_fullscreen(m)) {
+                    continue;
                 }
+                self.backend.window_ops().configure_stack_above(wid, None)?;
             }
         }
 
@@ -3036,71 +4271,95 @@ impl Jwm {
             return;
         }
 
-        // 选择目标 monitor
-        let target_mon_id = self
-            .current_bar_monitor_id
-            .or_else(|| {
-                self.sel_mon
-                    .and_then(|k| self.monitors.get(k))
-                    .map(|m| m.num)
-            })
-            .or_else(|| self.pending_bar_updates.iter().copied().next());
+        // 选择目标 monitor：每屏模式下是所有待刷新的显示器，单实例模式下是状态栏所在的显示器
+        let targets: Vec<MonitorKey> = if CONFIG.load().status_bar_per_monitor() {
+            self.pending_bar_updates
+                .iter()
+                .filter_map(|&id| self.get_monitor_by_id(id))
+                .collect()
+        } else {
+            self.status_bars
+                .keys()
+                .find(|&k| self.monitors.contains_key(k))
+                .or(self.sel_mon)
+                .or_else(|| {
+                    self.pending_bar_updates
+                        .iter()
+                        .find_map(|&id| self.get_monitor_by_id(id))
+                })
+                .into_iter()
+                .collect()
+        };
 
-        if let Some(mon_id) = target_mon_id {
-            if let Some(mon_key) = self.get_monitor_by_id(mon_id) {
-                if !self.is_bar_visible_on_mon(mon_key) {
-                    self.pending_bar_updates.clear();
-                    return;
+        // 未到发送间隔的保留 pending，下个 tick 再发
+        let mut deferred = HashSet::new();
+        for mon_key in targets {
+            if !self.flush_bar_update(mon_key) {
+                if let Some(m) = self.monitors.get(mon_key) {
+                    deferred.insert(m.num);
                 }
+            }
+        }
+        self.pending_bar_updates = deferred;
+    }
 
-                // 1) 构造消息（更新 self.message）
-                self.update_bar_message_for_monitor(Some(mon_key));
+    // 向显示器的状态栏发送一次更新；返回 false 表示被去抖推迟
+    fn flush_bar_update(&mut self, mon_key: MonitorKey) -> bool {
+        if !self.is_bar_visible_on_mon(mon_key) {
+            return true;
+        }
 
-                // 2) 序列化用于差异比较
-                let payload = match bincode::encode_to_vec(&self.message, standard()) {
-                    Ok(v) => v,
-                    Err(_) => {
-                        self.pending_bar_updates.clear();
-                        return;
-                    }
-                };
+        // 1) 构造消息（更新 self.message）
+        self.update_bar_message_for_monitor(Some(mon_key));
 
-                // 3) 去抖：时间间隔
-                let now = std::time::Instant::now();
-                if let Some(last) = self.last_bar_update_at {
-                    if now.duration_since(last) < self.bar_min_interval {
-                        // 未到发送间隔，先保留 pending，下个 tick 再发
-                        return;
-                    }
-                }
+        // 2) 序列化用于差异比较
+        let payload = match bincode::encode_to_vec(&self.message, standard()) {
+            Ok(v) => v,
+            Err(_) => return true,
+        };
 
-                // 4) 差异比较：相同则跳过
-                if self.last_bar_payload.as_ref().map(|p| &**p) == Some(&payload[..]) {
-                    self.pending_bar_updates.clear();
-                    return;
-                }
+        self.ensure_status_bar_entry(mon_key);
+        let min_interval = self.bar_min_interval;
+        let now = std::time::Instant::now();
+        {
+            let bar = match self.status_bars.get_mut(mon_key) {
+                Some(bar) => bar,
+                None => return true,
+            };
 
-                // 5) 确保 ring buffer 与进程
-                if self.status_bar_shmem.is_none() {
-                    let ring_buffer = SharedRingBuffer::create_aux(SHARED_PATH, None, None)
-                        .expect("Create bar shmem failed");
-                    info!("Create bar shmem");
-                    self.status_bar_shmem = Some(ring_buffer);
+            // 3) 去抖：时间间隔
+            if let Some(last) = bar.last_update_at {
+                if now.duration_since(last) < min_interval {
+                    return false;
                 }
-                self.ensure_bar_is_running(SHARED_PATH);
+            }
 
-                // 6) 写消息
-                if let Some(rb) = self.status_bar_shmem.as_mut() {
-                    let _ = rb.try_write_message(&self.message);
-                }
+            // 4) 差异比较：相同则跳过
+            if bar.last_payload.as_deref() == Some(&payload[..]) {
+                return true;
+            }
 
-                // 7) 记录发送状态
-                self.last_bar_payload = Some(payload);
-                self.last_bar_update_at = Some(now);
+            // 5) 确保 ring buffer
+            if bar.shmem.is_none() {
+                let ring_buffer = SharedRingBuffer::create_aux(&bar.shared_path, None, None)
+                    .expect("Create bar shmem failed");
+                info!("Create bar shmem {}", bar.shared_path);
+                bar.shmem = Some(ring_buffer);
             }
         }
+        self.ensure_bar_is_running(mon_key);
+
+        if let Some(bar) = self.status_bars.get_mut(mon_key) {
+            // 6) 写消息
+            if let Some(rb) = bar.shmem.as_mut() {
+                let _ = rb.try_write_message(&self.message);
+            }
 
-        self.pending_bar_updates.clear();
+            // 7) 记录发送状态
+            bar.last_payload = Some(payload);
+            bar.last_update_at = Some(now);
+        }
+        true
     }
 
     pub fn run(&mut self) -> Result<(), Box<dyn std::error::Error>> {
@@ -3130,8 +4389,14 @@ impl Jwm {
                 let _ = self.handle_backend_event(ev);
             }
 
+            self.check_config_changes();
+            self.process_output_changes();
+            self.process_ipc_requests();
+
             // 处理状态栏命令与待更新
             self.process_commands_from_status_bar();
+            self.emit_view_events();
+            self.sync_ewmh_current_desktop();
             if !self.pending_bar_updates.is_empty() {
                 self.flush_pending_bar_updates();
             }
@@ -3161,8 +4426,14 @@ impl Jwm {
                 let _ = self.handle_backend_event(ev);
             }
 
+            self.check_config_changes();
+            self.process_output_changes();
+            self.process_ipc_requests();
+
             // 处理状态栏命令与待更新
             self.process_commands_from_status_bar();
+            self.emit_view_events();
+            self.sync_ewmh_current_desktop();
             if !self.pending_bar_updates.is_empty() {
                 self.flush_pending_bar_updates();
             }
@@ -3175,15 +4446,21 @@ impl Jwm {
 
     fn process_commands_from_status_bar(&mut self) {
         // 创建一个临时向量来收集所有命令
-        let mut commands_to_process: Vec<SharedCommand> = Vec::new();
-        // 第一步：遍历共享内存缓冲区并收集命令
-        if let Some(buffer) = self.status_bar_shmem.as_mut() {
-            while let Some(cmd) = buffer.receive_command() {
-                commands_to_process.push(cmd);
+        let mut commands_to_process: Vec<(MonitorKey, SharedCommand)> = Vec::new();
+        // 第一步：遍历各状态栏的共享内存缓冲区并收集命令
+        for (mon_key, bar) in &mut self.status_bars {
+            if let Some(buffer) = bar.shmem.as_mut() {
+                while let Some(cmd) = buffer.receive_command() {
+                    commands_to_process.push((mon_key, cmd));
+                }
             }
         }
-        // 第二步：处理收集到的命令
-        for cmd in commands_to_process {
+        // 第二步：处理收集到的命令，作用于发出命令的状态栏所在显示器
+        for (mon_key, cmd) in commands_to_process {
+            if Some(mon_key) != self.sel_mon && self.monitors.contains_key(mon_key) {
+                let _ = self.switch_to_monitor(mon_key);
+                let _ = self.focus(None);
+            }
             match cmd.cmd_type.into() {
                 CommandType::ViewTag => {
                     // 切换到指定标签
@@ -3209,8 +4486,12 @@ impl Jwm {
                         "[process_commands] SetLayout command received: {}",
                         cmd.parameter
                     );
-                    let arg = WMArgEnum::Layout(Rc::new(LayoutEnum::from(cmd.parameter)));
-                    let _ = self.setlayout(&arg);
+                    match LAYOUTS.by_index(cmd.parameter as usize) {
+                        Some(lt) => {
+                            let _ = self.setlayout(&WMArgEnum::Layout(lt));
+                        }
+                        None => warn!("[process_commands] Unknown layout index: {}", cmd.parameter),
+                    }
                 }
                 CommandType::None => {}
             }
@@ -3238,7 +4519,7 @@ impl Jwm {
                     .backend
                     .property_ops()
                     .get_wm_state(*win)
-                    .map_or(false, |s| s == ICONIC_STATE.into())
+                    .map_or(false, |s| s == i64::from(ICONIC_STATE))
             {
                 self.manage(win.0 as u32, geom)?;
             }
@@ -3250,7 +4531,7 @@ impl Jwm {
                         .backend
                         .property_ops()
                         .get_wm_state(*win)
-                        .map_or(false, |s| s == ICONIC_STATE.into())
+                        .map_or(false, |s| s == i64::from(ICONIC_STATE))
                 {
                     self.manage(win.0 as u32, geom)?;
                 }
@@ -3362,7 +4643,7 @@ impl Jwm {
         let mut mut_arg: WMArgEnum = arg.clone();
         if let WMArgEnum::StringVec(ref mut v) = mut_arg {
             // 处理 dmenu 命令的特殊情况
-            if *v == *CONFIG.get_dmenucmd() {
+            if *v == *CONFIG.load().get_dmenucmd() {
                 let monitor_num = self.get_sel_mon().unwrap().num;
                 let tmp = (b'0' + monitor_num as u8) as char;
                 let tmp = tmp.to_string();
@@ -3416,75 +4697,18 @@ impl Jwm {
         Ok(())
     }
 
-    fn tile(&mut self, mon_key: MonitorKey) {
-        info!("[tile]");
-
-        // 获取监视器基本信息
-        let (wx, wy, ww, wh, mfact, nmaster, monitor_num, client_y_offset) =
-            self.get_monitor_info(mon_key);
-
-        // 收集所有可平铺的客户端
-        let clients = self.collect_tileable_clients(mon_key);
-
-        if clients.is_empty() {
-            return;
-        }
-
-        info!(
-            "[tile] monitor_num: {}, clients: {}",
-            monitor_num,
-            clients.len()
-        );
-
-        // 计算布局参数
-        let (mw, mfacts, sfacts) = self.calculate_layout_params(&clients, ww, mfact, nmaster);
-
-        // 安排客户端位置
-        self.arrange_clients(
-            &clients,
-            wx,
-            wy,
-            ww,
-            wh,
-            mw,
-            mfacts,
-            sfacts,
-            nmaster,
-            client_y_offset,
-        );
-    }
-
-    // 获取监视器基本信息
-    fn get_monitor_info(&self, mon_key: MonitorKey) -> (i32, i32, i32, i32, f32, u32, i32, i32) {
-        if let Some(monitor) = self.monitors.get(mon_key) {
-            let client_y_offset = self.get_client_y_offset(monitor);
-            (
-                monitor.geometry.w_x,
-                monitor.geometry.w_y,
-                monitor.geometry.w_w,
-                monitor.geometry.w_h,
-                monitor.layout.m_fact,
-                monitor.layout.n_master,
-                monitor.num,
-                client_y_offset,
-            )
-        } else {
-            warn!("[get_monitor_info] Monitor {:?} not found", mon_key);
-            (0, 0, 0, 0, 0.55, 1, 0, 0) // 默认值
-        }
-    }
-
     // 收集所有可平铺的客户端
-    fn collect_tileable_clients(&self, mon_key: MonitorKey) -> Vec<(ClientKey, f32, i32)> {
+    fn collect_tileable_clients(&self, mon_key: MonitorKey) -> Vec<TiledClient> {
         let mut clients = Vec::new();
         let mut current_client = self.nexttiled(mon_key, None);
 
         while let Some(client_key) = current_client {
             if let Some(client) = self.clients.get(client_key) {
-                let client_fact = client.state.client_fact;
-                let border_w = client.geometry.border_w;
-
-                clients.push((client_key, client_fact, border_w));
+                clients.push(TiledClient {
+                    key: client_key,
+                    client_fact: client.state.client_fact,
+                    border_w: client.geometry.border_w,
+                });
 
                 // 找下一个平铺客户端
                 current_client = self.nexttiled(mon_key, Some(client_key));
@@ -3496,190 +4720,172 @@ impl Jwm {
         clients
     }
 
-    // 计算布局参数
-    fn calculate_layout_params(
-        &self,
-        clients: &[(ClientKey, f32, i32)],
-        ww: i32,
-        mfact: f32,
-        nmaster: u32,
-    ) -> (i32, f32, f32) {
-        let n = clients.len() as u32;
-
-        // 计算主区域和堆栈区域的cfact总和
-        let (mfacts, sfacts) = clients.iter().enumerate().fold(
-            (0.0, 0.0),
-            |(mfacts, sfacts), (i, (_, client_fact, _))| {
-                if i < nmaster as usize {
-                    (mfacts + client_fact, sfacts)
-                } else {
-                    (mfacts, sfacts + client_fact)
-                }
-            },
-        );
+    fn get_client_y_offset(&self, monitor: &WMMonitor) -> i32 {
+        // 只按该 monitor 当前 tag 的 show_bars 决定是否保留顶部 gap
+        let show_bar = monitor
+            .pertag
+            .as_ref()
+            .and_then(|p| p.show_bars.get(p.cur_tag))
+            .copied()
+            .unwrap_or(true);
 
-        // 计算主区域宽度
-        let mw = if n > nmaster && nmaster > 0 {
-            (ww as f32 * mfact) as i32
+        if show_bar {
+            CONFIG.load().status_bar_height() + CONFIG.load().status_bar_padding() * 2
         } else {
-            ww
+            0
+        }
+    }
+
+    pub fn togglescratch(&mut self, arg: &WMArgEnum) -> Result<(), Box<dyn std::error::Error>> {
+        let name = match arg {
+            WMArgEnum::StringVec(v) if !v.is_empty() => v[0].clone(),
+            _ => return Ok(()),
+        };
+        let scratchpad = match CONFIG.load().scratchpad(&name) {
+            Some(sp) => sp.clone(),
+            None => {
+                warn!("[togglescratch] Unknown scratchpad: {}", name);
+                return Ok(());
+            }
         };
+        let sel_mon_key = self.sel_mon.ok_or("No monitor selected")?;
 
-        (mw, mfacts, sfacts)
-    }
+        let client_key = match self.scratchpads.get(&name).copied() {
+            Some(ck) if self.clients.contains_key(ck) => ck,
+            _ => {
+                self.expire_pending_scratchpads();
+                if self.pending_scratchpads.contains_key(&name) {
+                    info!("[togglescratch] Scratchpad '{}' is still starting", name);
+                    return Ok(());
+                }
+                // 尚未运行：启动并等待 manage 认领
+                info!("[togglescratch] Spawning scratchpad '{}'", name);
+                self.pending_scratchpads.insert(name, Instant::now());
+                return self.spawn(&WMArgEnum::StringVec(scratchpad.command));
+            }
+        };
 
-    // 安排客户端位置
-    fn arrange_clients(
-        &mut self,
-        clients: &[(ClientKey, f32, i32)],
-        wx: i32,
-        wy: i32,
-        ww: i32,
-        wh: i32,
-        mw: i32,
-        mfacts: f32,
-        sfacts: f32,
-        nmaster: u32,
-        client_y_offset: i32,
-    ) {
-        let available_height = wh - client_y_offset;
-        let mut my = 0i32; // 主区域Y偏移
-        let mut ty = 0i32; // 堆栈区域Y偏移
-        let mut remaining_mfacts = mfacts;
-        let mut remaining_sfacts = sfacts;
-
-        for (i, &(client_key, client_fact, border_w)) in clients.iter().enumerate() {
-            let is_master = i < nmaster as usize;
-
-            let (x, y, w, h) = if is_master {
-                self.calculate_master_geometry(
-                    wx,
-                    wy,
-                    mw,
-                    available_height,
-                    client_y_offset,
-                    client_fact,
-                    border_w,
-                    i,
-                    nmaster,
-                    &mut my,
-                    &mut remaining_mfacts,
-                )
-            } else {
-                self.calculate_stack_geometry(
-                    wx,
-                    wy,
-                    ww,
-                    mw,
-                    available_height,
-                    client_y_offset,
-                    client_fact,
-                    border_w,
-                    i,
-                    nmaster,
-                    clients.len(),
-                    &mut ty,
-                    &mut remaining_sfacts,
-                )
-            };
+        let on_sel_mon = self.clients.get(client_key).and_then(|c| c.mon) == Some(sel_mon_key);
+        if on_sel_mon && self.is_client_visible_on_monitor(client_key, sel_mon_key) {
+            // 当前可见：隐藏
+            if let Some(client) = self.clients.get_mut(client_key) {
+                client.state.tags = 0;
+            }
+            self.focus(None)?;
+            self.arrange(Some(sel_mon_key));
+            return Ok(());
+        }
 
-            // 调整客户端大小
+        // 移到当前显示器的当前 tag 上显示
+        if !on_sel_mon {
+            self.sendmon(Some(client_key), Some(sel_mon_key));
+        }
+        let tags = self
+            .monitors
+            .get(sel_mon_key)
+            .map(|m| m.tag_set[m.sel_tags])
+            .unwrap_or(1);
+        if let Some(client) = self.clients.get_mut(client_key) {
+            client.state.tags = tags;
+            client.state.is_floating = true;
+        }
+        if let Some((x, y, w, h)) = self.resolve_geometry(sel_mon_key, &scratchpad.geometry) {
             self.resize_client(client_key, x, y, w, h, false);
         }
+        self.setclienttagprop(client_key)?;
+        self.focus(Some(client_key))?;
+        self.arrange(Some(sel_mon_key));
+        self.restack(Some(sel_mon_key))?;
+        Ok(())
     }
 
-    // 计算主区域窗口几何形状（保持不变）
-    fn calculate_master_geometry(
-        &self,
-        wx: i32,
-        wy: i32,
-        mw: i32,
-        available_height: i32,
-        client_y_offset: i32,
-        client_fact: f32,
-        border_w: i32,
-        index: usize,
-        nmaster: u32,
-        my: &mut i32,
-        remaining_mfacts: &mut f32,
-    ) -> (i32, i32, i32, i32) {
-        let remaining_masters = nmaster - index as u32;
-        let remaining_height = (available_height - *my).max(0);
-
-        let height = if *remaining_mfacts > 0.001 {
-            (remaining_height as f32 * (client_fact / *remaining_mfacts)) as i32
-        } else if remaining_masters > 0 {
-            remaining_height / remaining_masters as i32
-        } else {
-            remaining_height
-        };
-
-        *my += height;
-        *remaining_mfacts -= client_fact;
-
-        (
-            wx,
-            wy + *my - height + client_y_offset,
-            mw - 2 * border_w,
-            height - 2 * border_w,
-        )
+    // 丢弃超时仍未出现窗口的 scratchpad，允许再次启动
+    fn expire_pending_scratchpads(&mut self) {
+        const PENDING_SCRATCHPAD_TIMEOUT: Duration = Duration::from_secs(10);
+        self.pending_scratchpads.retain(|name, started| {
+            let alive = started.elapsed() < PENDING_SCRATCHPAD_TIMEOUT;
+            if !alive {
+                warn!(
+                    "[expire_pending_scratchpads] No window claimed scratchpad '{}' in time",
+                    name
+                );
+            }
+            alive
+        });
     }
 
-    // 计算堆栈区域窗口几何形状（保持不变）
-    fn calculate_stack_geometry(
-        &self,
-        wx: i32,
-        wy: i32,
-        ww: i32,
-        mw: i32,
-        available_height: i32,
-        client_y_offset: i32,
-        client_fact: f32,
-        border_w: i32,
-        index: usize,
-        nmaster: u32,
-        total_clients: usize,
-        ty: &mut i32,
-        remaining_sfacts: &mut f32,
-    ) -> (i32, i32, i32, i32) {
-        let stack_index = index - nmaster as usize;
-        let stack_count = total_clients - nmaster as usize;
-        let remaining_stacks = stack_count - stack_index;
-        let remaining_height = (available_height - *ty).max(0);
-
-        let height = if *remaining_sfacts > 0.001 {
-            (remaining_height as f32 * (client_fact / *remaining_sfacts)) as i32
-        } else if remaining_stacks > 0 {
-            remaining_height / remaining_stacks as i32
-        } else {
-            remaining_height
+    // 若有等待中的 scratchpad 与新窗口匹配，则将其登记为该 scratchpad
+    fn claim_scratchpad(&mut self, client_key: ClientKey) {
+        self.expire_pending_scratchpads();
+        if self.pending_scratchpads.is_empty() {
+            return;
+        }
+        let (class, instance, mon_key) = match self.clients.get(client_key) {
+            Some(c) => (c.class.clone(), c.instance.clone(), c.mon),
+            None => return,
+        };
+        let scratchpad = match CONFIG.load().scratchpads().iter().find(|sp| {
+            self.pending_scratchpads.contains_key(&sp.name)
+                && !(sp.class.is_empty() && sp.instance.is_empty())
+                && (sp.class.is_empty() || class.contains(&sp.class))
+                && (sp.instance.is_empty() || instance.contains(&sp.instance))
+        }) {
+            Some(sp) => sp.clone(),
+            None => return,
         };
 
-        *ty += height;
-        *remaining_sfacts -= client_fact;
-
-        (
-            wx + mw,
-            wy + *ty - height + client_y_offset,
-            ww - mw - 2 * border_w,
-            height - 2 * border_w,
-        )
+        info!(
+            "[claim_scratchpad] Window of class '{}' claimed as scratchpad '{}'",
+            class, scratchpad.name
+        );
+        self.pending_scratchpads.remove(&scratchpad.name);
+        self.scratchpads.insert(scratchpad.name.clone(), client_key);
+        let geometry =
+            mon_key.and_then(|mon_key| self.resolve_geometry(mon_key, &scratchpad.geometry));
+        if let Some(client) = self.clients.get_mut(client_key) {
+            client.state.is_floating = true;
+            // 窗口尚未配置，直接写入几何，后续 manage 流程会应用
+            if let Some((x, y, w, h)) = geometry {
+                client.geometry.x = x;
+                client.geometry.y = y;
+                client.geometry.w = w;
+                client.geometry.h = h;
+            }
+        }
     }
 
-    fn get_client_y_offset(&self, monitor: &WMMonitor) -> i32 {
-        // 只按该 monitor 当前 tag 的 show_bars 决定是否保留顶部 gap
-        let show_bar = monitor
-            .pertag
-            .as_ref()
-            .and_then(|p| p.show_bars.get(p.cur_tag))
-            .copied()
-            .unwrap_or(true);
+    // 按配置计算窗口在显示器工作区内的几何，返回 (x, y, w, h)，宽高已扣除边框
+    fn resolve_geometry(
+        &self,
+        mon_key: MonitorKey,
+        geometry: &GeometryConfig,
+    ) -> Option<(i32, i32, i32, i32)> {
+        let monitor = self.monitors.get(mon_key)?;
+        let (mx, my, mw, mh) = (
+            monitor.geometry.w_x,
+            monitor.geometry.w_y,
+            monitor.geometry.w_w,
+            monitor.geometry.w_h,
+        );
+        let w = geometry.width.resolve(mw).clamp(1, mw);
+        let h = geometry.height.resolve(mh).clamp(1, mh);
+        let x = mx + geometry.x.as_ref().map_or((mw - w) / 2, |x| x.resolve(mw));
+        let y = my + geometry.y.as_ref().map_or((mh - h) / 2, |y| y.resolve(mh));
+        let border_w = CONFIG.load().border_px() as i32;
+        Some((x, y, w - 2 * border_w, h - 2 * border_w))
+    }
 
-        if show_bar {
-            CONFIG.status_bar_height() + CONFIG.status_bar_padding() * 2
-        } else {
-            0
-        }
+    pub fn togglesticky(&mut self, _arg: &WMArgEnum) -> Result<(), Box<dyn std::error::Error>> {
+        let sel_client_key = match self.get_selected_client_key() {
+            Some(key) => key,
+            None => return Ok(()),
+        };
+        let is_sticky = self
+            .clients
+            .get(sel_client_key)
+            .map(|c| c.state.is_sticky)
+            .unwrap_or(false);
+        self.setsticky(sel_client_key, !is_sticky)
     }
 
     pub fn togglefloating(&mut self, _arg: &WMArgEnum) -> Result<(), Box<dyn std::error::Error>> {
@@ -3772,7 +4978,7 @@ impl Jwm {
                 if Some(target_mon_key) == self.sel_mon {
                     return Ok(());
                 }
-                // 统一走切屏逻辑：会更新 current_bar_monitor_id 并移动状态栏
+                // 统一走切屏逻辑：单实例模式下会移动状态栏
                 self.switch_to_monitor(target_mon_key)?;
                 // 切屏后在目标屏上重新评估焦点
                 self.focus(None)?;
@@ -3786,11 +4992,29 @@ impl Jwm {
         return Ok(());
     }
 
+    // 调整主音量：正数增大、负数减小（百分比），0 切换静音
+    pub fn volume(&mut self, arg: &WMArgEnum) -> Result<(), Box<dyn std::error::Error>> {
+        if let WMArgEnum::Int(step) = *arg {
+            let amount = match step {
+                0 => "toggle".to_string(),
+                s if s > 0 => format!("{}%+", s),
+                s => format!("{}%-", s.unsigned_abs()),
+            };
+            info!("[volume] amixer sset Master {}", amount);
+            let command = ["amixer", "-q", "sset", "Master", &amount]
+                .iter()
+                .map(|s| s.to_string())
+                .collect();
+            return self.spawn(&WMArgEnum::StringVec(command));
+        }
+        Ok(())
+    }
+
     pub fn tag(&mut self, arg: &WMArgEnum) -> Result<(), Box<dyn std::error::Error>> {
         // info!("[tag]");
         if let WMArgEnum::UInt(ui) = *arg {
             let sel_client_key = self.get_selected_client_key();
-            let target_tag = ui & CONFIG.tagmask();
+            let target_tag = ui & CONFIG.load().tagmask();
 
             if let Some(client_key) = sel_client_key {
                 if target_tag > 0 {
@@ -3847,46 +5071,244 @@ impl Jwm {
             None => return,
         };
 
-        // 检查客户端当前是否已在目标监视器上
-        if let Some(client) = self.clients.get(client_key) {
-            if client.mon == Some(target_mon_key) {
-                return; // 客户端已在目标监视器上，无需移动
-            }
+        // 检查客户端当前是否已在目标监视器上
+        if let Some(client) = self.clients.get(client_key) {
+            if client.mon == Some(target_mon_key) {
+                return; // 客户端已在目标监视器上，无需移动
+            }
+        } else {
+            return;
+        }
+
+        // 取消客户端焦点
+        let _ = self.unfocus(client_key, true);
+
+        // 从当前监视器分离客户端
+        self.detach(client_key);
+        self.detachstack(client_key);
+
+        // 更新客户端的监视器归属
+        if let Some(client) = self.clients.get_mut(client_key) {
+            client.mon = Some(target_mon_key);
+        }
+
+        // 获取目标监视器的标签集并分配给客户端
+        if let Some(target_monitor) = self.monitors.get(target_mon_key) {
+            let target_tags = target_monitor.tag_set[target_monitor.sel_tags];
+
+            if let Some(client) = self.clients.get_mut(client_key) {
+                client.state.tags = target_tags;
+            }
+        }
+
+        // 将客户端附加到目标监视器
+        self.attach(client_key);
+        self.attachstack(client_key);
+
+        // 设置客户端标签属性
+        let _ = self.setclienttagprop(client_key);
+
+        // 重新聚焦和排列
+        let _ = self.focus(None);
+        self.arrange(None);
+    }
+
+    // 与相邻显示器交换当前视图（标签集及其窗口）
+    pub fn swapmon(&mut self, arg: &WMArgEnum) -> Result<(), Box<dyn std::error::Error>> {
+        if self.monitor_order.len() <= 1 {
+            return Ok(());
+        }
+        if let WMArgEnum::Int(i) = *arg {
+            let sel_mon_key = self.sel_mon.ok_or("No monitor selected")?;
+            if let Some(target_mon_key) = self.dirtomon(&i) {
+                let target_tags = self
+                    .monitors
+                    .get(target_mon_key)
+                    .map(|m| m.tag_set[m.sel_tags])
+                    .ok_or("Monitor not found")?;
+                self.exchange_views(sel_mon_key, target_mon_key, target_tags)?;
+            }
+        }
+        Ok(())
+    }
+
+    // 查看标签；若它正显示在其他显示器上，则把它连同窗口拉过来，并把当前视图换过去
+    pub fn greedyview(&mut self, arg: &WMArgEnum) -> Result<(), Box<dyn std::error::Error>> {
+        let ui = match arg {
+            WMArgEnum::UInt(val) => *val,
+            _ => return Ok(()),
+        };
+        let target_tag = ui & CONFIG.load().tagmask();
+        let sel_mon_key = match self.sel_mon {
+            Some(k) => k,
+            None => return Ok(()),
+        };
+        if target_tag == 0 || self.is_same_tag(target_tag) {
+            return self.view(arg);
+        }
+
+        let other_mon_key = self.monitor_order.iter().copied().find(|&k| {
+            k != sel_mon_key
+                && self
+                    .monitors
+                    .get(k)
+                    .map_or(false, |m| m.tag_set[m.sel_tags] & target_tag == target_tag)
+        });
+        match other_mon_key {
+            Some(mon_key) => {
+                info!(
+                    "[greedyview] Pulling tag {:#x} from {:?}",
+                    target_tag, mon_key
+                );
+                self.exchange_views(sel_mon_key, mon_key, target_tag)
+            }
+            None => self.view(arg),
+        }
+    }
+
+    // 把当前视图中的所有窗口发送到相邻显示器
+    pub fn tagallmon(&mut self, arg: &WMArgEnum) -> Result<(), Box<dyn std::error::Error>> {
+        if self.monitor_order.len() <= 1 {
+            return Ok(());
+        }
+        if let WMArgEnum::Int(i) = *arg {
+            let sel_mon_key = self.sel_mon.ok_or("No monitor selected")?;
+            let target_mon_key = match self.dirtomon(&i) {
+                Some(k) if k != sel_mon_key => k,
+                _ => return Ok(()),
+            };
+            let (tags, target_tags) = match (
+                self.monitors.get(sel_mon_key),
+                self.monitors.get(target_mon_key),
+            ) {
+                (Some(m), Some(t)) => (m.tag_set[m.sel_tags], t.tag_set[t.sel_tags]),
+                _ => return Ok(()),
+            };
+            let client_keys = self.view_clients(sel_mon_key, tags);
+            if client_keys.is_empty() {
+                return Ok(());
+            }
+            info!(
+                "[tagallmon] Sending {} client(s) to {:?}",
+                client_keys.len(),
+                target_mon_key
+            );
+
+            let current_sel = self.get_selected_client_key();
+            self.unfocus_client_opt(current_sel, true)?;
+            self.move_clients_between_monitors(
+                &client_keys,
+                sel_mon_key,
+                target_mon_key,
+                Some(target_tags),
+            );
+            self.update_fullscreen_clients_on_monitor(target_mon_key)?;
+            self.focus(None)?;
+            self.arrange(None);
+            self.mark_bar_update_needed_if_visible(None);
+        }
+        Ok(())
+    }
+
+    // 显示器上属于该视图的窗口（不含 sticky 和隐藏的 scratchpad），按原顺序
+    fn view_clients(&self, mon_key: MonitorKey, tags: u32) -> Vec<ClientKey> {
+        self.monitor_clients
+            .get(mon_key)
+            .map(|keys| {
+                keys.iter()
+                    .copied()
+                    .filter(|&ck| {
+                        self.clients
+                            .get(ck)
+                            .map_or(false, |c| c.state.tags & tags != 0 && !c.state.is_sticky)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // 视图对应的 pertag 下标：全部标签为 0，否则为最低位标签（从 1 开始）
+    fn view_tag_index(tags: u32) -> usize {
+        if tags == CONFIG.load().tagmask() {
+            0
         } else {
-            return;
+            tags.trailing_zeros() as usize + 1
         }
+    }
 
-        // 取消客户端焦点
-        let _ = self.unfocus(client_key, true);
-
-        // 从当前监视器分离客户端
-        self.detach(client_key);
-        self.detachstack(client_key);
-
-        // 更新客户端的监视器归属
-        if let Some(client) = self.clients.get_mut(client_key) {
-            client.mon = Some(target_mon_key);
+    /// 交换两个显示器的视图：a 改看 b 上的 tags_b，b 改看 a 的当前视图；窗口与标签的 pertag 设置随视图移动
+    fn exchange_views(
+        &mut self,
+        a: MonitorKey,
+        b: MonitorKey,
+        tags_b: u32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if a == b {
+            return Ok(());
         }
+        let (tags_a, pertag_a) = {
+            let m = self.monitors.get(a).ok_or("Monitor not found")?;
+            (m.tag_set[m.sel_tags], m.pertag.clone())
+        };
+        let pertag_b = self
+            .monitors
+            .get(b)
+            .ok_or("Monitor not found")?
+            .pertag
+            .clone();
+        let from_a = self.view_clients(a, tags_a);
+        let from_b = self.view_clients(b, tags_b);
+        info!(
+            "[exchange_views] {:?} tags {:#x} ({} clients) <-> {:?} tags {:#x} ({} clients)",
+            a,
+            tags_a,
+            from_a.len(),
+            b,
+            tags_b,
+            from_b.len()
+        );
 
-        // 获取目标监视器的标签集并分配给客户端
-        if let Some(target_monitor) = self.monitors.get(target_mon_key) {
-            let target_tags = target_monitor.tag_set[target_monitor.sel_tags];
-
-            if let Some(client) = self.clients.get_mut(client_key) {
-                client.state.tags = target_tags;
+        let current_sel = self.get_selected_client_key();
+        self.unfocus_client_opt(current_sel, true)?;
+        self.move_clients_between_monitors(&from_a, a, b, None);
+        self.move_clients_between_monitors(&from_b, b, a, None);
+
+        // 标签的布局参数与选中窗口跟随视图
+        for (mon_key, source, tags) in [(a, &pertag_b, tags_b), (b, &pertag_a, tags_a)] {
+            if let (Some(source), Some(pertag)) = (
+                source.as_ref(),
+                self.monitors
+                    .get_mut(mon_key)
+                    .and_then(|m| m.pertag.as_mut()),
+            ) {
+                pertag.copy_tag_from(source, Self::view_tag_index(tags));
             }
         }
 
-        // 将客户端附加到目标监视器
-        self.attach(client_key);
-        self.attachstack(client_key);
-
-        // 设置客户端标签属性
-        let _ = self.setclienttagprop(client_key);
+        let mut sel_opt = None;
+        for (mon_key, tags) in [(b, tags_a), (a, tags_b)] {
+            let ui = if Self::view_tag_index(tags) == 0 {
+                !0
+            } else {
+                tags
+            };
+            let cur_tag = self.switch_to_tag(mon_key, tags, ui)?;
+            let sel = self.apply_pertag_settings(mon_key, cur_tag)?;
+            if Some(mon_key) == self.sel_mon {
+                sel_opt = sel;
+            }
+            self.update_fullscreen_clients_on_monitor(mon_key)?;
+        }
 
-        // 重新聚焦和排列
-        let _ = self.focus(None);
+        self.focus(sel_opt)?;
         self.arrange(None);
+        for mon_key in [a, b] {
+            if self.status_bars.contains_key(mon_key) {
+                self.position_statusbar_on_monitor(mon_key)?;
+            }
+        }
+        self.mark_bar_update_needed_if_visible(None);
+        Ok(())
     }
 
     pub fn focusstack(&mut self, arg: &WMArgEnum) -> Result<(), Box<dyn std::error::Error>> {
@@ -3926,7 +5348,7 @@ impl Jwm {
 
         if let Some(client) = self.clients.get(sel_client_key) {
             let is_locked_fullscreen =
-                client.state.is_fullscreen && CONFIG.behavior().lock_fullscreen;
+                client.state.is_fullscreen && CONFIG.load().behavior().lock_fullscreen;
             Ok(!is_locked_fullscreen)
         } else {
             Err("Selected client not found".into())
@@ -4019,8 +5441,8 @@ impl Jwm {
 
         // 现在可以安全调用 &mut self 方法
         if let Some(mon_num) = monitor_num_opt {
-            if self.current_bar_monitor_id == Some(mon_num) {
-                self.position_statusbar_on_monitor(mon_num)?;
+            if self.status_bars.contains_key(sel_mon_key) {
+                self.position_statusbar_on_monitor(sel_mon_key)?;
                 self.arrange(Some(sel_mon_key));
                 let _ = self.restack(Some(sel_mon_key));
             }
@@ -4046,8 +5468,8 @@ impl Jwm {
         };
 
         // 再调用需要 &mut self 的方法
-        if self.current_bar_monitor_id == Some(mon_num) {
-            self.position_statusbar_on_monitor(mon_num)?;
+        if self.status_bars.contains_key(sel_mon_key) {
+            self.position_statusbar_on_monitor(sel_mon_key)?;
             self.arrange(Some(sel_mon_key));
             let _ = self.restack(Some(sel_mon_key));
             self.mark_bar_update_needed_if_visible(Some(mon_num));
@@ -4085,6 +5507,105 @@ impl Jwm {
         Ok(())
     }
 
+    // 修改当前 tag 的间隙并重新布局
+    fn update_gaps(
+        &mut self,
+        f: impl FnOnce(&mut layout::Gaps),
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let sel_mon_key = self.sel_mon.ok_or("No monitor selected")?;
+        if let Some(monitor) = self.monitors.get_mut(sel_mon_key) {
+            let mut gaps = monitor.layout.gaps;
+            f(&mut gaps);
+            gaps.inner_h = gaps.inner_h.max(0);
+            gaps.inner_v = gaps.inner_v.max(0);
+            gaps.outer_h = gaps.outer_h.max(0);
+            gaps.outer_v = gaps.outer_v.max(0);
+            monitor.layout.gaps = gaps;
+            if let Some(ref mut pertag) = monitor.pertag {
+                let cur_tag = pertag.cur_tag;
+                pertag.gaps[cur_tag] = gaps;
+                info!(
+                    "[update_gaps] Updated gaps to {:?} for tag {}",
+                    gaps, cur_tag
+                );
+            }
+        }
+        self.arrange(Some(sel_mon_key));
+        Ok(())
+    }
+
+    pub fn incrgaps(&mut self, arg: &WMArgEnum) -> Result<(), Box<dyn std::error::Error>> {
+        if let WMArgEnum::Int(i) = *arg {
+            self.update_gaps(|g| {
+                g.inner_h += i;
+                g.inner_v += i;
+                g.outer_h += i;
+                g.outer_v += i;
+            })?;
+        }
+        Ok(())
+    }
+
+    pub fn incrigaps(&mut self, arg: &WMArgEnum) -> Result<(), Box<dyn std::error::Error>> {
+        if let WMArgEnum::Int(i) = *arg {
+            self.update_gaps(|g| {
+                g.inner_h += i;
+                g.inner_v += i;
+            })?;
+        }
+        Ok(())
+    }
+
+    pub fn incrogaps(&mut self, arg: &WMArgEnum) -> Result<(), Box<dyn std::error::Error>> {
+        if let WMArgEnum::Int(i) = *arg {
+            self.update_gaps(|g| {
+                g.outer_h += i;
+                g.outer_v += i;
+            })?;
+        }
+        Ok(())
+    }
+
+    pub fn incrihgaps(&mut self, arg: &WMArgEnum) -> Result<(), Box<dyn std::error::Error>> {
+        if let WMArgEnum::Int(i) = *arg {
+            self.update_gaps(|g| g.inner_h += i)?;
+        }
+        Ok(())
+    }
+
+    pub fn incrivgaps(&mut self, arg: &WMArgEnum) -> Result<(), Box<dyn std::error::Error>> {
+        if let WMArgEnum::Int(i) = *arg {
+            self.update_gaps(|g| g.inner_v += i)?;
+        }
+        Ok(())
+    }
+
+    pub fn incrohgaps(&mut self, arg: &WMArgEnum) -> Result<(), Box<dyn std::error::Error>> {
+        if let WMArgEnum::Int(i) = *arg {
+            self.update_gaps(|g| g.outer_h += i)?;
+        }
+        Ok(())
+    }
+
+    pub fn incrovgaps(&mut self, arg: &WMArgEnum) -> Result<(), Box<dyn std::error::Error>> {
+        if let WMArgEnum::Int(i) = *arg {
+            self.update_gaps(|g| g.outer_v += i)?;
+        }
+        Ok(())
+    }
+
+    pub fn togglegaps(&mut self, _arg: &WMArgEnum) -> Result<(), Box<dyn std::error::Error>> {
+        self.gaps_enabled = !self.gaps_enabled;
+        info!("[togglegaps] gaps_enabled: {}", self.gaps_enabled);
+        // 开关对所有显示器生效
+        self.arrange(None);
+        Ok(())
+    }
+
+    pub fn defaultgaps(&mut self, _arg: &WMArgEnum) -> Result<(), Box<dyn std::error::Error>> {
+        self.update_gaps(|g| *g = CONFIG.load().gaps())
+    }
+
     pub fn setcfact(&mut self, arg: &WMArgEnum) -> Result<(), Box<dyn std::error::Error>> {
         // info!("[setcfact]");
 
@@ -4345,7 +5866,7 @@ impl Jwm {
         arg: &WMArgEnum,
     ) -> Result<(), Box<dyn std::error::Error>> {
         match *arg {
-            WMArgEnum::Layout(ref lt) => self.handle_specific_layout(sel_mon_key, lt),
+            WMArgEnum::Layout(lt) => self.handle_specific_layout(sel_mon_key, lt),
             _ => self.toggle_layout_selection(sel_mon_key),
         }
     }
@@ -4354,18 +5875,18 @@ impl Jwm {
     fn handle_specific_layout(
         &mut self,
         sel_mon_key: MonitorKey,
-        layout: &Rc<LayoutEnum>,
+        layout: &'static dyn Layout,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let monitor = self.monitors.get(sel_mon_key).ok_or("Monitor not found")?;
 
-        let current_layout = monitor.lt[monitor.sel_lt].clone();
+        let current_layout = monitor.lt[monitor.sel_lt];
         let cur_tag = monitor
             .pertag
             .as_ref()
             .ok_or("No pertag information")?
             .cur_tag;
 
-        if **layout == *current_layout {
+        if *layout == *current_layout {
             // 如果是相同布局，则切换选择
             self.toggle_layout_selection_impl(sel_mon_key, cur_tag);
         } else {
@@ -4403,12 +5924,17 @@ impl Jwm {
     }
 
     // 设置新布局
-    fn set_new_layout(&mut self, sel_mon_key: MonitorKey, layout: &Rc<LayoutEnum>, cur_tag: usize) {
+    fn set_new_layout(
+        &mut self,
+        sel_mon_key: MonitorKey,
+        layout: &'static dyn Layout,
+        cur_tag: usize,
+    ) {
         if let Some(monitor) = self.monitors.get_mut(sel_mon_key) {
             let sel_lt = monitor.sel_lt;
             if let Some(ref mut pertag) = monitor.pertag {
-                pertag.lt_idxs[cur_tag][sel_lt] = Some(layout.clone());
-                monitor.lt[sel_lt] = layout.clone();
+                pertag.lt_idxs[cur_tag][sel_lt] = Some(layout);
+                monitor.lt[sel_lt] = layout;
             }
         }
     }
@@ -4504,10 +6030,11 @@ impl Jwm {
         );
 
         // 执行标签切换
-        let cur_tag = self.switch_to_tag(next_tag, next_tag)?;
+        let sel_mon_key = self.sel_mon.ok_or("No monitor selected")?;
+        let cur_tag = self.switch_to_tag(sel_mon_key, next_tag, next_tag)?;
 
         // 应用per-tag设置
-        let sel_opt = self.apply_pertag_settings(cur_tag)?;
+        let sel_opt = self.apply_pertag_settings(sel_mon_key, cur_tag)?;
 
         // 更新焦点和布局
         self.focus(sel_opt)?;
@@ -4539,14 +6066,14 @@ impl Jwm {
             current_tag.trailing_zeros() as usize
         };
 
-        const MAX_TAGS: usize = 9;
+        let tags_length = CONFIG.load().tags_length();
         let next_tag_index = if direction > 0 {
-            // 向前循环：1>2>3>...>9>1
-            (current_tag_index + 1) % MAX_TAGS
+            // 向前循环：1>2>3>...>n>1
+            (current_tag_index + 1) % tags_length
         } else {
-            // 向后循环：1>9>8>...>2>1
+            // 向后循环：1>n>...>2>1
             if current_tag_index == 0 {
-                MAX_TAGS - 1
+                tags_length - 1
             } else {
                 current_tag_index - 1
             }
@@ -4571,7 +6098,7 @@ impl Jwm {
             _ => return Ok(()),
         };
 
-        let target_tag = ui & CONFIG.tagmask();
+        let target_tag = ui & CONFIG.load().tagmask();
 
         // 检查是否需要切换标签
         if self.is_same_tag(target_tag) {
@@ -4581,10 +6108,11 @@ impl Jwm {
         info!("[view] ui: {}, target_tag: {}", ui, target_tag);
 
         // 执行标签切换
-        let cur_tag = self.switch_to_tag(target_tag, ui)?;
+        let sel_mon_key = self.sel_mon.ok_or("No monitor selected")?;
+        let cur_tag = self.switch_to_tag(sel_mon_key, target_tag, ui)?;
 
         // 应用per-tag设置
-        let sel_opt = self.apply_pertag_settings(cur_tag)?;
+        let sel_opt = self.apply_pertag_settings(sel_mon_key, cur_tag)?;
 
         // 更新焦点和布局
         self.focus(sel_opt)?;
@@ -4608,14 +6136,11 @@ impl Jwm {
     // 切换到指定标签
     fn switch_to_tag(
         &mut self,
+        mon_key: MonitorKey,
         target_tag: u32,
         ui: u32,
     ) -> Result<usize, Box<dyn std::error::Error>> {
-        let sel_mon_key = match self.sel_mon {
-            Some(k) => k,
-            None => return Ok(0),
-        };
-        let sel_mon_mut = if let Some(sel_mon) = self.monitors.get_mut(sel_mon_key) {
+        let sel_mon_mut = if let Some(sel_mon) = self.monitors.get_mut(mon_key) {
             sel_mon
         } else {
             return Ok(0);
@@ -4669,12 +6194,11 @@ impl Jwm {
 
     fn apply_pertag_settings(
         &mut self,
+        sel_mon_key: MonitorKey,
         cur_tag: usize,
     ) -> Result<Option<ClientKey>, Box<dyn std::error::Error>> {
-        let sel_mon_key = self.sel_mon.ok_or("No monitor selected")?;
-
         // 先提取所有需要的值，避免借用冲突
-        let (n_master, m_fact, sel_lt, layout_0, layout_1, sel_client_key) = {
+        let (n_master, m_fact, gaps, sel_lt, layout_0, layout_1, sel_client_key) = {
             let monitor = self
                 .monitors
                 .get(sel_mon_key)
@@ -4689,13 +6213,10 @@ impl Jwm {
             (
                 pertag.n_masters[cur_tag],
                 pertag.m_facts[cur_tag],
+                pertag.gaps[cur_tag],
                 sel_lt,
-                pertag.lt_idxs[cur_tag][sel_lt]
-                    .clone()
-                    .ok_or("Layout not found")?,
-                pertag.lt_idxs[cur_tag][sel_lt ^ 1]
-                    .clone()
-                    .ok_or("Alternative layout not found")?,
+                pertag.lt_idxs[cur_tag][sel_lt].ok_or("Layout not found")?,
+                pertag.lt_idxs[cur_tag][sel_lt ^ 1].ok_or("Alternative layout not found")?,
                 pertag.sel[cur_tag],
             )
         };
@@ -4704,6 +6225,7 @@ impl Jwm {
         if let Some(monitor) = self.monitors.get_mut(sel_mon_key) {
             monitor.layout.n_master = n_master;
             monitor.layout.m_fact = m_fact;
+            monitor.layout.gaps = gaps;
             monitor.sel_lt = sel_lt;
             monitor.lt[sel_lt] = layout_0;
             monitor.lt[sel_lt ^ 1] = layout_1;
@@ -4746,7 +6268,7 @@ impl Jwm {
         // 计算新的标签集
         let (sel_tags, newtagset) = if let Some(monitor) = self.monitors.get(sel_mon_key) {
             let sel_tags = monitor.sel_tags;
-            let newtagset = monitor.tag_set[sel_tags] ^ (ui & CONFIG.tagmask());
+            let newtagset = monitor.tag_set[sel_tags] ^ (ui & CONFIG.load().tagmask());
             (sel_tags, newtagset)
         } else {
             return Ok(());
@@ -4838,7 +6360,7 @@ impl Jwm {
         let monitor = self.monitors.get_mut(mon_key).ok_or("Monitor not found")?;
 
         // 提取所有需要的值
-        let (n_master, m_fact, sel_lt, layout_0, layout_1) = {
+        let (n_master, m_fact, gaps, sel_lt, layout_0, layout_1) = {
             let pertag = monitor
                 .pertag
                 .as_ref()
@@ -4848,13 +6370,10 @@ impl Jwm {
             (
                 pertag.n_masters[cur_tag],
                 pertag.m_facts[cur_tag],
+                pertag.gaps[cur_tag],
                 sel_lt,
-                pertag.lt_idxs[cur_tag][sel_lt]
-                    .clone()
-                    .ok_or("Layout not found")?,
-                pertag.lt_idxs[cur_tag][sel_lt ^ 1]
-                    .clone()
-                    .ok_or("Alternative layout not found")?,
+                pertag.lt_idxs[cur_tag][sel_lt].ok_or("Layout not found")?,
+                pertag.lt_idxs[cur_tag][sel_lt ^ 1].ok_or("Alternative layout not found")?,
             )
         };
 
@@ -4862,6 +6381,7 @@ impl Jwm {
         let monitor = self.monitors.get_mut(mon_key).unwrap();
         monitor.layout.n_master = n_master;
         monitor.layout.m_fact = m_fact;
+        monitor.layout.gaps = gaps;
         monitor.sel_lt = sel_lt;
         monitor.lt[sel_lt] = layout_0;
         monitor.lt[sel_lt ^ 1] = layout_1;
@@ -4917,7 +6437,7 @@ impl Jwm {
                 return Ok(());
             };
 
-            let newtags = current_tags ^ (ui & CONFIG.tagmask());
+            let newtags = current_tags ^ (ui & CONFIG.load().tagmask());
 
             if newtags > 0 {
                 // 更新客户端标签
@@ -4955,17 +6475,250 @@ impl Jwm {
                 EwmhFeature::WmState,
                 EwmhFeature::SupportingWmCheck,
                 EwmhFeature::WmStateFullscreen,
+                EwmhFeature::WmStateSticky,
+                EwmhFeature::WmStateAbove,
+                EwmhFeature::WmStateBelow,
+                EwmhFeature::WmStateMaximizedVert,
+                EwmhFeature::WmStateMaximizedHorz,
+                EwmhFeature::WmStateHidden,
+                EwmhFeature::WmStateDemandsAttention,
+                EwmhFeature::WmStateSkipTaskbar,
+                EwmhFeature::WmMoveresize,
+                EwmhFeature::MoveresizeWindow,
+                EwmhFeature::CloseWindow,
+                EwmhFeature::RestackWindow,
                 EwmhFeature::ClientList,
                 EwmhFeature::ClientInfo,
                 EwmhFeature::WmWindowType,
                 EwmhFeature::WmWindowTypeDialog,
+                EwmhFeature::DesktopNames,
+                EwmhFeature::NumberOfDesktops,
+                EwmhFeature::CurrentDesktop,
+                EwmhFeature::DesktopViewport,
+                EwmhFeature::Workarea,
+                EwmhFeature::WmDesktop,
             ];
             facade.declare_supported(&supported)?;
         }
+        self.update_ewmh_desktops()?;
         self.backend.window_ops().flush()?;
         Ok(())
     }
 
+    // 每个标签对应一个 EWMH 桌面；标签数量或名称变化后调用
+    fn update_ewmh_desktops(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(facade) = self.backend.ewmh_facade().as_ref() {
+            let config = CONFIG.load();
+            facade.set_number_of_desktops(config.tags_length() as u32)?;
+            facade.set_desktop_names(&config.tag_names())?;
+        }
+        // 强制下次同步时重新发布当前桌面与工作区
+        self.ewmh_desktop_state = None;
+        self.sync_ewmh_current_desktop();
+        Ok(())
+    }
+
+    // 当前桌面取选中显示器上选中的第一个标签；EWMH 每个桌面只能描述一个
+    // 工作区，这里发布选中显示器的工作区。只在变化时写属性
+    fn sync_ewmh_current_desktop(&mut self) {
+        let state = match self.get_sel_mon() {
+            Some(mon) => {
+                let g = &mon.geometry;
+                (
+                    mon.tag_set[mon.sel_tags].trailing_zeros(),
+                    [
+                        g.w_x.max(0) as u32,
+                        g.w_y.max(0) as u32,
+                        g.w_w.max(0) as u32,
+                        g.w_h.max(0) as u32,
+                    ],
+                )
+            }
+            None => return,
+        };
+        if self.ewmh_desktop_state == Some(state) {
+            return;
+        }
+        self.ewmh_desktop_state = Some(state);
+        if let Some(facade) = self.backend.ewmh_facade().as_ref() {
+            let areas = vec![state.1; CONFIG.load().tags_length()];
+            let _ = facade.set_current_desktop(state.0);
+            let _ = facade.set_workarea(&areas);
+        }
+    }
+
+    // 窗口所在桌面：sticky 为所有桌面，否则取第一个标签；隐藏的 scratchpad 不更新
+    fn client_desktop(client: &WMClient) -> Option<u32> {
+        if client.state.is_sticky {
+            Some(ALL_DESKTOPS)
+        } else if client.state.tags != 0 {
+            Some(client.state.tags.trailing_zeros())
+        } else {
+            None
+        }
+    }
+
+    // 自绘标题栏的窗口请求开始拖动，复用 movemouse/resizemouse 的拖动循环
+    fn handle_moveresize_message(
+        &mut self,
+        win: u32,
+        action: MoveResizeAction,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // 拖动循环是同步的，收到取消时已经结束
+        if action == MoveResizeAction::Cancel {
+            return Ok(());
+        }
+        let client_key = match self.wintoclient(win) {
+            Some(ck) => ck,
+            None => return Ok(()),
+        };
+        if self.clients[client_key].state.is_fullscreen {
+            return Ok(());
+        }
+        // 拖动以松开按键结束，按键已经松开时不再开始
+        const ANY_BUTTON_MASK: u16 = 0x1f00;
+        let (_, _, mask, _) = self.backend.input_ops().query_pointer_root()?;
+        if mask & ANY_BUTTON_MASK == 0 {
+            debug!("[handle_moveresize_message] No button held, ignoring");
+            return Ok(());
+        }
+        if !self.is_client_selected(client_key) {
+            self.focus(Some(client_key))?;
+        }
+        match action {
+            MoveResizeAction::Move => self.movemouse(&WMArgEnum::Int(0)),
+            MoveResizeAction::Resize {
+                horizontal,
+                vertical,
+            } => self.resize_from_edge(client_key, horizontal, vertical),
+            MoveResizeAction::Cancel => Ok(()),
+        }
+    }
+
+    // _NET_MOVERESIZE_WINDOW 使用根窗口坐标；平铺窗口的几何由布局决定，只回送当前配置
+    fn handle_moveresize_window_message(
+        &mut self,
+        win: u32,
+        x: Option<i32>,
+        y: Option<i32>,
+        w: Option<u32>,
+        h: Option<u32>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let client_key = match self.wintoclient(win) {
+            Some(ck) => ck,
+            None => return Ok(()),
+        };
+        let client = &self.clients[client_key];
+        if !client.state.is_floating || client.state.is_fullscreen {
+            return self.configure_client(client_key);
+        }
+        let g = &client.geometry;
+        let x = x.unwrap_or(g.x);
+        let y = y.unwrap_or(g.y);
+        let w = w.map_or(g.w, |w| (w as i32).max(1));
+        let h = h.map_or(g.h, |h| (h as i32).max(1));
+        self.resize_client(client_key, x, y, w, h, false);
+        Ok(())
+    }
+
+    // 任务栏请求关闭窗口，与 killclient 相同：先礼貌关闭，不支持时强制结束
+    fn handle_close_window_message(&mut self, win: u32) -> Result<(), Box<dyn std::error::Error>> {
+        let client_key = match self.wintoclient(win) {
+            Some(ck) => ck,
+            None => return Ok(()),
+        };
+        info!("[handle_close_window_message] Closing window 0x{:x}", win);
+        if self.sendevent_by_window(win) {
+            return Ok(());
+        }
+        self.force_kill_client(client_key)
+    }
+
+    // 浮动窗口在堆栈中移到 sibling 之上/之下，没有 sibling 时置顶/置底
+    fn handle_restack_window_message(
+        &mut self,
+        win: u32,
+        sibling: Option<u32>,
+        above: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let client_key = match self.wintoclient(win) {
+            Some(ck) => ck,
+            None => return Ok(()),
+        };
+        let mon_key = match self.clients.get(client_key) {
+            Some(c) if c.state.is_floating => c.mon,
+            _ => return Ok(()),
+        };
+        let mon_key = match mon_key {
+            Some(mon_key) => mon_key,
+            None => return Ok(()),
+        };
+        let sibling_key = sibling.and_then(|s| self.wintoclient(s));
+        if let Some(stack) = self.monitor_stack.get_mut(mon_key) {
+            // 堆栈从顶到底排列
+            stack.retain(|&k| k != client_key);
+            let sibling_pos = sibling_key.and_then(|sk| stack.iter().position(|&k| k == sk));
+            let pos = match (sibling_pos, above) {
+                (Some(p), true) => p,
+                (Some(p), false) => p + 1,
+                (None, true) => 0,
+                (None, false) => stack.len(),
+            };
+            stack.insert(pos, client_key);
+        }
+        self.restack(Some(mon_key))
+    }
+
+    // 分页器请求切换桌面
+    fn handle_current_desktop_message(
+        &mut self,
+        desktop: u32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if desktop as usize >= CONFIG.load().tags_length() {
+            warn!(
+                "[handle_current_desktop_message] Invalid desktop {}",
+                desktop
+            );
+            return Ok(());
+        }
+        self.view(&WMArgEnum::UInt(1 << desktop))
+    }
+
+    // 请求把窗口移到某个桌面，ALL_DESKTOPS 表示设为 sticky
+    fn handle_wm_desktop_message(
+        &mut self,
+        win: u32,
+        desktop: u32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let client_key = match self.wintoclient(win) {
+            Some(ck) => ck,
+            None => return Ok(()),
+        };
+        if desktop == ALL_DESKTOPS {
+            return self.setsticky(client_key, true);
+        }
+        if desktop as usize >= CONFIG.load().tags_length() {
+            warn!("[handle_wm_desktop_message] Invalid desktop {}", desktop);
+            return Ok(());
+        }
+        self.setsticky(client_key, false)?;
+        let mon_key = match self.clients.get_mut(client_key) {
+            Some(client) => {
+                client.state.tags = 1 << desktop;
+                client.mon
+            }
+            None => return Ok(()),
+        };
+        self.setclienttagprop(client_key)?;
+        self.focus(None)?;
+        if let Some(mon_key) = mon_key {
+            self.arrange(Some(mon_key));
+        }
+        let mon_num = mon_key.and_then(|k| self.monitors.get(k)).map(|m| m.num);
+        self.mark_bar_update_needed_if_visible(mon_num);
+        Ok(())
+    }
+
     pub fn setup(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         info!("[setup]");
         self.backend.init_visual()?;
@@ -4975,6 +6728,7 @@ impl Jwm {
         let mask = (EventMaskBits::SUBSTRUCTURE_REDIRECT
             | EventMaskBits::STRUCTURE_NOTIFY
             | EventMaskBits::BUTTON_PRESS
+            | EventMaskBits::BUTTON_RELEASE
             | EventMaskBits::POINTER_MOTION
             | EventMaskBits::ENTER_WINDOW
             | EventMaskBits::LEAVE_WINDOW
@@ -4988,15 +6742,30 @@ impl Jwm {
         self.backend
             .window_ops()
             .change_event_mask(self.backend.root_window(), mask)?;
+        if let Err(e) = self.backend.output_ops().watch_output_changes() {
+            warn!("[setup] Monitor hotplug disabled: {}", e);
+        }
         self.grabkeys()?;
         self.focus(None)?;
         self.backend.window_ops().flush()?;
 
+        match ConfigWatcher::new(Config::get_default_config_path()) {
+            Ok(watcher) => self.config_watcher = Some(watcher),
+            Err(e) => warn!("[setup] Config hot-reload disabled: {}", e),
+        }
+        match IpcServer::bind(&ipc::socket_path()) {
+            Ok(server) => self.ipc_server = Some(server),
+            Err(e) => warn!("[setup] IPC disabled: {}", e),
+        }
+
         let snapshot_opt = Self::load_restart_snapshot();
 
         self.restoring_from_snapshot = snapshot_opt.is_some();
 
-        self.scan()?;
+        self.scanning = true;
+        let scanned = self.scan();
+        self.scanning = false;
+        scanned?;
 
         if let Some(snap) = snapshot_opt {
             info!("[setup] applying snapshot...");
@@ -5163,10 +6932,16 @@ impl Jwm {
         // 获取新标题
         let new_title = self.fetch_window_title(win);
         // 更新客户端标题
+        let mut changed = false;
         if let Some(client) = self.clients.get_mut(client_key) {
+            changed = client.name != new_title;
             client.name = new_title;
             debug!("Updated title for window 0x{:x}: '{}'", win, client.name);
         }
+        if changed {
+            let title = self.clients[client_key].name.clone();
+            self.emit_ipc_event(IpcEvent::TitleChanged { window: win, title });
+        }
     }
 
     // 截断到字符数（非字节数）上限
@@ -5203,6 +6978,9 @@ impl Jwm {
         // 更新标题
         self.updatetitle_by_key(client_key);
 
+        // 按新标题重新匹配规则
+        self.reapply_rules(client_key)?;
+
         // 检查是否需要更新状态栏
         let should_update_bar = self.is_client_selected(client_key);
 
@@ -5249,7 +7027,7 @@ impl Jwm {
 
             if let Some(mon_key) = client.mon {
                 if let Some(monitor) = self.monitors.get(mon_key) {
-                    return !monitor.lt[monitor.sel_lt].is_tile();
+                    return monitor.lt[monitor.sel_lt].is_floating();
                 }
             }
         }
@@ -5273,7 +7051,7 @@ impl Jwm {
                 return Ok(());
             };
 
-        let snap_distance = CONFIG.snap() as i32;
+        let snap_distance = CONFIG.load().snap() as i32;
 
         // 吸附到左边缘
         if (mon_wx - *new_x).abs() < snap_distance {
@@ -5306,7 +7084,7 @@ impl Jwm {
             if let Some(client) = self.clients.get(client_key) {
                 let layout_is_tile = if let Some(mon_key) = client.mon {
                     if let Some(monitor) = self.monitors.get(mon_key) {
-                        monitor.lt[monitor.sel_lt].is_tile()
+                        !monitor.lt[monitor.sel_lt].is_floating()
                     } else {
                         false
                     }
@@ -5327,8 +7105,8 @@ impl Jwm {
         // 如果窗口不是浮动的且当前是平铺布局，并且移动距离超过阈值
         if !is_floating
             && current_layout_is_tile
-            && ((new_x - current_x).abs() > CONFIG.snap() as i32
-                || (new_y - current_y).abs() > CONFIG.snap() as i32)
+            && ((new_x - current_x).abs() > CONFIG.load().snap() as i32
+                || (new_y - current_y).abs() > CONFIG.load().snap() as i32)
         {
             self.togglefloating(&WMArgEnum::Int(0))?;
         }
@@ -5486,19 +7264,82 @@ impl Jwm {
 
                     self.check_and_toggle_floating_for_resize(client_key, new_width, new_height)?;
                     if self.should_resize_client(client_key) {
-                        self.resize_client(
-                            client_key, start_x, start_y, new_width, new_height, true,
-                        );
+                        self.resize_client(
+                            client_key, start_x, start_y, new_width, new_height, true,
+                        );
+                    }
+                    Ok(())
+                },
+            )?;
+        }
+
+        self.cleanup_resize(window_id, border_w)?;
+        Ok(())
+    }
+
+    /// 从任意边或角拖动调整大小，按指针位移计算，不移动指针
+    fn resize_from_edge(
+        &mut self,
+        client_key: ClientKey,
+        horizontal: i8,
+        vertical: i8,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.restack(self.sel_mon)?;
+
+        let (start_x, start_y, start_w, start_h, window_id) = match self.clients.get(client_key) {
+            Some(c) => (
+                c.geometry.x,
+                c.geometry.y,
+                c.geometry.w,
+                c.geometry.h,
+                c.win,
+            ),
+            None => return Err("Client not found".into()),
+        };
+        let (pointer_x, pointer_y, _mask, _unused) =
+            self.backend.input_ops().query_pointer_root()?;
+
+        let cursor_handle = self.backend.cursor_provider().get(StdCursorKind::Fleur)?.0;
+
+        let io = self.backend.input_ops_handle();
+        {
+            let ops = io.lock().unwrap();
+            ops.drag_loop(
+                Some(cursor_handle),
+                None,
+                WindowId(window_id.into()),
+                &mut |root_x, root_y, _time| {
+                    let (x, w) =
+                        Self::drag_edge(start_x, start_w, horizontal, root_x as i32 - pointer_x);
+                    let (y, h) =
+                        Self::drag_edge(start_y, start_h, vertical, root_y as i32 - pointer_y);
+
+                    self.check_and_toggle_floating_for_resize(client_key, w, h)?;
+                    if self.should_resize_client(client_key) {
+                        self.resize_client(client_key, x, y, w, h, true);
                     }
                     Ok(())
                 },
             )?;
         }
 
-        self.cleanup_resize(window_id, border_w)?;
+        self.backend.input_ops().ungrab_pointer()?;
+        self.check_monitor_change_after_resize()?;
         Ok(())
     }
 
+    // 单个方向上拖动边：edge 为 -1 时移动起点，1 时移动终点，返回 (起点, 长度)
+    fn drag_edge(start: i32, len: i32, edge: i8, delta: i32) -> (i32, i32) {
+        match edge {
+            1 => (start, (len + delta).max(1)),
+            -1 => {
+                let new_len = (len - delta).max(1);
+                (start + len - new_len, new_len)
+            }
+            _ => (start, len),
+        }
+    }
+
     fn check_and_toggle_floating_for_resize(
         &mut self,
         client_key: ClientKey,
@@ -5509,7 +7350,7 @@ impl Jwm {
             if let Some(client) = self.clients.get(client_key) {
                 let is_tile = if let Some(mon_key) = client.mon {
                     if let Some(monitor) = self.monitors.get(mon_key) {
-                        monitor.lt[monitor.sel_lt].is_tile()
+                        !monitor.lt[monitor.sel_lt].is_floating()
                     } else {
                         false
                     }
@@ -5528,7 +7369,7 @@ impl Jwm {
             };
 
         if !is_floating && is_tile_layout {
-            let snap_threshold = CONFIG.snap() as i32;
+            let snap_threshold = CONFIG.load().snap() as i32;
             if (new_width - current_w).abs() > snap_threshold
                 || (new_height - current_h).abs() > snap_threshold
             {
@@ -5549,7 +7390,7 @@ impl Jwm {
 
             if let Some(mon_key) = client.mon {
                 if let Some(monitor) = self.monitors.get(mon_key) {
-                    return !monitor.lt[monitor.sel_lt].is_tile();
+                    return monitor.lt[monitor.sel_lt].is_floating();
                 }
             }
         }
@@ -5641,6 +7482,11 @@ impl Jwm {
                 client.state.tags,
                 monitor_num,
             )?;
+            if let Some(desktop) = Self::client_desktop(client) {
+                if let Some(facade) = self.backend.ewmh_facade().as_ref() {
+                    facade.set_window_desktop(WindowId(client.win.into()), desktop)?;
+                }
+            }
             self.backend.window_ops().flush()?;
         }
         Ok(())
@@ -5673,19 +7519,8 @@ impl Jwm {
 
         if let Some(monitor) = self.monitors.get(target_monitor_key) {
             info!("Switched to monitor {}", monitor.num);
-            let old_mon_id = self.current_bar_monitor_id;
-            let new_mon_id = monitor.num;
-
-            if old_mon_id != Some(new_mon_id) {
-                self.current_bar_monitor_id = Some(new_mon_id);
-                self.position_statusbar_on_monitor(new_mon_id)?;
-                // 旧屏与新屏都 arrange 一次，更新偏移
-                if let Some(old_id) = old_mon_id {
-                    if let Some(old_key) = self.get_monitor_by_id(old_id) {
-                        self.arrange(Some(old_key));
-                    }
-                }
-                self.arrange(Some(target_monitor_key));
+            // 单实例状态栏跟随选中显示器
+            if self.move_status_bar(target_monitor_key)? {
                 self.restack(Some(target_monitor_key))?;
             }
         }
@@ -5793,7 +7628,7 @@ impl Jwm {
                 .grab_button_any_anymod(WindowId(client_win_id.into()), BUTTONMASK.bits())?;
         }
 
-        for button_config in CONFIG.get_buttons().iter() {
+        for button_config in CONFIG.load().get_buttons().iter() {
             if button_config.click_type == WMClickType::ClickClientWin {
                 let base = button_config.mask;
                 let combos = [
@@ -5831,7 +7666,7 @@ impl Jwm {
         // 如果传入的是状态栏客户端，忽略并寻找合适的替代
         if let Some(client_key) = client_key_opt {
             if let Some(client) = self.clients.get(client_key) {
-                if Some(client.win) == self.status_bar_window {
+                if self.is_status_bar_window(client.win) {
                     client_key_opt = None; // 忽略状态栏
                 }
             }
@@ -5859,6 +7694,7 @@ impl Jwm {
 
         // 更新选中监视器的状态
         self.update_monitor_selection_by_key(client_key_opt);
+        self.emit_focus_event(client_key_opt);
 
         // 标记状态栏需要更新
         self.mark_bar_update_needed_if_visible(None);
@@ -6056,21 +7892,28 @@ impl Jwm {
         client.geometry.old_border_w = geom.border as i32;
         client.state.client_fact = 1.0;
         client.name = self.fetch_window_title(client.win);
+        client.pid = self
+            .backend
+            .property_ops()
+            .get_window_pid(WindowId(win.into()))
+            .unwrap_or(0);
         self.update_class_info(&mut client);
 
         info!("[manage] {}", client);
         // 检查是否是状态栏
         if client.is_status_bar() {
             info!("[manage] Detected status bar, managing as statusbar");
+            let pid = client.pid;
             // 插入到SlotMap
             let client_key = self.insert_client(client);
-            // 绑定到当前聚焦显示器
-            let current_mon_id = self.get_sel_mon().map(|m| m.num).unwrap_or(0);
-            self.status_bar_client = Some(client_key);
-            self.status_bar_window = Some(win);
-            self.current_bar_monitor_id = Some(current_mon_id);
-
-            return self.manage_statusbar(client_key, win, current_mon_id);
+            // 绑定到启动它的显示器
+            return match self.claim_status_bar(pid, client_key, win) {
+                Some(mon_key) => self.manage_statusbar(client_key, win, mon_key),
+                None => Ok(()),
+            };
+        }
+        if self.is_dock_window(win) {
+            return self.manage_dock(win);
         }
 
         // 插入到SlotMap
@@ -6105,7 +7948,7 @@ impl Jwm {
         info!("[setup_client_window] Setting up window 0x{:x}", win);
 
         if let Some(client) = self.clients.get_mut(client_key) {
-            client.geometry.border_w = CONFIG.border_px() as i32;
+            client.geometry.border_w = CONFIG.load().border_px() as i32;
         }
         let border_w = self.clients.get(client_key).unwrap().geometry.border_w;
         self.set_window_border_width(win, border_w as u32)?;
@@ -6149,6 +7992,7 @@ impl Jwm {
     fn handle_new_client_focus(
         &mut self,
         client_key: ClientKey,
+        no_focus: bool,
     ) -> Result<(), Box<dyn std::error::Error>> {
         // info!("[handle_new_client_focus]");
         if self.is_popup_like(client_key) {
@@ -6172,6 +8016,20 @@ impl Jwm {
             return Err("Client not found".into());
         };
 
+        if no_focus {
+            // 规则要求不抢焦点：只排列，保持原选中窗口
+            if let Some(mon_key) = client_mon_key {
+                self.arrange(Some(mon_key));
+            }
+            if let Some(prev_key) = self.get_selected_client_key() {
+                if prev_key != client_key {
+                    self.focus(Some(prev_key))?;
+                }
+            }
+            info!("[handle_new_client_focus] New client has no_focus rule, kept previous focus");
+            return Ok(());
+        }
+
         let current_client_monitor_is_selected_monitor = client_mon_key == self.sel_mon;
 
         if current_client_monitor_is_selected_monitor {
@@ -6223,7 +8081,7 @@ impl Jwm {
         }
 
         // 根据配置决定是否自动切换到新窗口的显示器
-        if CONFIG.behavior().focus_follows_new_window {
+        if CONFIG.load().behavior().focus_follows_new_window {
             if let Some(new_mon_key) = client_mon_key {
                 if Some(new_mon_key) != self.sel_mon {
                     // 切换到新窗口的显示器
@@ -6246,24 +8104,54 @@ impl Jwm {
         &mut self,
         client_key: ClientKey,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        // 处理 WM_TRANSIENT_FOR
-        self.handle_transient_for(client_key)?;
+        // 处理 WM_TRANSIENT_FOR（同时应用规则）
+        let rule = self.handle_transient_for(client_key)?;
+
+        // 认领由 togglescratch 启动的窗口
+        self.claim_scratchpad(client_key);
+
+        // 规则指定的初始几何，窗口尚未配置，直接写入
+        if let Some(ref rule) = rule {
+            if let Some((x, y, w, h)) = self.rule_geometry(client_key, rule) {
+                if let Some(client) = self.clients.get_mut(client_key) {
+                    client.geometry.x = x;
+                    client.geometry.y = y;
+                    client.geometry.w = w;
+                    client.geometry.h = h;
+                }
+            }
+        }
 
         // 调整窗口位置
         self.adjust_client_position(client_key);
 
         // 设置窗口属性
         self.setup_client_window(client_key)?;
+        if let Some(ref rule) = rule {
+            self.apply_rule_decorations(client_key, rule)?;
+        }
 
         // 更新各种提示
         self.updatewindowtype(client_key);
         self.updatesizehints(client_key)?;
         self.updatewmhints(client_key);
+        let _ = self.sync_net_wm_state(client_key);
 
         // 添加到管理结构
         self.attach(client_key);
         self.attachstack(client_key);
 
+        // 从终端启动的程序接替终端的位置
+        if !self.scanning {
+            if let Some(term_key) = self.term_for_client(client_key) {
+                self.swallow(term_key, client_key);
+            }
+        }
+
+        if rule.as_ref().is_some_and(|r| r.fullscreen) {
+            self.setfullscreen(client_key, true)?;
+        }
+
         // 注册事件和抓取按钮
         self.register_client_events(client_key)?;
 
@@ -6284,16 +8172,281 @@ impl Jwm {
         // 更新客户端列表
         self.update_net_client_list()?;
 
+        if let Some(c) = self.clients.get(client_key) {
+            let event = IpcEvent::ClientManaged {
+                window: c.win,
+                monitor: self.client_mon_num(client_key),
+                class: c.class.clone(),
+                instance: c.instance.clone(),
+                title: c.name.clone(),
+            };
+            self.emit_ipc_event(event);
+        }
+
         // 处理焦点
-        self.handle_new_client_focus(client_key)?;
+        let no_focus = rule.as_ref().is_some_and(|r| r.no_focus);
+        self.handle_new_client_focus(client_key, no_focus)?;
+
+        if rule.as_ref().is_some_and(|r| r.switch_to_tag) {
+            self.switch_to_client_tag(client_key)?;
+        }
 
         Ok(())
     }
 
-    fn handle_transient_for(
+    // 规则指定的几何：geometry 按显示器工作区解析，center 时忽略 x/y 居中；
+    // 只有 center 时保持窗口原尺寸居中
+    fn rule_geometry(&self, client_key: ClientKey, rule: &WMRule) -> Option<(i32, i32, i32, i32)> {
+        let client = self.clients.get(client_key)?;
+        let mon_key = client.mon?;
+        if let Some(ref geometry) = rule.geometry {
+            if rule.center {
+                let centered = GeometryConfig {
+                    x: None,
+                    y: None,
+                    ..geometry.clone()
+                };
+                return self.resolve_geometry(mon_key, &centered);
+            }
+            return self.resolve_geometry(mon_key, geometry);
+        }
+        if !rule.center {
+            return None;
+        }
+        let monitor = self.monitors.get(mon_key)?;
+        let x = monitor.geometry.w_x + (monitor.geometry.w_w - client.total_width()) / 2;
+        let y = monitor.geometry.w_y + (monitor.geometry.w_h - client.total_height()) / 2;
+        Some((x, y, client.geometry.w, client.geometry.h))
+    }
+
+    // 规则指定的边框宽度与透明度
+    fn apply_rule_decorations(
+        &mut self,
+        client_key: ClientKey,
+        rule: &WMRule,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let win = match self.clients.get(client_key) {
+            Some(client) => client.win,
+            None => return Ok(()),
+        };
+        if let Some(border_w) = rule.border_width {
+            if let Some(client) = self.clients.get_mut(client_key) {
+                client.geometry.border_w = border_w as i32;
+            }
+            self.set_window_border_width(win, border_w)?;
+        }
+        if let Some(opacity) = rule.opacity {
+            self.backend
+                .property_ops()
+                .set_window_opacity(WindowId(win.into()), Some(opacity))?;
+        }
+        Ok(())
+    }
+
+    // 切换到客户端所在的显示器和 tag 并聚焦它
+    fn switch_to_client_tag(
         &mut self,
         client_key: ClientKey,
     ) -> Result<(), Box<dyn std::error::Error>> {
+        let (mon_key, tags) = match self.clients.get(client_key) {
+            Some(WMClient {
+                mon: Some(mon_key),
+                state,
+                ..
+            }) => (*mon_key, state.tags),
+            _ => return Ok(()),
+        };
+        if self.sel_mon != Some(mon_key) {
+            self.switch_to_monitor(mon_key)?;
+        }
+        if !self.is_client_visible_on_monitor(client_key, mon_key) {
+            self.view(&WMArgEnum::UInt(tags))?;
+        }
+        self.focus(Some(client_key))?;
+        self.restack(Some(mon_key))?;
+        Ok(())
+    }
+
+    // 标题变化后重新匹配规则（如晚设置标题的程序），命中的规则变化时才重新应用
+    fn reapply_rules(&mut self, client_key: ClientKey) -> Result<(), Box<dyn std::error::Error>> {
+        if self.is_popup_like(client_key)
+            || self.swallowed.contains_key(&client_key)
+            || self.scratchpads.values().any(|&k| k == client_key)
+        {
+            return Ok(());
+        }
+        let props = match self.window_props(client_key) {
+            Some(props) => props,
+            None => return Ok(()),
+        };
+        let (index, rule) = match self.find_rule(&props) {
+            Some(matched) => matched,
+            None => return Ok(()),
+        };
+        if self.client_rules.get(client_key) == Some(&index) {
+            return Ok(());
+        }
+        info!(
+            "[reapply_rules] Title of '{}' now matches rule {}: {:?}",
+            props.name, index, rule
+        );
+        self.client_rules.insert(client_key, index);
+
+        let (old_mon, is_fullscreen) = match self.clients.get(client_key) {
+            Some(client) => (client.mon, client.state.is_fullscreen),
+            None => return Ok(()),
+        };
+        self.apply_single_rule(client_key, &rule);
+        // apply_single_rule 只改写归属，已管理的窗口需通过 sendmon 迁移
+        let new_mon = self.clients.get(client_key).and_then(|c| c.mon);
+        if new_mon != old_mon {
+            if let Some(client) = self.clients.get_mut(client_key) {
+                client.mon = old_mon;
+            }
+            self.sendmon(Some(client_key), new_mon);
+        }
+        if rule.tags > 0 {
+            let tags = rule.tags as u32 & CONFIG.load().tagmask();
+            if tags > 0 {
+                if let Some(client) = self.clients.get_mut(client_key) {
+                    client.state.tags = tags;
+                }
+            }
+        }
+        self.setclienttagprop(client_key)?;
+
+        if let Some((x, y, w, h)) = self.rule_geometry(client_key, &rule) {
+            self.resize_client(client_key, x, y, w, h, false);
+        }
+        self.apply_rule_decorations(client_key, &rule)?;
+        if rule.fullscreen && !is_fullscreen {
+            self.setfullscreen(client_key, true)?;
+        }
+
+        self.arrange(old_mon);
+        if new_mon != old_mon {
+            self.arrange(new_mon);
+        }
+        if rule.switch_to_tag {
+            self.switch_to_client_tag(client_key)?;
+        } else {
+            self.focus(None)?;
+        }
+        Ok(())
+    }
+
+    // 查找启动了该窗口进程的终端（沿 /proc 父进程链）
+    fn term_for_client(&self, client_key: ClientKey) -> Option<ClientKey> {
+        let client = self.clients.get(client_key)?;
+        if client.pid == 0 || client.state.is_terminal || client.state.no_swallow {
+            return None;
+        }
+        if self.is_popup_like(client_key) {
+            return None;
+        }
+        let pid = client.pid;
+        self.clients
+            .iter()
+            .find(|&(term_key, term)| {
+                term_key != client_key
+                    && term.state.is_terminal
+                    && term.pid != 0
+                    && !self.swallowed.values().any(|&k| k == term_key)
+                    && miscellaneous::is_descendant_process(term.pid, pid)
+            })
+            .map(|(term_key, _)| term_key)
+    }
+
+    // 子窗口接替终端在列表中的位置并继承其显示器、tag，终端移出列表并隐藏
+    fn swallow(&mut self, term_key: ClientKey, child_key: ClientKey) {
+        let (term_mon, term_tags, term_floating, term_geometry) = match self.clients.get(term_key) {
+            Some(term) => (
+                term.mon,
+                term.state.tags,
+                term.state.is_floating,
+                term.geometry.clone(),
+            ),
+            None => return,
+        };
+        let mon_key = match term_mon {
+            Some(mon_key) => mon_key,
+            None => return,
+        };
+        info!(
+            "[swallow] Client {:?} swallows terminal {:?}",
+            child_key, term_key
+        );
+
+        self.detach(child_key);
+        self.detachstack(child_key);
+        if let Some(child) = self.clients.get_mut(child_key) {
+            child.mon = Some(mon_key);
+            child.state.tags = term_tags;
+            child.state.is_floating = term_floating;
+            if term_floating {
+                child.geometry.x = term_geometry.x;
+                child.geometry.y = term_geometry.y;
+                child.geometry.w = term_geometry.w;
+                child.geometry.h = term_geometry.h;
+            }
+        }
+        self.replace_in_monitor(mon_key, term_key, child_key);
+        self.swallowed.insert(child_key, term_key);
+        self.hide_client(term_key);
+        let _ = self.setclienttagprop(child_key);
+    }
+
+    // 子窗口退出后终端回到其位置
+    fn unswallow(&mut self, child_key: ClientKey, term_key: ClientKey) {
+        let (child_mon, child_tags) = match self.clients.get(child_key) {
+            Some(child) => (child.mon, child.state.tags),
+            None => return,
+        };
+        let mon_key = match child_mon {
+            Some(mon_key) => mon_key,
+            None => return,
+        };
+        info!("[unswallow] Restoring terminal {:?}", term_key);
+
+        if let Some(term) = self.clients.get_mut(term_key) {
+            term.mon = Some(mon_key);
+            term.state.tags = child_tags;
+        }
+        self.replace_in_monitor(mon_key, child_key, term_key);
+        let _ = self.setclienttagprop(term_key);
+    }
+
+    // 在显示器的 clients/stack 列表中原位替换客户端，并转移选中状态
+    fn replace_in_monitor(&mut self, mon_key: MonitorKey, old_key: ClientKey, new_key: ClientKey) {
+        if let Some(client_list) = self.monitor_clients.get_mut(mon_key) {
+            if let Some(pos) = client_list.iter().position(|&k| k == old_key) {
+                client_list[pos] = new_key;
+            }
+        }
+        if let Some(stack_list) = self.monitor_stack.get_mut(mon_key) {
+            if let Some(pos) = stack_list.iter().position(|&k| k == old_key) {
+                stack_list[pos] = new_key;
+            }
+        }
+        if let Some(monitor) = self.monitors.get_mut(mon_key) {
+            if monitor.sel == Some(old_key) {
+                monitor.sel = Some(new_key);
+            }
+            if let Some(ref mut pertag) = monitor.pertag {
+                for sel in pertag.sel.iter_mut() {
+                    if *sel == Some(old_key) {
+                        *sel = Some(new_key);
+                    }
+                }
+            }
+        }
+    }
+
+    // 返回命中的规则，供 manage 流程应用几何/焦点等后续动作
+    fn handle_transient_for(
+        &mut self,
+        client_key: ClientKey,
+    ) -> Result<Option<WMRule>, Box<dyn std::error::Error>> {
         let win = if let Some(client) = self.clients.get(client_key) {
             client.win
         } else {
@@ -6327,7 +8480,7 @@ impl Jwm {
                     if let Some(client) = self.clients.get_mut(client_key) {
                         client.mon = self.sel_mon;
                     }
-                    self.applyrules_by_key(client_key);
+                    return Ok(self.applyrules_by_key(client_key));
                 }
             }
             None => {
@@ -6336,10 +8489,10 @@ impl Jwm {
                 if let Some(client) = self.clients.get_mut(client_key) {
                     client.mon = self.sel_mon;
                 }
-                self.applyrules_by_key(client_key);
+                return Ok(self.applyrules_by_key(client_key));
             }
         }
-        Ok(())
+        Ok(None)
     }
 
     fn update_class_info(&mut self, client: &mut WMClient) {
@@ -6350,18 +8503,82 @@ impl Jwm {
     }
 
     /// 检查规则是否匹配客户端
-    fn rule_matches(&self, rule: &WMRule, name: &str, class: &str, instance: &str) -> bool {
+    fn rule_matches(&self, rule: &WMRule, props: &WindowProps) -> bool {
         // 如果规则的所有字段都为空，则不匹配
-        if rule.name.is_empty() && rule.class.is_empty() && rule.instance.is_empty() {
+        if rule.name.is_any()
+            && rule.class.is_any()
+            && rule.instance.is_any()
+            && rule.role.is_any()
+            && rule.window_type.is_empty()
+        {
             return false;
         }
 
-        // 检查每个字段是否匹配（空字符串表示忽略该字段）
-        let name_matches = rule.name.is_empty() || name.contains(&rule.name);
-        let class_matches = rule.class.is_empty() || class.contains(&rule.class);
-        let instance_matches = rule.instance.is_empty() || instance.contains(&rule.instance);
+        // 检查每个字段是否匹配（空模式表示忽略该字段）
+        let type_matches = rule.window_type.is_empty()
+            || props.window_types.iter().any(|t| *t == rule.window_type);
+
+        rule.name.matches(&props.name)
+            && rule.class.matches(&props.class)
+            && rule.instance.matches(&props.instance)
+            && rule.role.matches(&props.role)
+            && type_matches
+    }
+
+    /// 收集规则匹配所需的窗口属性
+    fn window_props(&mut self, client_key: ClientKey) -> Option<WindowProps> {
+        let (win, name, mut class, mut instance) = {
+            let client = self.clients.get(client_key)?;
+            (
+                client.win,
+                client.name.clone(),
+                client.class.clone(),
+                client.instance.clone(),
+            )
+        };
+
+        // 如果类信息为空，尝试从 X11 获取
+        if class.is_empty() && instance.is_empty() {
+            if let Some((inst, cls)) = self.get_wm_class(win) {
+                instance = inst;
+                class = cls;
+
+                // 更新客户端的类信息
+                if let Some(client) = self.clients.get_mut(client_key) {
+                    client.instance = instance.clone();
+                    client.class = class.clone();
+                }
+            }
+        }
+
+        let property_ops = self.backend.property_ops();
+        let role = property_ops
+            .get_window_role(WindowId(win.into()))
+            .unwrap_or_default();
+        let mut window_types = property_ops.get_window_type_names(WindowId(win.into()));
+        // 未设置 _NET_WM_WINDOW_TYPE 的窗口按 normal 处理
+        if window_types.is_empty() {
+            window_types.push("normal".to_string());
+        }
+
+        Some(WindowProps {
+            name,
+            class,
+            instance,
+            role,
+            window_types,
+        })
+    }
 
-        name_matches && class_matches && instance_matches
+    /// 查找第一条匹配的规则，返回其下标和规则
+    fn find_rule(&self, props: &WindowProps) -> Option<(usize, WMRule)> {
+        CONFIG
+            .load()
+            .get_rules()
+            .iter()
+            .enumerate()
+            .find(|(_, rule)| self.rule_matches(rule, props))
+            .map(|(index, rule)| (index, rule.clone()))
     }
 
     /// 应用单个规则到客户端
@@ -6369,8 +8586,17 @@ impl Jwm {
         if let Some(client) = self.clients.get_mut(client_key) {
             info!("[apply_single_rule] Applying rule: {:?}", rule);
 
-            // 设置浮动状态
-            client.state.is_floating = rule.is_floating;
+            // 设置浮动状态，指定几何或居中的窗口总是浮动
+            client.state.is_floating = rule.is_floating || rule.geometry.is_some() || rule.center;
+            client.state.is_terminal = rule.is_terminal;
+            client.state.no_swallow = rule.no_swallow;
+            if rule.is_sticky {
+                client.state.is_sticky = true;
+                let _ = self
+                    .backend
+                    .property_ops()
+                    .set_sticky_state(WindowId(client.win.into()), true);
+            }
 
             // 设置标签
             if rule.tags > 0 {
@@ -6411,7 +8637,7 @@ impl Jwm {
     /// 为客户端设置默认标签
     fn set_default_tags(&mut self, client_key: ClientKey) {
         if let Some(client) = self.clients.get_mut(client_key) {
-            let condition = client.state.tags & CONFIG.tagmask();
+            let condition = client.state.tags & CONFIG.load().tagmask();
 
             if condition > 0 {
                 // 如果客户端已有有效标签，保持现有标签
@@ -6435,37 +8661,14 @@ impl Jwm {
         }
     }
 
-    /// 应用所有规则到客户端（完整版本）
-    fn applyrules_by_key(&mut self, client_key: ClientKey) {
-        let (win, name, mut class, mut instance) =
-            if let Some(client) = self.clients.get(client_key) {
-                (
-                    client.win,
-                    client.name.clone(),
-                    client.class.clone(),
-                    client.instance.clone(),
-                )
-            } else {
-                return;
-            };
-
-        // 如果类信息为空，尝试从 X11 获取
-        if class.is_empty() && instance.is_empty() {
-            if let Some((inst, cls)) = self.get_wm_class(win) {
-                instance = inst;
-                class = cls;
-
-                // 更新客户端的类信息
-                if let Some(client) = self.clients.get_mut(client_key) {
-                    client.instance = instance.clone();
-                    client.class = class.clone();
-                }
-            }
-        }
+    /// 应用所有规则到客户端（完整版本），返回命中的规则
+    fn applyrules_by_key(&mut self, client_key: ClientKey) -> Option<WMRule> {
+        let props = self.window_props(client_key)?;
+        let win = self.clients.get(client_key)?.win;
 
         info!(
-            "[applyrules_by_key] win: 0x{:x}, name: '{}', instance: '{}', class: '{}'",
-            win, name, instance, class
+            "[applyrules_by_key] win: 0x{:x}, name: '{}', instance: '{}', class: '{}', role: '{}', types: {:?}",
+            win, props.name, props.instance, props.class, props.role, props.window_types
         );
 
         // 重置浮动状态
@@ -6474,7 +8677,7 @@ impl Jwm {
         }
 
         // 特殊处理：如果所有信息都为空，设置为浮动
-        if name.is_empty() && class.is_empty() && instance.is_empty() {
+        if props.name.is_empty() && props.class.is_empty() && props.instance.is_empty() {
             if let Some(client) = self.clients.get_mut(client_key) {
                 client.state.is_floating = true;
             }
@@ -6482,13 +8685,11 @@ impl Jwm {
         }
 
         // 应用配置规则
-        let mut rule_applied = false;
-        for rule in &CONFIG.get_rules() {
-            if self.rule_matches(rule, &name, &class, &instance) {
-                self.apply_single_rule(client_key, rule);
-                rule_applied = true;
-                break;
-            }
+        let matched = self.find_rule(&props);
+        let rule_applied = matched.is_some();
+        if let Some((index, ref rule)) = matched {
+            self.apply_single_rule(client_key, rule);
+            self.client_rules.insert(client_key, index);
         }
 
         if !rule_applied {
@@ -6505,6 +8706,8 @@ impl Jwm {
                 client.class, client.instance, client.name, client.state.tags, client.state.is_floating
             );
         }
+
+        matched.map(|(_, rule)| rule)
     }
 
     fn register_client_events(
@@ -6557,41 +8760,127 @@ impl Jwm {
             .map(|w| w.0 as u32)
     }
 
-    fn manage_statusbar(
-        &mut self,
-        client_key: ClientKey,
-        win: u32,
-        current_mon_id: i32,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        // 配置状态栏客户端
-        let mon_key = self.get_monitor_by_id(current_mon_id);
-        if let Some(client) = self.clients.get_mut(client_key) {
-            client.mon = mon_key;
-            client.state.never_focus = true;
-            client.state.is_floating = true;
-            client.state.tags = CONFIG.tagmask();
-            client.geometry.border_w = CONFIG.border_px() as i32;
+    fn manage_statusbar(
+        &mut self,
+        client_key: ClientKey,
+        win: u32,
+        mon_key: MonitorKey,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // 配置状态栏客户端
+        if let Some(client) = self.clients.get_mut(client_key) {
+            client.mon = Some(mon_key);
+            client.state.never_focus = true;
+            client.state.is_floating = true;
+            client.state.tags = CONFIG.load().tagmask();
+            client.geometry.border_w = CONFIG.load().border_px() as i32;
+        }
+
+        // 调整状态栏位置（通常在顶部）
+        self.position_statusbar_on_monitor(mon_key)?;
+
+        // 设置状态栏特有的窗口属性
+        self.setup_statusbar_window_by_key(client_key)?;
+
+        // 映射状态栏窗口
+        self.backend.window_ops().map_window(WindowId(win.into()))?;
+        self.backend.window_ops().flush()?;
+        Ok(())
+    }
+
+    fn is_dock_window(&self, win: u32) -> bool {
+        self.backend
+            .property_ops()
+            .get_window_type_names(WindowId(win.into()))
+            .iter()
+            .any(|t| t == "dock")
+    }
+
+    /// dock/panel 只映射并记录 strut，不参与布局和焦点
+    fn manage_dock(&mut self, win: u32) -> Result<(), Box<dyn std::error::Error>> {
+        let wid = WindowId(win.into());
+        let strut = self.backend.property_ops().get_window_strut(wid);
+        info!("[manage_dock] Dock 0x{:x}, strut: {:?}", win, strut);
+        let mask = (EventMaskBits::STRUCTURE_NOTIFY | EventMaskBits::PROPERTY_CHANGE).bits();
+        self.backend.window_ops().change_event_mask(wid, mask)?;
+        self.backend.window_ops().map_window(wid)?;
+        self.backend.window_ops().configure_stack_above(wid, None)?;
+        self.docks.insert(win, strut.unwrap_or_default());
+        if self.update_workareas() {
+            self.arrange(None);
+        }
+        self.backend.window_ops().flush()?;
+        Ok(())
+    }
+
+    fn update_dock_strut(&mut self, win: u32) -> Result<(), Box<dyn std::error::Error>> {
+        let strut = self
+            .backend
+            .property_ops()
+            .get_window_strut(WindowId(win.into()))
+            .unwrap_or_default();
+        if self.docks.insert(win, strut) != Some(strut) && self.update_workareas() {
+            info!("[update_dock_strut] Dock 0x{:x} strut: {:?}", win, strut);
+            self.arrange(None);
+        }
+        Ok(())
+    }
+
+    fn remove_dock(&mut self, win: u32) -> Result<(), Box<dyn std::error::Error>> {
+        info!("[remove_dock] Dock 0x{:x} gone", win);
+        self.docks.remove(&win);
+        if self.update_workareas() {
+            self.arrange(None);
         }
+        Ok(())
+    }
 
-        // 调整状态栏位置（通常在顶部）
-        self.position_statusbar_on_monitor(current_mon_id)?;
-
-        // 设置状态栏特有的窗口属性
-        self.setup_statusbar_window_by_key(client_key)?;
+    /// 工作区 = 显示器区域减去与之相交的 dock strut；返回是否有变化
+    fn update_workareas(&mut self) -> bool {
+        // strut 的范围是闭区间，判断是否与 [pos, pos + len) 相交
+        fn overlaps(start: u32, end: u32, pos: i32, len: i32) -> bool {
+            i64::from(start) < i64::from(pos) + i64::from(len) && i64::from(end) >= i64::from(pos)
+        }
 
-        // 映射状态栏窗口
-        self.backend.window_ops().map_window(WindowId(win.into()))?;
-        self.backend.window_ops().flush()?;
-        Ok(())
+        let (s_w, s_h) = (self.s_w, self.s_h);
+        let mut changed = false;
+        for monitor in self.monitors.values_mut() {
+            let g = &mut monitor.geometry;
+            let (mut left, mut top) = (g.m_x, g.m_y);
+            let (mut right, mut bottom) = (g.m_x + g.m_w, g.m_y + g.m_h);
+            for s in self.docks.values() {
+                if s.left > 0 && overlaps(s.left_start_y, s.left_end_y, g.m_y, g.m_h) {
+                    left = left.max(s.left as i32);
+                }
+                if s.right > 0 && overlaps(s.right_start_y, s.right_end_y, g.m_y, g.m_h) {
+                    right = right.min(s_w - s.right as i32);
+                }
+                if s.top > 0 && overlaps(s.top_start_x, s.top_end_x, g.m_x, g.m_w) {
+                    top = top.max(s.top as i32);
+                }
+                if s.bottom > 0 && overlaps(s.bottom_start_x, s.bottom_end_x, g.m_x, g.m_w) {
+                    bottom = bottom.min(s_h - s.bottom as i32);
+                }
+            }
+            // 异常 strut 占满整个显示器时忽略
+            if right <= left || bottom <= top {
+                (left, top, right, bottom) = (g.m_x, g.m_y, g.m_x + g.m_w, g.m_y + g.m_h);
+            }
+            let area = (left, top, right - left, bottom - top);
+            if area != (g.w_x, g.w_y, g.w_w, g.w_h) {
+                (g.w_x, g.w_y, g.w_w, g.w_h) = area;
+                changed = true;
+            }
+        }
+        changed
     }
 
     fn set_bar_strut(
         &self,
         bar_win: u32,
         mon: &WMMonitor,
-        bar_height: i32,
+        bar_bottom: i32,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let top_amount = bar_height.max(0) as u32;
+        let top_amount = bar_bottom.max(0) as u32;
         let top_start_x = mon.geometry.m_x.max(0) as u32;
         let top_end_x = (mon.geometry.m_x + mon.geometry.m_w - 1).max(0) as u32;
         self.backend.property_ops().set_window_strut_top(
@@ -6608,19 +8897,142 @@ impl Jwm {
             .clear_window_strut(WindowId(bar_win.into()))
     }
 
+    // 状态栏窗口所属的显示器
+    fn bar_monitor_of_window(&self, win: u32) -> Option<MonitorKey> {
+        self.status_bars
+            .iter()
+            .find(|(_, bar)| bar.window == Some(win))
+            .map(|(key, _)| key)
+    }
+
+    fn is_status_bar_window(&self, win: u32) -> bool {
+        self.bar_monitor_of_window(win).is_some()
+    }
+
+    // 单实例用 SHARED_PATH；每屏模式追加显示器编号，编号已被占用（重新编号后）时顺延
+    fn bar_shared_path(&self, monitor_num: i32) -> String {
+        if !CONFIG.load().status_bar_per_monitor() {
+            return SHARED_PATH.to_string();
+        }
+        let mut id = monitor_num;
+        loop {
+            let path = format!("{}_{}", SHARED_PATH, id);
+            if !self.status_bars.values().any(|bar| bar.shared_path == path) {
+                return path;
+            }
+            id += 1;
+        }
+    }
+
+    fn ensure_status_bar_entry(&mut self, mon_key: MonitorKey) {
+        if self.status_bars.contains_key(mon_key) {
+            return;
+        }
+        let monitor_num = self.monitors.get(mon_key).map_or(0, |m| m.num);
+        let shared_path = self.bar_shared_path(monitor_num);
+        self.status_bars
+            .insert(mon_key, StatusBar::new(shared_path));
+    }
+
+    // 新出现的状态栏窗口：按 _NET_WM_PID 找到启动它的显示器，否则取尚无窗口的状态栏或选中显示器
+    fn claim_status_bar(
+        &mut self,
+        pid: u32,
+        client_key: ClientKey,
+        win: u32,
+    ) -> Option<MonitorKey> {
+        let mon_key = if CONFIG.load().status_bar_per_monitor() {
+            let by_pid = self
+                .status_bars
+                .iter()
+                .find(|(_, bar)| pid != 0 && bar.child.as_ref().map(Child::id) == Some(pid))
+                .map(|(key, _)| key);
+            let unclaimed = self
+                .status_bars
+                .iter()
+                .find(|(key, bar)| bar.window.is_none() && self.monitors.contains_key(*key))
+                .map(|(key, _)| key);
+            by_pid.or(unclaimed).or(self.sel_mon)?
+        } else {
+            self.status_bars.keys().next().or(self.sel_mon)?
+        };
+        self.ensure_status_bar_entry(mon_key);
+        let bar = self.status_bars.get_mut(mon_key)?;
+        bar.client = Some(client_key);
+        bar.window = Some(win);
+        Some(mon_key)
+    }
+
+    // 单实例模式：状态栏移到目标显示器，返回是否发生移动
+    fn move_status_bar(&mut self, target: MonitorKey) -> Result<bool, Box<dyn std::error::Error>> {
+        if CONFIG.load().status_bar_per_monitor() {
+            return Ok(false);
+        }
+        let current = match self.status_bars.keys().next() {
+            Some(k) => k,
+            None => return Ok(false),
+        };
+        if current == target {
+            return Ok(false);
+        }
+        if let Some(bar) = self.status_bars.remove(current) {
+            if let Some(client) = bar.client.and_then(|ck| self.clients.get_mut(ck)) {
+                client.mon = Some(target);
+            }
+            self.status_bars.insert(target, bar);
+        }
+        self.position_statusbar_on_monitor(target)?;
+        // 旧屏与新屏都 arrange 一次，更新偏移
+        if self.monitors.contains_key(current) {
+            self.arrange(Some(current));
+        }
+        self.arrange(Some(target));
+        Ok(true)
+    }
+
+    // 显示器变化后：单实例状态栏移到仍存在的显示器，每屏模式下关闭已消失显示器的状态栏
+    fn sync_status_bars_with_monitors(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let stale: Vec<MonitorKey> = self
+            .status_bars
+            .keys()
+            .filter(|&k| !self.monitors.contains_key(k))
+            .collect();
+        if CONFIG.load().status_bar_per_monitor() {
+            for mon_key in stale {
+                info!(
+                    "[sync_status_bars_with_monitors] Removing bar of {:?}",
+                    mon_key
+                );
+                self.remove_status_bar(mon_key);
+            }
+        } else if !stale.is_empty() {
+            let fallback = self
+                .sel_mon
+                .filter(|&k| self.monitors.contains_key(k))
+                .or_else(|| self.monitor_order.first().copied());
+            if let Some(target) = fallback {
+                self.move_status_bar(target)?;
+            }
+        }
+        let bar_monitors: Vec<MonitorKey> = self.status_bars.keys().collect();
+        for mon_key in bar_monitors {
+            self.position_statusbar_on_monitor(mon_key)?;
+        }
+        Ok(())
+    }
+
     fn position_statusbar_on_monitor(
         &mut self,
-        monitor_id: i32,
+        mon_key: MonitorKey,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let client_key = match self.status_bar_client {
+        let client_key = match self.status_bars.get(mon_key).and_then(|bar| bar.client) {
             Some(k) => k,
             None => return Ok(()),
         };
-        let mon_key = match self.get_monitor_by_id(monitor_id) {
-            Some(k) => k,
+        let monitor = match self.monitors.get(mon_key) {
+            Some(m) => m,
             None => return Ok(()),
         };
-        let monitor = self.monitors.get(mon_key).unwrap();
 
         let show_bar = monitor
             .pertag
@@ -6631,11 +9043,12 @@ impl Jwm {
 
         let (client_win, client_height) = if let Some(client) = self.clients.get_mut(client_key) {
             if show_bar {
-                let pad = CONFIG.status_bar_padding();
-                client.geometry.x = monitor.geometry.m_x + pad;
-                client.geometry.y = monitor.geometry.m_y + pad;
-                client.geometry.w = monitor.geometry.m_w - 2 * pad;
-                client.geometry.h = CONFIG.status_bar_height();
+                // 放在工作区顶部，避开第三方 dock
+                let pad = CONFIG.load().status_bar_padding();
+                client.geometry.x = monitor.geometry.w_x + pad;
+                client.geometry.y = monitor.geometry.w_y + pad;
+                client.geometry.w = monitor.geometry.w_w - 2 * pad;
+                client.geometry.h = CONFIG.load().status_bar_height();
 
                 self.backend.window_ops().configure_xywh_border(
                     WindowId(client.win.into()),
@@ -6645,7 +9058,7 @@ impl Jwm {
                     Some(client.geometry.h as u32),
                     None,
                 )?;
-                (client.win, Some(client.geometry.h))
+                (client.win, Some(client.geometry.y + client.geometry.h))
             } else {
                 self.backend.window_ops().configure_xywh_border(
                     WindowId(client.win.into()),
@@ -6662,8 +9075,8 @@ impl Jwm {
             return Ok(());
         };
 
-        if let Some(height) = client_height {
-            self.set_bar_strut(client_win, monitor, height)?;
+        if let Some(bar_bottom) = client_height {
+            self.set_bar_strut(client_win, monitor, bar_bottom)?;
         } else {
             self.remove_bar_strut(client_win)?;
         }
@@ -6733,82 +9146,6 @@ impl Jwm {
         Ok(())
     }
 
-    fn monocle(&mut self, mon_key: MonitorKey) {
-        info!("[monocle]");
-
-        // 获取监视器信息
-        let (wx, wy, ww, wh, monitor_num) = if let Some(monitor) = self.monitors.get(mon_key) {
-            (
-                monitor.geometry.w_x,
-                monitor.geometry.w_y,
-                monitor.geometry.w_w,
-                monitor.geometry.w_h,
-                monitor.num,
-            )
-        } else {
-            warn!("[monocle] Monitor {:?} not found", mon_key);
-            return;
-        };
-
-        // 统计可见客户端数量并收集平铺客户端
-        let mut visible_count = 0u32;
-        let mut tiled_clients = Vec::new();
-
-        // 获取监视器的客户端列表
-        if let Some(client_keys) = self.monitor_clients.get(mon_key) {
-            for &client_key in client_keys {
-                if let Some(client) = self.clients.get(client_key) {
-                    let is_visible = self.is_client_visible_on_monitor(client_key, mon_key);
-
-                    if is_visible {
-                        visible_count += 1;
-                        // 收集平铺客户端（可见且非浮动）
-                        if !client.state.is_floating {
-                            tiled_clients.push((client_key, client.geometry.border_w));
-                        }
-                    }
-                }
-            }
-        }
-
-        // 更新布局符号
-        if visible_count > 0 {
-            let formatted_string = format!("[{}]", visible_count);
-            if let Some(monitor) = self.monitors.get_mut(mon_key) {
-                monitor.lt_symbol = formatted_string.clone();
-            }
-            info!(
-                "[monocle] formatted_string: {}, monitor_num: {}",
-                formatted_string, monitor_num
-            );
-        }
-
-        // 如果没有平铺客户端，直接返回
-        if tiled_clients.is_empty() {
-            return;
-        }
-
-        // 获取Y轴偏移
-        let client_y_offset = if let Some(monitor) = self.monitors.get(mon_key) {
-            self.get_client_y_offset(monitor)
-        } else {
-            0
-        };
-        info!("[monocle] client_y_offset: {}", client_y_offset);
-
-        // 调整所有平铺客户端为全屏大小
-        for (client_key, border_w) in tiled_clients {
-            self.resize_client(
-                client_key,
-                wx,
-                wy + client_y_offset,
-                ww - 2 * border_w,
-                wh - 2 * border_w - client_y_offset,
-                false,
-            );
-        }
-    }
-
     fn handle_monitor_switch_by_key(
         &mut self,
         new_monitor_key: Option<MonitorKey>,
@@ -6853,8 +9190,8 @@ impl Jwm {
         };
 
         // 检查是否是状态栏
-        if Some(win) == self.status_bar_window {
-            self.unmanage_statusbar(destroyed)?;
+        if let Some(bar_mon_key) = self.bar_monitor_of_window(win) {
+            self.unmanage_statusbar(bar_mon_key, win, destroyed)?;
             return Ok(());
         }
 
@@ -6863,21 +9200,51 @@ impl Jwm {
         Ok(())
     }
 
-    fn unmanage_statusbar(&mut self, destroyed: bool) -> Result<(), Box<dyn std::error::Error>> {
+    fn unmanage_statusbar(
+        &mut self,
+        mon_key: MonitorKey,
+        win: u32,
+        destroyed: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
         if !destroyed {
-            self.cleanup_statusbar_window(self.status_bar_window.unwrap())?;
+            self.cleanup_statusbar_window(win)?;
         }
+        self.remove_status_bar(mon_key);
+        info!(
+            "[unmanage_statusbar] Successfully removed statusbar 0x{:x}",
+            win
+        );
+        Ok(())
+    }
+
+    // 终止状态栏进程、释放共享内存，并移除其客户端
+    fn remove_status_bar(&mut self, mon_key: MonitorKey) {
+        let mut bar = match self.status_bars.remove(mon_key) {
+            Some(bar) => bar,
+            None => return,
+        };
         let cleanup_results = [
-            ("terminate_process", self.cleanup_statusbar_processes()),
-            ("cleanup_shared_memory", self.cleanup_shared_memory_safe()),
+            (
+                "terminate_process",
+                match bar.child.take() {
+                    Some(child) => Self::terminate_bar_process(child),
+                    None => Ok(()),
+                },
+            ),
+            (
+                "cleanup_shared_memory",
+                Self::cleanup_shared_memory_safe(&mut bar),
+            ),
         ];
         for (operation, result) in cleanup_results.iter() {
             if let Err(ref e) = result {
-                error!("[unmanage_statusbar] {} failed for {}", operation, e);
+                error!("[remove_status_bar] {} failed for {}", operation, e);
             }
         }
-        info!("[unmanage_statusbar] Successfully removed statusbar",);
-        Ok(())
+        if let Some(client_key) = bar.client {
+            self.clients.remove(client_key);
+            self.client_order.retain(|&k| k != client_key);
+        }
     }
 
     fn cleanup_statusbar_window(&mut self, win: u32) -> Result<(), Box<dyn std::error::Error>> {
@@ -6893,13 +9260,13 @@ impl Jwm {
     }
 
     /// 安全的共享内存清理方法
-    fn cleanup_shared_memory_safe(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(shmem) = self.status_bar_shmem.take() {
+    fn cleanup_shared_memory_safe(bar: &mut StatusBar) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(shmem) = bar.shmem.take() {
             info!("[cleanup_shared_memory_safe] Cleaning up shared memory",);
             drop(shmem);
             #[cfg(unix)]
             {
-                if let Ok(c_name) = std::ffi::CString::new(SHARED_PATH) {
+                if let Ok(c_name) = std::ffi::CString::new(bar.shared_path.as_str()) {
                     unsafe {
                         let result = libc::shm_unlink(c_name.as_ptr());
                         if result != 0 {
@@ -7046,8 +9413,8 @@ impl Jwm {
             0
         };
 
-        if client_y < client_y_offset {
-            client_y = client_y_offset;
+        if client_y < mon_wy + client_y_offset {
+            client_y = mon_wy + client_y_offset;
             info!(
                 "[adjust_client_position] Adjusted Y to avoid status bar: {}",
                 client_y
@@ -7082,6 +9449,23 @@ impl Jwm {
         destroyed: bool,
     ) -> Result<(), Box<dyn std::error::Error>> {
         info!("[unmanage_regular_client] Removing client {:?}", client_key);
+        if let Some(c) = self.clients.get(client_key) {
+            let event = IpcEvent::ClientUnmanaged {
+                window: c.win,
+                class: c.class.clone(),
+            };
+            self.emit_ipc_event(event);
+        }
+
+        // 吞噬了终端的窗口退出时先让终端接替其位置
+        if let Some(term_key) = self.swallowed.remove(&client_key) {
+            self.unswallow(client_key, term_key);
+        }
+        // 被隐藏的终端自身退出
+        self.swallowed
+            .retain(|_, &mut term_key| term_key != client_key);
+        self.client_rules.remove(client_key);
+        self.maximize_restore.remove(client_key);
 
         // 获取客户端的监视器信息
         let mon_key = self.clients.get(client_key).and_then(|client| client.mon);
@@ -7102,6 +9486,7 @@ impl Jwm {
 
         // 从 SlotMap 中移除客户端
         self.clients.remove(client_key);
+        self.scratchpads.retain(|_, &mut k| k != client_key);
 
         // 从顺序列表中移除
         self.client_order.retain(|&k| k != client_key);
@@ -7120,7 +9505,7 @@ impl Jwm {
     fn clear_pertag_references(&mut self, client_key: ClientKey, mon_key: MonitorKey) {
         if let Some(monitor) = self.monitors.get_mut(mon_key) {
             if let Some(ref mut pertag) = monitor.pertag {
-                for i in 0..=CONFIG.tags_length() {
+                for i in 0..=CONFIG.load().tags_length() {
                     if pertag.sel[i] == Some(client_key) {
                         pertag.sel[i] = None;
                     }
@@ -7209,6 +9594,9 @@ impl Jwm {
         from_configure: bool,
     ) -> Result<(), Box<dyn std::error::Error>> {
         // info!("[unmapnotify]");
+        if !from_configure && self.docks.contains_key(&window) {
+            return self.remove_dock(window);
+        }
         if let Some(client_key) = self.wintoclient(window) {
             if from_configure {
                 // 这是由于配置请求导致的unmap（通常是合成窗口管理器）
@@ -7232,133 +9620,260 @@ impl Jwm {
 
     fn updategeom(&mut self) -> bool {
         info!("[updategeom]");
-        let outputs = self.backend.output_ops().enumerate_outputs();
-
-        let dirty = if outputs.len() <= 1 {
-            self.setup_single_monitor()
-        } else {
-            // 把 outputs 转换为 (x,y,w,h)
-            let mons: Vec<(i32, i32, i32, i32)> = outputs
-                .iter()
-                .map(|o| (o.x, o.y, o.width, o.height))
-                .collect();
-            self.setup_multiple_monitors(mons)
-        };
+        let mut outputs = self.backend.output_ops().enumerate_outputs();
+        let nums_before = self.monitor_nums();
+
+        // 单显示器时以根窗口尺寸为准
+        if outputs.len() <= 1 {
+            let (name, edid) = outputs
+                .first()
+                .map(|o| (o.name.clone(), o.edid))
+                .unwrap_or_default();
+            outputs = vec![OutputInfo {
+                id: 0,
+                x: 0,
+                y: 0,
+                width: self.s_w,
+                height: self.s_h,
+                name,
+                edid,
+            }];
+        }
+        let dirty = self.sync_monitors_with_outputs(&outputs);
+        let workarea_changed = self.update_workareas();
+        let dirty = dirty || workarea_changed;
 
         if dirty {
             self.sel_mon = self.wintomon(self.backend.root_window().0 as u32);
             if self.sel_mon.is_none() && !self.monitor_order.is_empty() {
                 self.sel_mon = self.monitor_order.first().copied();
             }
+            self.emit_monitor_events(&nums_before);
         }
         dirty
     }
 
-    fn setup_single_monitor(&mut self) -> bool {
-        let mut dirty = false;
+    fn monitor_nums(&self) -> Vec<i32> {
+        self.monitor_order
+            .iter()
+            .filter_map(|&k| self.monitors.get(k))
+            .map(|m| m.num)
+            .collect()
+    }
 
-        if self.monitor_order.is_empty() {
-            let new_monitor = self.createmon(CONFIG.show_bar());
-            let mon_key = self.insert_monitor(new_monitor);
-            self.sel_mon = Some(mon_key);
-            dirty = true;
+    fn emit_monitor_events(&mut self, nums_before: &[i32]) {
+        let mut events = Vec::new();
+        let nums_after = self.monitor_nums();
+        for num in nums_before {
+            if !nums_after.contains(num) {
+                self.ipc_view_state.remove(num);
+                events.push(IpcEvent::MonitorRemoved { monitor: *num });
+            }
         }
-
-        if let Some(&mon_key) = self.monitor_order.first() {
-            if let Some(monitor) = self.monitors.get_mut(mon_key) {
-                if monitor.geometry.m_w != self.s_w || monitor.geometry.m_h != self.s_h {
-                    dirty = true;
-                    monitor.num = 0;
-                    monitor.geometry.m_x = 0;
-                    monitor.geometry.w_x = 0;
-                    monitor.geometry.m_y = 0;
-                    monitor.geometry.w_y = 0;
-                    monitor.geometry.m_w = self.s_w;
-                    monitor.geometry.w_w = self.s_w;
-                    monitor.geometry.m_h = self.s_h;
-                    monitor.geometry.w_h = self.s_h;
+        for &mon_key in &self.monitor_order {
+            if let Some(m) = self.monitors.get(mon_key) {
+                if !nums_before.contains(&m.num) {
+                    let g = &m.geometry;
+                    events.push(IpcEvent::MonitorAdded {
+                        monitor: m.num,
+                        geometry: ipc::Rect {
+                            x: g.m_x,
+                            y: g.m_y,
+                            width: g.m_w,
+                            height: g.m_h,
+                        },
+                    });
                 }
             }
         }
-
-        dirty
+        for event in events {
+            self.emit_ipc_event(event);
+        }
     }
 
-    fn setup_multiple_monitors(&mut self, monitors: Vec<(i32, i32, i32, i32)>) -> bool {
+    /// 按输出身份对齐显示器：保留仍在的、恢复重新接入的、暂存被拔掉的，返回是否有变化
+    fn sync_monitors_with_outputs(&mut self, outputs: &[OutputInfo]) -> bool {
         let mut dirty = false;
-        let num_detected_monitors = monitors.len();
-        let current_num_monitors = self.monitor_order.len();
-
-        // 如果检测到的显示器数量多于当前管理的数量，创建新的显示器
-        if num_detected_monitors > current_num_monitors {
+        let identities: Vec<OutputIdentity> = outputs.iter().map(OutputIdentity::of).collect();
+
+        // 1) 新出现的输出：曾经拔掉过的恢复原状态，否则新建
+        let mut restored = Vec::new();
+        for (index, (output, identity)) in outputs.iter().zip(&identities).enumerate() {
+            let exists = self.monitor_order.iter().any(|&k| {
+                self.monitors
+                    .get(k)
+                    .map_or(false, |m| &m.output == identity)
+            });
+            if exists {
+                continue;
+            }
             dirty = true;
-            for _ in current_num_monitors..num_detected_monitors {
-                let new_monitor = self.createmon(CONFIG.show_bar());
-                let mon_key = self.insert_monitor(new_monitor);
-                info!(
-                    "[setup_multiple_monitors] Created new monitor {:?}",
-                    mon_key
-                );
+            let detached = self
+                .detached_monitors
+                .remove(identity)
+                .filter(|d| d.is_compatible(CONFIG.load().tags_length()));
+            let (mut monitor, clients) = match detached {
+                Some(d) => {
+                    info!(
+                        "[sync_monitors_with_outputs] Restoring monitor {}",
+                        identity
+                    );
+                    (d.monitor, d.clients)
+                }
+                None => (self.createmon(&identity.name, index), Vec::new()),
+            };
+            monitor.sel = None;
+            monitor.output = identity.clone();
+            let mon_key = self.insert_monitor(monitor);
+            restored.push((mon_key, clients));
+            info!(
+                "[sync_monitors_with_outputs] Output {} attached as {:?} at {}x{}+{}+{}",
+                identity, mon_key, output.width, output.height, output.x, output.y
+            );
+        }
+
+        // 2) 更新几何信息
+        for (output, identity) in outputs.iter().zip(&identities) {
+            let mon_key = self.monitor_order.iter().copied().find(|&k| {
+                self.monitors
+                    .get(k)
+                    .map_or(false, |m| &m.output == identity)
+            });
+            if let Some(monitor) = mon_key.and_then(|k| self.monitors.get_mut(k)) {
+                let g = &mut monitor.geometry;
+                let rect = (output.x, output.y, output.width, output.height);
+                if (g.m_x, g.m_y, g.m_w, g.m_h) != rect {
+                    dirty = true;
+                    (g.m_x, g.m_y, g.m_w, g.m_h) = rect;
+                    (g.w_x, g.w_y, g.w_w, g.w_h) = rect;
+                }
             }
         }
 
-        // 更新现有显示器的几何信息
-        for (i, &(x, y, w, h)) in monitors.iter().enumerate() {
-            if let Some(&mon_key) = self.monitor_order.get(i) {
-                if let Some(monitor) = self.monitors.get_mut(mon_key) {
-                    // 检查几何信息是否需要更新
-                    if monitor.geometry.m_x != x
-                        || monitor.geometry.m_y != y
-                        || monitor.geometry.m_w != w
-                        || monitor.geometry.m_h != h
-                    {
-                        dirty = true;
-                        monitor.num = i as i32;
-                        monitor.geometry.m_x = x;
-                        monitor.geometry.w_x = x;
-                        monitor.geometry.m_y = y;
-                        monitor.geometry.w_y = y;
-                        monitor.geometry.m_w = w;
-                        monitor.geometry.w_w = w;
-                        monitor.geometry.m_h = h;
-                        monitor.geometry.w_h = h;
-                    }
+        // 3) 按输出顺序排列并重新编号，已消失的排在最后
+        let position = |m: Option<&WMMonitor>| {
+            m.and_then(|m| identities.iter().position(|id| id == &m.output))
+                .unwrap_or(usize::MAX)
+        };
+        let mut order = self.monitor_order.clone();
+        order.sort_by_key(|&k| position(self.monitors.get(k)));
+        let gone: Vec<MonitorKey> = order
+            .iter()
+            .copied()
+            .filter(|&k| position(self.monitors.get(k)) == usize::MAX)
+            .collect();
+        order.retain(|k| !gone.contains(k));
+        if order != self.monitor_order[..self.monitor_order.len() - gone.len()] {
+            dirty = true;
+        }
+        self.monitor_order = order;
+        for (i, &mon_key) in self.monitor_order.iter().enumerate() {
+            if let Some(monitor) = self.monitors.get_mut(mon_key) {
+                if monitor.num != i as i32 {
+                    monitor.num = i as i32;
+                    dirty = true;
                 }
             }
         }
 
-        // 如果当前显示器数量多于检测到的数量，移除多余的显示器
-        if num_detected_monitors < current_num_monitors {
+        // 4) 暂存被拔掉的显示器，客户端先移到第一个显示器
+        for mon_key in gone {
             dirty = true;
-            self.remove_excess_monitors(num_detected_monitors);
+            self.detach_monitor(mon_key);
+        }
+
+        // 5) 把重新接入的显示器原有的客户端移回去
+        for (mon_key, clients) in restored {
+            self.restore_monitor_clients(mon_key, clients);
         }
 
         dirty
     }
 
-    fn remove_excess_monitors(&mut self, target_count: usize) {
-        // 从后往前移除多余的显示器
-        while self.monitor_order.len() > target_count {
-            if let Some(mon_key_to_remove) = self.monitor_order.pop() {
-                // 将该显示器上的客户端移动到第一个显示器
-                self.move_clients_to_first_monitor(mon_key_to_remove);
+    fn detach_monitor(&mut self, mon_key: MonitorKey) {
+        let monitor = match self.monitors.get(mon_key) {
+            Some(m) => m.clone(),
+            None => return,
+        };
+        let clients: Vec<(ClientKey, u32, i32, i32)> = self
+            .get_monitor_clients(mon_key)
+            .iter()
+            .filter_map(|&ck| {
+                let c = self.clients.get(ck)?;
+                Some((
+                    ck,
+                    c.state.tags,
+                    c.geometry.x - monitor.geometry.m_x,
+                    c.geometry.y - monitor.geometry.m_y,
+                ))
+            })
+            .collect();
+        info!(
+            "[detach_monitor] Output {} removed, remembering {} client(s)",
+            monitor.output,
+            clients.len()
+        );
 
-                // 如果被移除的是当前选中的显示器，切换到第一个
-                if self.sel_mon == Some(mon_key_to_remove) {
-                    self.sel_mon = self.monitor_order.first().copied();
-                }
+        // 将该显示器上的客户端移动到第一个显示器
+        self.move_clients_to_first_monitor(mon_key);
 
-                // 从所有相关数据结构中移除
-                self.monitors.remove(mon_key_to_remove);
-                self.monitor_clients.remove(mon_key_to_remove);
-                self.monitor_stack.remove(mon_key_to_remove);
+        // 如果被移除的是当前选中的显示器，切换到第一个
+        if self.sel_mon == Some(mon_key) {
+            self.sel_mon = self.monitor_order.first().copied();
+        }
 
-                info!(
-                    "[remove_excess_monitors] Removed monitor {:?}",
-                    mon_key_to_remove
-                );
+        // 从所有相关数据结构中移除
+        self.monitors.remove(mon_key);
+        self.monitor_clients.remove(mon_key);
+        self.monitor_stack.remove(mon_key);
+        self.last_stacking.remove(mon_key);
+
+        // 未识别身份的输出无法在重新接入时匹配，不必保存
+        if !monitor.output.is_unknown() {
+            self.detached_monitors
+                .insert(monitor.output.clone(), DetachedMonitor { monitor, clients });
+        }
+    }
+
+    fn restore_monitor_clients(
+        &mut self,
+        mon_key: MonitorKey,
+        clients: Vec<(ClientKey, u32, i32, i32)>,
+    ) {
+        let (m_x, m_y) = match self.monitors.get(mon_key) {
+            Some(m) => (m.geometry.m_x, m.geometry.m_y),
+            None => return,
+        };
+        // 逆序插入以保持原来的顺序
+        for &(client_key, tags, rel_x, rel_y) in clients.iter().rev() {
+            let from_mon = match self.clients.get(client_key) {
+                Some(c) => c.mon,
+                None => continue,
+            };
+            if let Some(from_mon) = from_mon {
+                self.detach_from_monitor(client_key, from_mon);
+                if let Some(m) = self.monitors.get_mut(from_mon) {
+                    if m.sel == Some(client_key) {
+                        m.sel = None;
+                    }
+                }
             }
+            if let Some(client) = self.clients.get_mut(client_key) {
+                client.mon = Some(mon_key);
+                client.state.tags = tags;
+                if client.state.is_floating && !client.state.is_fullscreen {
+                    client.geometry.x = m_x + rel_x;
+                    client.geometry.y = m_y + rel_y;
+                }
+            }
+            self.attach_to_monitor(client_key, mon_key);
+            let _ = self.setclienttagprop(client_key);
         }
+        info!(
+            "[restore_monitor_clients] Restored {} client(s) to {:?}",
+            clients.len(),
+            mon_key
+        );
     }
 
     fn move_clients_to_first_monitor(&mut self, from_monitor_key: MonitorKey) {
@@ -7383,33 +9898,100 @@ impl Jwm {
             1 // 默认标签
         };
 
-        // 移动所有客户端
-        for client_key in clients_to_move {
-            // 更新客户端的监视器和标签
-            if let Some(client) = self.clients.get_mut(client_key) {
-                client.mon = Some(target_monitor_key);
-                client.state.tags = target_tags;
-            }
+        // 隐藏的 scratchpad 保持隐藏
+        self.move_clients_between_monitors(
+            &clients_to_move,
+            from_monitor_key,
+            target_monitor_key,
+            Some(target_tags),
+        );
+        info!(
+            "[move_clients_to_first_monitor] Moved {} client(s) from monitor {:?} to {:?}",
+            clients_to_move.len(),
+            from_monitor_key,
+            target_monitor_key
+        );
+    }
+
+    /// 把一组客户端按原顺序移到另一显示器；tags 为 Some 时改为该标签集（tags 为 0 的保持隐藏）
+    fn move_clients_between_monitors(
+        &mut self,
+        client_keys: &[ClientKey],
+        from_monitor_key: MonitorKey,
+        target_monitor_key: MonitorKey,
+        tags: Option<u32>,
+    ) {
+        // 浮动窗口保持相对原显示器的偏移，并限制在目标工作区内
+        let from_origin = self
+            .monitors
+            .get(from_monitor_key)
+            .map(|m| (m.geometry.m_x, m.geometry.m_y));
+        let target_area = self.monitors.get(target_monitor_key).map(|m| {
+            (
+                m.geometry.w_x,
+                m.geometry.w_y,
+                m.geometry.w_w,
+                m.geometry.w_h,
+            )
+        });
 
-            // 从原监视器移除
+        for &client_key in client_keys {
+            // 从原监视器移除，并清掉原监视器 pertag 中的引用
+            self.clear_pertag_references(client_key, from_monitor_key);
             self.detach_from_monitor(client_key, from_monitor_key);
+            if let Some(m) = self.monitors.get_mut(from_monitor_key) {
+                if m.sel == Some(client_key) {
+                    m.sel = None;
+                }
+            }
 
-            // 添加到目标监视器
-            self.attach_to_monitor(client_key, target_monitor_key);
+            if let Some(client) = self.clients.get_mut(client_key) {
+                if let Some(tags) = tags {
+                    if client.state.tags != 0 {
+                        client.state.tags = tags;
+                    }
+                }
+                if let (true, false, Some((fx, fy)), Some((wx, wy, ww, wh))) = (
+                    client.state.is_floating,
+                    client.state.is_fullscreen,
+                    from_origin,
+                    target_area,
+                ) {
+                    let max_x = (wx + ww - client.total_width()).max(wx);
+                    let max_y = (wy + wh - client.total_height()).max(wy);
+                    client.geometry.x = (wx + client.geometry.x - fx).clamp(wx, max_x);
+                    client.geometry.y = (wy + client.geometry.y - fy).clamp(wy, max_y);
+                }
+            }
 
-            info!(
-                "[move_clients_to_first_monitor] Moved client {:?} from monitor {:?} to {:?}",
-                client_key, from_monitor_key, target_monitor_key
-            );
+            // 添加到目标监视器
+            self.attach_to_monitor_end(client_key, target_monitor_key);
+            self.attach_to_monitor_stack_end(client_key, target_monitor_key);
+            let _ = self.setclienttagprop(client_key);
         }
     }
 
     fn updatewindowtype(&mut self, client_key: ClientKey) {
         if let Some(client) = self.clients.get(client_key) {
             let win_id = WindowId(client.win.into());
-            if let Ok(true) = self.backend.property_ops().is_fullscreen(win_id) {
+            let states = self.backend.property_ops().get_net_wm_states(win_id);
+            if states.contains(&NetWmState::Fullscreen) {
                 let _ = self.setfullscreen(client_key, true);
             }
+            if let Some(c) = self.clients.get_mut(client_key) {
+                // hidden 不保留：客户端映射窗口即表示希望显示
+                for state in states {
+                    match state {
+                        NetWmState::Sticky => c.state.is_sticky = true,
+                        NetWmState::Above => c.state.is_above = !c.state.is_below,
+                        NetWmState::Below => c.state.is_below = !c.state.is_above,
+                        NetWmState::MaximizedVert => c.state.is_maximized_vert = true,
+                        NetWmState::MaximizedHorz => c.state.is_maximized_horz = true,
+                        NetWmState::SkipTaskbar => c.state.skip_taskbar = true,
+                        _ => {}
+                    }
+                }
+            }
             if self.backend.property_ops().is_popup_type(win_id) {
                 if let Some(c) = self.clients.get_mut(client_key) {
                     c.state.is_floating = true;
@@ -7424,6 +10006,7 @@ impl Jwm {
             None => return,
         };
         let wid = WindowId(win.into());
+        let was_urgent = self.clients[client_key].state.is_urgent;
         if let Some(hints) = self.backend.property_ops().get_wm_hints(wid) {
             // 处理紧急状态
             if hints.urgent {
@@ -7431,9 +10014,14 @@ impl Jwm {
                 if is_focused {
                     let _ = self.backend.property_ops().set_urgent_hint(wid, false);
                 } else {
+                    let mut newly_urgent = false;
                     if let Some(c) = self.clients.get_mut(client_key) {
+                        newly_urgent = !c.state.is_urgent;
                         c.state.is_urgent = true;
                     }
+                    if newly_urgent {
+                        self.emit_urgent_event(client_key);
+                    }
                 }
             } else {
                 if let Some(c) = self.clients.get_mut(client_key) {
@@ -7451,6 +10039,10 @@ impl Jwm {
                 }
             }
         }
+        // 紧急状态同步到 _NET_WM_STATE_DEMANDS_ATTENTION
+        if self.clients.get(client_key).map(|c| c.state.is_urgent) != Some(was_urgent) {
+            let _ = self.sync_net_wm_state(client_key);
+        }
     }
 
     fn update_bar_message_for_monitor(&mut self, mon_key_opt: Option<MonitorKey>) {
@@ -7485,12 +10077,17 @@ impl Jwm {
         monitor_info_for_message.monitor_height = monitor.geometry.w_h;
         monitor_info_for_message.monitor_num = monitor.num;
         monitor_info_for_message.set_ltsymbol(&monitor.lt_symbol);
+        monitor_info_for_message.set_layout_symbols(LAYOUTS.iter().map(|l| l.symbol()));
+        monitor_info_for_message.set_key_mode(self.key_mode.as_deref().unwrap_or(""));
 
         // 计算标签掩码
         let (occupied_tags_mask, urgent_tags_mask) = self.calculate_tag_masks(mon_key);
 
         // 处理标签状态
-        for i in 0..CONFIG.tags_length() {
+        let config = CONFIG.load();
+        monitor_info_for_message.set_tag_count(config.tags_length());
+        monitor_info_for_message.set_tag_labels(config.tag_labels().iter().map(String::as_str));
+        for i in 0..config.tags_length() {
             let tag_bit = 1 << i;
 
             // 计算是否为填充标签（当前选中客户端是否在此标签上）
@@ -7530,7 +10127,10 @@ impl Jwm {
         if let Some(client_keys) = self.monitor_clients.get(mon_key) {
             for &client_key in client_keys {
                 if let Some(client) = self.clients.get(client_key) {
-                    occupied_tags_mask |= client.state.tags;
+                    // sticky 窗口到处可见，不算作占用
+                    if !client.state.is_sticky {
+                        occupied_tags_mask |= client.state.tags;
+                    }
                     if client.state.is_urgent {
                         urgent_tags_mask |= client.state.tags;
                     }
@@ -0,0 +1,851 @@
+use bincode::{Decode, Encode};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::jwm::ClientKey;
+
+pub const DEFAULT_TILE_SYMBOL: &str = "[]=";
+pub const DEFAULT_FLOAT_SYMBOL: &str = "><>";
+pub const DEFAULT_MONOCLE_SYMBOL: &str = "[M]";
+pub const DEFAULT_BSTACK_SYMBOL: &str = "TTT";
+pub const DEFAULT_CENTERED_MASTER_SYMBOL: &str = "|M|";
+pub const DEFAULT_GRID_SYMBOL: &str = "###";
+pub const DEFAULT_SPIRAL_SYMBOL: &str = "[@]";
+pub const DEFAULT_DWINDLE_SYMBOL: &str = "[\\]";
+pub const DEFAULT_DECK_SYMBOL: &str = "[D]";
+
+/// 布局可用的工作区（已扣除状态栏占用的高度）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayoutArea {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
+/// 参与布局的平铺客户端
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TiledClient {
+    pub key: ClientKey,
+    pub client_fact: f32,
+    pub border_w: i32,
+}
+
+/// 间隙（像素）：inner_h 为左右相邻窗口之间，inner_v 为上下相邻窗口之间，
+/// outer_h 为窗口与屏幕左右边缘之间，outer_v 为窗口与屏幕上下边缘之间
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Decode, Encode)]
+pub struct Gaps {
+    pub inner_h: i32,
+    pub inner_v: i32,
+    pub outer_h: i32,
+    pub outer_v: i32,
+}
+
+impl Gaps {
+    pub const NONE: Self = Self {
+        inner_h: 0,
+        inner_v: 0,
+        outer_h: 0,
+        outer_v: 0,
+    };
+
+    /// 扣除外间隙后的工作区
+    pub fn shrink(&self, area: LayoutArea) -> LayoutArea {
+        LayoutArea {
+            x: area.x + self.outer_h,
+            y: area.y + self.outer_v,
+            w: area.w - 2 * self.outer_h,
+            h: area.h - 2 * self.outer_v,
+        }
+    }
+}
+
+/// 当前 tag 的布局参数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutParams {
+    pub n_master: u32,
+    pub m_fact: f32,
+    // 外间隙已在 area 中扣除，布局只需处理内间隙
+    pub gaps: Gaps,
+}
+
+/// 布局结果：直接交给 resize_client 的几何（宽高已扣除边框）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayoutRect {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
+pub trait Layout: Sync {
+    /// 注册名，配置文件与快照中使用
+    fn name(&self) -> &'static str;
+
+    /// 状态栏布局选择器中显示的符号
+    fn symbol(&self) -> &'static str;
+
+    /// 排列后写入 lt_symbol 的符号，visible 为该显示器上可见客户端数
+    fn bar_symbol(&self, _visible: usize) -> String {
+        self.symbol().to_string()
+    }
+
+    /// 浮动布局不排列任何窗口
+    fn is_floating(&self) -> bool {
+        false
+    }
+
+    /// 返回的几何与 clients 一一对应
+    fn arrange(
+        &self,
+        area: LayoutArea,
+        clients: &[TiledClient],
+        params: LayoutParams,
+    ) -> Vec<LayoutRect>;
+}
+
+impl fmt::Debug for dyn Layout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Layout({})", self.name())
+    }
+}
+
+impl PartialEq for dyn Layout {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name()
+    }
+}
+
+pub const TILE: &dyn Layout = &TileLayout;
+pub const FLOAT: &dyn Layout = &FloatLayout;
+pub const MONOCLE: &dyn Layout = &MonocleLayout;
+pub const BSTACK: &dyn Layout = &BottomStackLayout;
+pub const CENTERED_MASTER: &dyn Layout = &CenteredMasterLayout;
+pub const GRID: &dyn Layout = &GridLayout;
+pub const SPIRAL: &dyn Layout = &FibonacciLayout { dwindle: false };
+pub const DWINDLE: &dyn Layout = &FibonacciLayout { dwindle: true };
+pub const DECK: &dyn Layout = &DeckLayout;
+
+// 按 client_fact 比例切分一段长度（已扣除间隙），返回每段长度
+fn split_by_facts(total: i32, gap: i32, clients: &[TiledClient]) -> Vec<i32> {
+    let total = total - gap * (clients.len() as i32 - 1).max(0);
+    let mut remaining_facts: f32 = clients.iter().map(|c| c.client_fact).sum();
+    let mut used = 0i32;
+    let mut sizes = Vec::with_capacity(clients.len());
+    for (i, c) in clients.iter().enumerate() {
+        let remaining = (total - used).max(0);
+        let left = (clients.len() - i) as i32;
+        let size = if remaining_facts > 0.001 {
+            (remaining as f32 * (c.client_fact / remaining_facts)) as i32
+        } else {
+            remaining / left
+        };
+        used += size;
+        remaining_facts -= c.client_fact;
+        sizes.push(size);
+    }
+    sizes
+}
+
+// 在 area 内纵向排列（高度按 client_fact 分配）
+fn stack_column(area: LayoutArea, clients: &[TiledClient], gap: i32) -> Vec<LayoutRect> {
+    let mut y = area.y;
+    clients
+        .iter()
+        .zip(split_by_facts(area.h, gap, clients))
+        .map(|(c, h)| {
+            let rect = LayoutRect {
+                x: area.x,
+                y,
+                w: area.w - 2 * c.border_w,
+                h: h - 2 * c.border_w,
+            };
+            y += h + gap;
+            rect
+        })
+        .collect()
+}
+
+// 在 area 内横向排列（宽度按 client_fact 分配）
+fn stack_row(area: LayoutArea, clients: &[TiledClient], gap: i32) -> Vec<LayoutRect> {
+    let mut x = area.x;
+    clients
+        .iter()
+        .zip(split_by_facts(area.w, gap, clients))
+        .map(|(c, w)| {
+            let rect = LayoutRect {
+                x,
+                y: area.y,
+                w: w - 2 * c.border_w,
+                h: area.h - 2 * c.border_w,
+            };
+            x += w + gap;
+            rect
+        })
+        .collect()
+}
+
+// 左侧切出宽 width 的区域，返回 (左侧区, 间隙之后的剩余区)
+fn split_left(area: LayoutArea, width: i32, gap: i32) -> (LayoutArea, LayoutArea) {
+    (
+        LayoutArea { w: width, ..area },
+        LayoutArea {
+            x: area.x + width + gap,
+            w: area.w - width - gap,
+            ..area
+        },
+    )
+}
+
+// 顶部切出高 height 的区域，返回 (顶部区, 间隙之后的剩余区)
+fn split_top(area: LayoutArea, height: i32, gap: i32) -> (LayoutArea, LayoutArea) {
+    (
+        LayoutArea { h: height, ..area },
+        LayoutArea {
+            y: area.y + height + gap,
+            h: area.h - height - gap,
+            ..area
+        },
+    )
+}
+
+// 左右划分 master/stack：只有一侧有窗口时该侧占满
+fn split_master_left(
+    area: LayoutArea,
+    n: usize,
+    n_master: usize,
+    params: &LayoutParams,
+) -> (LayoutArea, LayoutArea) {
+    let gap = params.gaps.inner_h;
+    if n_master == 0 {
+        (LayoutArea { w: 0, ..area }, area)
+    } else if n <= n_master {
+        (area, LayoutArea { w: 0, ..area })
+    } else {
+        let mw = ((area.w - gap) as f32 * params.m_fact) as i32;
+        split_left(area, mw, gap)
+    }
+}
+
+// 上下划分 master/stack：只有一侧有窗口时该侧占满
+fn split_master_top(
+    area: LayoutArea,
+    n: usize,
+    n_master: usize,
+    params: &LayoutParams,
+) -> (LayoutArea, LayoutArea) {
+    let gap = params.gaps.inner_v;
+    if n_master == 0 {
+        (LayoutArea { h: 0, ..area }, area)
+    } else if n <= n_master {
+        (area, LayoutArea { h: 0, ..area })
+    } else {
+        let mh = ((area.h - gap) as f32 * params.m_fact) as i32;
+        split_top(area, mh, gap)
+    }
+}
+
+/// 左侧 master，右侧垂直 stack
+pub struct TileLayout;
+
+impl Layout for TileLayout {
+    fn name(&self) -> &'static str {
+        "tile"
+    }
+
+    fn symbol(&self) -> &'static str {
+        DEFAULT_TILE_SYMBOL
+    }
+
+    fn arrange(
+        &self,
+        area: LayoutArea,
+        clients: &[TiledClient],
+        params: LayoutParams,
+    ) -> Vec<LayoutRect> {
+        let n_master = (params.n_master as usize).min(clients.len());
+        let (master_area, stack_area) = split_master_left(area, clients.len(), n_master, &params);
+        let (masters, stack) = clients.split_at(n_master);
+
+        let mut rects = stack_column(master_area, masters, params.gaps.inner_v);
+        rects.extend(stack_column(stack_area, stack, params.gaps.inner_v));
+        rects
+    }
+}
+
+/// 顶部 master 横向排列，底部 stack 横向排列
+pub struct BottomStackLayout;
+
+impl Layout for BottomStackLayout {
+    fn name(&self) -> &'static str {
+        "bstack"
+    }
+
+    fn symbol(&self) -> &'static str {
+        DEFAULT_BSTACK_SYMBOL
+    }
+
+    fn arrange(
+        &self,
+        area: LayoutArea,
+        clients: &[TiledClient],
+        params: LayoutParams,
+    ) -> Vec<LayoutRect> {
+        let n_master = (params.n_master as usize).min(clients.len());
+        let (master_area, stack_area) = split_master_top(area, clients.len(), n_master, &params);
+        let (masters, stack) = clients.split_at(n_master);
+
+        let mut rects = stack_row(master_area, masters, params.gaps.inner_h);
+        rects.extend(stack_row(stack_area, stack, params.gaps.inner_h));
+        rects
+    }
+}
+
+/// master 居中，stack 交替分布在左右两侧
+pub struct CenteredMasterLayout;
+
+impl Layout for CenteredMasterLayout {
+    fn name(&self) -> &'static str {
+        "centeredmaster"
+    }
+
+    fn symbol(&self) -> &'static str {
+        DEFAULT_CENTERED_MASTER_SYMBOL
+    }
+
+    fn arrange(
+        &self,
+        area: LayoutArea,
+        clients: &[TiledClient],
+        params: LayoutParams,
+    ) -> Vec<LayoutRect> {
+        let n_master = (params.n_master as usize).min(clients.len());
+        let (masters, stack) = clients.split_at(n_master);
+        let (gap_h, gap_v) = (params.gaps.inner_h, params.gaps.inner_v);
+
+        // 只有一个 stack 窗口时放在右侧，不留左侧空白
+        if stack.len() <= 1 {
+            let (master_area, stack_area) =
+                split_master_left(area, clients.len(), n_master, &params);
+            let mut rects = stack_column(master_area, masters, gap_v);
+            rects.extend(stack_column(stack_area, stack, gap_v));
+            return rects;
+        }
+
+        // 三列（无 master 时两列）之间各有一条间隙
+        let columns_gap = if n_master > 0 { 2 * gap_h } else { gap_h };
+        let usable_w = area.w - columns_gap;
+        let mw = if n_master > 0 {
+            (usable_w as f32 * params.m_fact) as i32
+        } else {
+            0
+        };
+        let side_w = (usable_w - mw) / 2;
+        let (left_area, rest) = split_left(area, side_w, gap_h);
+        let (master_area, right_area) = if n_master > 0 {
+            split_left(rest, mw, gap_h)
+        } else {
+            (LayoutArea { w: 0, ..rest }, rest)
+        };
+
+        // 偶数下标放右侧，奇数下标放左侧
+        let right: Vec<TiledClient> = stack.iter().step_by(2).copied().collect();
+        let left: Vec<TiledClient> = stack.iter().skip(1).step_by(2).copied().collect();
+        let mut right_rects = stack_column(right_area, &right, gap_v).into_iter();
+        let mut left_rects = stack_column(left_area, &left, gap_v).into_iter();
+
+        let mut rects = stack_column(master_area, masters, gap_v);
+        for i in 0..stack.len() {
+            let rect = if i % 2 == 0 {
+                right_rects.next()
+            } else {
+                left_rects.next()
+            };
+            rects.extend(rect);
+        }
+        rects
+    }
+}
+
+/// 无空隙网格；有 master 时 master 占左侧一列
+pub struct GridLayout;
+
+impl GridLayout {
+    fn grid(area: LayoutArea, clients: &[TiledClient], gaps: Gaps) -> Vec<LayoutRect> {
+        let n = clients.len();
+        if n == 0 {
+            return Vec::new();
+        }
+        let mut cols = 1;
+        while cols * cols < n {
+            cols += 1;
+        }
+        if n == 5 {
+            cols = 2;
+        }
+
+        // 前面的列放 n / cols 个窗口，后面 n % cols 列各多放一个
+        let base_rows = n / cols;
+        let extra_cols = n % cols;
+        let col_width = (area.w - gaps.inner_h * (cols as i32 - 1)) / cols as i32;
+
+        let mut rects = Vec::with_capacity(n);
+        let mut start = 0;
+        for col in 0..cols {
+            let rows = if col >= cols - extra_cols {
+                base_rows + 1
+            } else {
+                base_rows
+            };
+            let col_x = area.x + col as i32 * (col_width + gaps.inner_h);
+            let col_area = LayoutArea {
+                x: col_x,
+                w: if col == cols - 1 {
+                    area.x + area.w - col_x
+                } else {
+                    col_width
+                },
+                ..area
+            };
+            rects.extend(stack_column(
+                col_area,
+                &clients[start..start + rows],
+                gaps.inner_v,
+            ));
+            start += rows;
+        }
+        rects
+    }
+}
+
+impl Layout for GridLayout {
+    fn name(&self) -> &'static str {
+        "grid"
+    }
+
+    fn symbol(&self) -> &'static str {
+        DEFAULT_GRID_SYMBOL
+    }
+
+    fn arrange(
+        &self,
+        area: LayoutArea,
+        clients: &[TiledClient],
+        params: LayoutParams,
+    ) -> Vec<LayoutRect> {
+        let n_master = (params.n_master as usize).min(clients.len());
+        if n_master == 0 || n_master == clients.len() {
+            return Self::grid(area, clients, params.gaps);
+        }
+        let (master_area, stack_area) = split_master_left(area, clients.len(), n_master, &params);
+        let (masters, stack) = clients.split_at(n_master);
+
+        let mut rects = stack_column(master_area, masters, params.gaps.inner_v);
+        rects.extend(Self::grid(stack_area, stack, params.gaps));
+        rects
+    }
+}
+
+/// 斐波那契螺旋（spiral）与向右下收缩（dwindle）
+pub struct FibonacciLayout {
+    pub dwindle: bool,
+}
+
+impl Layout for FibonacciLayout {
+    fn name(&self) -> &'static str {
+        if self.dwindle {
+            "dwindle"
+        } else {
+            "spiral"
+        }
+    }
+
+    fn symbol(&self) -> &'static str {
+        if self.dwindle {
+            DEFAULT_DWINDLE_SYMBOL
+        } else {
+            DEFAULT_SPIRAL_SYMBOL
+        }
+    }
+
+    fn arrange(
+        &self,
+        area: LayoutArea,
+        clients: &[TiledClient],
+        params: LayoutParams,
+    ) -> Vec<LayoutRect> {
+        let n_master = (params.n_master as usize).min(clients.len());
+        let (masters, stack) = clients.split_at(n_master);
+
+        let (gap_h, gap_v) = (params.gaps.inner_h, params.gaps.inner_v);
+
+        // master 先占左侧一列，其余窗口在剩余区域内螺旋切分
+        let (master_area, mut rest) = split_master_left(area, clients.len(), n_master, &params);
+        let mut rects = stack_column(master_area, masters, gap_v);
+
+        // 第 0 步（左右切）已由 master 完成时，从上下切开始
+        let first_step = usize::from(n_master > 0);
+        for (i, c) in stack.iter().enumerate() {
+            let step = first_step + i;
+            let own = if let Some(next) = stack.get(i + 1) {
+                let ratio = if c.client_fact + next.client_fact > 0.001 {
+                    c.client_fact / (c.client_fact + next.client_fact)
+                } else {
+                    0.5
+                };
+                // dwindle 总是取左/上半；spiral 依次取左、上、右、下
+                let take_far = !self.dwindle && step % 4 >= 2;
+                let (own, remain) = if step % 2 == 0 {
+                    let w = ((rest.w - gap_h) as f32 * ratio) as i32;
+                    if take_far {
+                        let (near, far) = split_left(rest, rest.w - w - gap_h, gap_h);
+                        (far, near)
+                    } else {
+                        split_left(rest, w, gap_h)
+                    }
+                } else {
+                    let h = ((rest.h - gap_v) as f32 * ratio) as i32;
+                    if take_far {
+                        let (near, far) = split_top(rest, rest.h - h - gap_v, gap_v);
+                        (far, near)
+                    } else {
+                        split_top(rest, h, gap_v)
+                    }
+                };
+                rest = remain;
+                own
+            } else {
+                rest
+            };
+            rects.push(LayoutRect {
+                x: own.x,
+                y: own.y,
+                w: own.w - 2 * c.border_w,
+                h: own.h - 2 * c.border_w,
+            });
+        }
+        rects
+    }
+}
+
+/// 左侧 master，stack 窗口叠放在右侧（类似 monocle）
+pub struct DeckLayout;
+
+impl Layout for DeckLayout {
+    fn name(&self) -> &'static str {
+        "deck"
+    }
+
+    fn symbol(&self) -> &'static str {
+        DEFAULT_DECK_SYMBOL
+    }
+
+    fn arrange(
+        &self,
+        area: LayoutArea,
+        clients: &[TiledClient],
+        params: LayoutParams,
+    ) -> Vec<LayoutRect> {
+        let n_master = (params.n_master as usize).min(clients.len());
+        let (master_area, stack_area) = split_master_left(area, clients.len(), n_master, &params);
+        let (masters, stack) = clients.split_at(n_master);
+
+        let mut rects = stack_column(master_area, masters, params.gaps.inner_v);
+        rects.extend(stack.iter().map(|c| LayoutRect {
+            x: stack_area.x,
+            y: stack_area.y,
+            w: stack_area.w - 2 * c.border_w,
+            h: stack_area.h - 2 * c.border_w,
+        }));
+        rects
+    }
+}
+
+/// 不排列，所有窗口自由摆放
+pub struct FloatLayout;
+
+impl Layout for FloatLayout {
+    fn name(&self) -> &'static str {
+        "float"
+    }
+
+    fn symbol(&self) -> &'static str {
+        DEFAULT_FLOAT_SYMBOL
+    }
+
+    fn is_floating(&self) -> bool {
+        true
+    }
+
+    fn arrange(
+        &self,
+        _area: LayoutArea,
+        _clients: &[TiledClient],
+        _params: LayoutParams,
+    ) -> Vec<LayoutRect> {
+        Vec::new()
+    }
+}
+
+/// 所有平铺窗口占满工作区
+pub struct MonocleLayout;
+
+impl Layout for MonocleLayout {
+    fn name(&self) -> &'static str {
+        "monocle"
+    }
+
+    fn symbol(&self) -> &'static str {
+        DEFAULT_MONOCLE_SYMBOL
+    }
+
+    fn bar_symbol(&self, visible: usize) -> String {
+        if visible > 0 {
+            format!("[{}]", visible)
+        } else {
+            self.symbol().to_string()
+        }
+    }
+
+    fn arrange(
+        &self,
+        area: LayoutArea,
+        clients: &[TiledClient],
+        _params: LayoutParams,
+    ) -> Vec<LayoutRect> {
+        clients
+            .iter()
+            .map(|c| LayoutRect {
+                x: area.x,
+                y: area.y,
+                w: area.w - 2 * c.border_w,
+                h: area.h - 2 * c.border_w,
+            })
+            .collect()
+    }
+}
+
+/// 按名字注册的布局表，索引顺序即状态栏选择器的顺序
+pub struct LayoutRegistry {
+    layouts: Vec<&'static dyn Layout>,
+}
+
+impl LayoutRegistry {
+    fn new() -> Self {
+        let mut registry = Self {
+            layouts: Vec::new(),
+        };
+        registry.register(TILE);
+        registry.register(FLOAT);
+        registry.register(MONOCLE);
+        registry.register(BSTACK);
+        registry.register(CENTERED_MASTER);
+        registry.register(GRID);
+        registry.register(SPIRAL);
+        registry.register(DWINDLE);
+        registry.register(DECK);
+        registry
+    }
+
+    /// 同名布局会被替换
+    fn register(&mut self, layout: &'static dyn Layout) {
+        if let Some(slot) = self.layouts.iter_mut().find(|l| l.name() == layout.name()) {
+            *slot = layout;
+        } else {
+            self.layouts.push(layout);
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&'static dyn Layout> {
+        self.layouts.iter().copied().find(|l| l.name() == name)
+    }
+
+    pub fn by_index(&self, index: usize) -> Option<&'static dyn Layout> {
+        self.layouts.get(index).copied()
+    }
+
+    pub fn index_of(&self, layout: &dyn Layout) -> Option<usize> {
+        self.layouts.iter().position(|l| l.name() == layout.name())
+    }
+
+    pub fn len(&self) -> usize {
+        self.layouts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layouts.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &'static dyn Layout> + '_ {
+        self.layouts.iter().copied()
+    }
+}
+
+// 全局实例
+pub static LAYOUTS: Lazy<LayoutRegistry> = Lazy::new(LayoutRegistry::new);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: LayoutArea = LayoutArea {
+        x: 0,
+        y: 0,
+        w: 1000,
+        h: 600,
+    };
+
+    fn params(n_master: u32) -> LayoutParams {
+        LayoutParams {
+            n_master,
+            m_fact: 0.55,
+            gaps: Gaps::NONE,
+        }
+    }
+
+    fn clients(facts: &[f32]) -> Vec<TiledClient> {
+        facts
+            .iter()
+            .map(|&client_fact| TiledClient {
+                key: ClientKey::default(),
+                client_fact,
+                border_w: 0,
+            })
+            .collect()
+    }
+
+    fn overlaps(a: &LayoutRect, b: &LayoutRect) -> bool {
+        a.x < b.x + b.w && b.x < a.x + a.w && a.y < b.y + b.h && b.y < a.y + a.h
+    }
+
+    // 无间隙无边框时，结果应恰好铺满工作区且互不重叠
+    fn assert_tiles(layout: &'static dyn Layout, rects: &[LayoutRect]) {
+        for (i, r) in rects.iter().enumerate() {
+            assert!(r.w > 0 && r.h > 0, "{:?}: 空矩形 {:?}", layout, r);
+            assert!(
+                r.x >= AREA.x
+                    && r.y >= AREA.y
+                    && r.x + r.w <= AREA.x + AREA.w
+                    && r.y + r.h <= AREA.y + AREA.h,
+                "{:?}: {:?} 超出工作区",
+                layout,
+                r
+            );
+            for other in &rects[i + 1..] {
+                assert!(
+                    !overlaps(r, other),
+                    "{:?}: {:?} 与 {:?} 重叠",
+                    layout,
+                    r,
+                    other
+                );
+            }
+        }
+        let covered: i32 = rects.iter().map(|r| r.w * r.h).sum();
+        assert_eq!(covered, AREA.w * AREA.h, "{:?}: 未铺满工作区", layout);
+    }
+
+    const TILING: [&dyn Layout; 6] = [TILE, BSTACK, CENTERED_MASTER, GRID, SPIRAL, DWINDLE];
+
+    #[test]
+    fn test_no_clients() {
+        for layout in TILING.iter().chain([&DECK, &MONOCLE, &FLOAT]) {
+            assert!(layout.arrange(AREA, &[], params(1)).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_single_client_fills_area() {
+        let full = LayoutRect {
+            x: AREA.x,
+            y: AREA.y,
+            w: AREA.w,
+            h: AREA.h,
+        };
+        for layout in TILING.iter().chain([&DECK]) {
+            for n_master in [0, 1, 2] {
+                let rects = layout.arrange(AREA, &clients(&[1.0]), params(n_master));
+                assert_eq!(rects, vec![full], "{:?} n_master={}", layout, n_master);
+            }
+        }
+    }
+
+    #[test]
+    fn test_layouts_tile_area() {
+        for layout in TILING {
+            for n_master in [0, 1, 2] {
+                for n in 2..=8 {
+                    let rects = layout.arrange(AREA, &clients(&vec![1.0; n]), params(n_master));
+                    assert_eq!(rects.len(), n, "{:?}", layout);
+                    assert_tiles(layout, &rects);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_deck_stacks_share_area() {
+        let rects = DECK.arrange(AREA, &clients(&[1.0; 4]), params(1));
+        assert_eq!(rects.len(), 4);
+        // master 与一个 stack 窗口铺满，其余 stack 窗口与之重叠
+        assert_tiles(DECK, &rects[..2]);
+        assert!(rects[1..].iter().all(|r| *r == rects[1]));
+    }
+
+    #[test]
+    fn test_split_by_facts() {
+        assert_eq!(
+            split_by_facts(400, 0, &clients(&[1.0, 3.0])),
+            vec![100, 300]
+        );
+        assert_eq!(
+            split_by_facts(400, 10, &clients(&[1.0, 1.0, 2.0])),
+            vec![95, 95, 190]
+        );
+        // 余数全部给最后一个窗口
+        assert_eq!(
+            split_by_facts(100, 0, &clients(&[1.0; 3])),
+            vec![33, 33, 34]
+        );
+        assert!(split_by_facts(100, 0, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_client_fact_weighting() {
+        let rects = TILE.arrange(AREA, &clients(&[1.0, 1.0, 2.0, 1.0]), params(1));
+        let heights: Vec<i32> = rects[1..].iter().map(|r| r.h).collect();
+        assert_eq!(heights, vec![150, 300, 150]);
+
+        let rects = BSTACK.arrange(AREA, &clients(&[1.0, 3.0, 1.0]), params(1));
+        let widths: Vec<i32> = rects[1..].iter().map(|r| r.w).collect();
+        assert_eq!(widths, vec![750, 250]);
+    }
+
+    #[test]
+    fn test_centered_master_sides() {
+        let p = params(1);
+        // 只有一个 stack 窗口时退化为左右两列
+        let rects = CENTERED_MASTER.arrange(AREA, &clients(&[1.0; 2]), p);
+        assert_eq!(rects[0].x, AREA.x);
+        assert!(rects[1].x > rects[0].x);
+
+        // 偶数下标在右，奇数下标在左
+        for n_stack in [2, 3, 4] {
+            let rects = CENTERED_MASTER.arrange(AREA, &clients(&vec![1.0; n_stack + 1]), p);
+            let master = rects[0];
+            for (i, r) in rects[1..].iter().enumerate() {
+                if i % 2 == 0 {
+                    assert_eq!(r.x, master.x + master.w, "stack {} 应在右侧", i);
+                } else {
+                    assert_eq!(r.x + r.w, master.x, "stack {} 应在左侧", i);
+                }
+            }
+        }
+
+        // 三个 stack 窗口：右侧两个平分高度，左侧一个占满
+        let rects = CENTERED_MASTER.arrange(AREA, &clients(&[1.0; 4]), p);
+        assert_eq!((rects[1].h, rects[3].h), (300, 300));
+        assert_eq!(rects[2].h, AREA.h);
+    }
+}
@@ -3,7 +3,10 @@
 
 pub mod backend;
 pub mod config;
+pub mod config_check;
+pub mod ipc;
 pub mod jwm;
+pub mod layout;
 pub mod miscellaneous;
 pub mod terminal_prober;
 
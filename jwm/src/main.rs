@@ -1,10 +1,21 @@
 // use bar::StatusBar;
+use clap::Parser;
 use jwm::backend::x11::backend::X11Backend;
+use jwm::config_check::run_check_config;
 use jwm::{jwm::SHARED_PATH, Jwm};
 use log::{error, info, warn};
+use std::path::PathBuf;
 use std::{env, process::Command, sync::atomic::Ordering};
 use xbar_core::initialize_logging;
 
+#[derive(Parser)]
+#[command(name = "jwm", version, about = "JWM 窗口管理器")]
+struct Cli {
+    /// 检查配置文件（缺省为 ~/.config/jwm/config.toml）并退出，有问题时返回非零
+    #[arg(long, value_name = "PATH", num_args = 0..=1)]
+    check_config: Option<Option<PathBuf>>,
+}
+
 pub fn setup_locale() {
     // 获取当前locale
     let locale = env::var("LANG")
@@ -31,6 +42,11 @@ pub fn setup_locale() {
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    if let Some(path) = cli.check_config {
+        std::process::exit(run_check_config(path));
+    }
+
     setup_locale();
     jwm::miscellaneous::init_auto_command();
     jwm::miscellaneous::init_auto_start();
@@ -34,6 +34,28 @@ pub fn init_auto_command() {
     }
 }
 
+// 从 /proc/<pid>/stat 读取父进程号（第 4 个字段，位于进程名括号之后）
+pub fn parent_pid(pid: u32) -> Option<u32> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    let rest = &stat[stat.rfind(')')? + 1..];
+    rest.split_whitespace().nth(1)?.parse().ok()
+}
+
+// 沿父进程链向上查找 ancestor
+pub fn is_descendant_process(ancestor: u32, pid: u32) -> bool {
+    let mut current = pid;
+    while current > 1 {
+        if current == ancestor {
+            return true;
+        }
+        match parent_pid(current) {
+            Some(ppid) if ppid != current => current = ppid,
+            _ => return false,
+        }
+    }
+    false
+}
+
 pub fn init_auto_start() {
     match home_dir() {
         Some(path) => {
@@ -11,10 +11,12 @@ use signal_hook::consts::{SIGINT, SIGTERM};
 use signal_hook::flag;
 use std::env;
 use std::fs::{self, OpenOptions};
-use std::io::{self, Write};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::Shutdown;
 use std::os::fd::AsFd;
 use std::os::fd::OwnedFd;
 use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::UnixStream;
 use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -60,6 +62,21 @@ enum Commands {
 
     /// 调试信息
     Debug,
+
+    /// 通过 IPC socket 向运行中的 JWM 发送 JSON 请求并打印响应
+    /// 例如：jwm-tool msg '{"command":"run","function":"view","argument":2}'
+    Msg {
+        /// 一行 JSON 请求
+        request: String,
+    },
+
+    /// 订阅 JWM 事件并逐行打印（JSON），不指定主题时订阅全部
+    /// 例如：jwm-tool subscribe focus title_changed
+    Subscribe {
+        /// 主题：focus、client_managed、client_unmanaged、title_changed、
+        /// tag_view、layout_changed、monitor_added、monitor_removed、urgent
+        events: Vec<String>,
+    },
 }
 
 fn default_jwm_dir() -> String {
@@ -498,6 +515,57 @@ fn find_control_pipe() -> Option<PathBuf> {
     }
 }
 
+fn connect_ipc() -> io::Result<UnixStream> {
+    let path = jwm::ipc::socket_path();
+    UnixStream::connect(&path)
+        .map_err(|e| io::Error::new(e.kind(), format!("无法连接 {}: {e}", path.display())))
+}
+
+fn subscribe_ipc_events(events: &[String]) -> io::Result<()> {
+    let mut stream = connect_ipc()?;
+    let request = serde_json::json!({ "command": "subscribe", "events": events });
+    stream.write_all(format!("{request}\n").as_bytes())?;
+
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let subscribed = serde_json::from_str::<jwm::ipc::IpcResponse>(&line)
+        .map(|response| response.success)
+        .unwrap_or(false);
+    if !subscribed {
+        eprintln!("订阅失败: {}", line.trim_end());
+        std::process::exit(1);
+    }
+
+    // 每行一个事件，直到 JWM 退出
+    let stdout = io::stdout();
+    for line in reader.lines() {
+        let mut out = stdout.lock();
+        writeln!(out, "{}", line?)?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+fn send_ipc_request(request: &str) -> io::Result<()> {
+    let mut stream = connect_ipc()?;
+    stream.write_all(request.trim().as_bytes())?;
+    stream.write_all(b"\n")?;
+    stream.shutdown(Shutdown::Write)?;
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    println!("{}", line.trim_end());
+    // 请求失败时以非零退出，便于脚本判断
+    let success = serde_json::from_str::<jwm::ipc::IpcResponse>(&line)
+        .map(|response| response.success)
+        .unwrap_or(false);
+    if !success {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
 fn send_command(cmd: &str) -> io::Result<()> {
     let pipe = match find_control_pipe() {
         Some(p) => p,
@@ -795,6 +863,9 @@ fn main() -> io::Result<()> {
         }
 
         Commands::Debug => debug_info(),
+
+        Commands::Msg { request } => send_ipc_request(&request)?,
+        Commands::Subscribe { events } => subscribe_ipc_events(&events)?,
     }
 
     Ok(())
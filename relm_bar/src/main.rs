@@ -5,7 +5,7 @@ use gtk::prelude::*;
 use gtk4 as gtk;
 use gtk4::Window;
 use gtk4::glib::ControlFlow;
-use log::{error, info, warn};
+use log::{error, info};
 use relm4::{ComponentParts, ComponentSender, RelmApp, SimpleComponent};
 
 use std::sync::Arc;
@@ -142,6 +142,8 @@ pub struct AppModel {
     #[do_not_track]
     monitor_label_widget: gtk::Label,
     #[do_not_track]
+    tab_box: gtk::Box,
+    #[do_not_track]
     tab_buttons: Vec<gtk::Button>,
 
     // 新增：布局开关与选项（与 gtk_bar 的 UI/样式一致）
@@ -253,19 +255,8 @@ impl SimpleComponent for AppModel {
             time_button_widget.connect_clicked(move |_| s.input(AppInput::ToggleSeconds));
         }
 
-        // Tab 按钮与初始 emoji
-        let mut tab_buttons = Vec::with_capacity(9);
-        for i in 0..9 {
-            let id = format!("tab_button_{}", i);
-            if let Some(btn) = builder.object::<gtk::Button>(&id) {
-                btn.set_label(pick_emoji(i));
-                let s = sender.clone();
-                btn.connect_clicked(move |_| s.input(AppInput::TabSelected(i)));
-                tab_buttons.push(btn);
-            } else {
-                warn!("Missing {}", id);
-            }
-        }
+        // Tab 按钮容器，按钮随 jwm 上报的 tag 数量创建
+        let tab_box: gtk::Box = builder.object("tab_box").expect("Missing tab_box");
 
         // 5) 构建 model
         let shared_arc =
@@ -289,7 +280,8 @@ impl SimpleComponent for AppModel {
             memory_label_widget,
             time_button_widget,
             monitor_label_widget,
-            tab_buttons,
+            tab_box,
+            tab_buttons: Vec::new(),
 
             layout_toggle_widget,
             layout_revealer_widget,
@@ -315,7 +307,7 @@ impl SimpleComponent for AppModel {
         ComponentParts { model, widgets }
     }
 
-    fn update(&mut self, msg: Self::Input, _sender: ComponentSender<Self>) {
+    fn update(&mut self, msg: Self::Input, sender: ComponentSender<Self>) {
         self.reset();
         match msg {
             AppInput::TabSelected(index) => {
@@ -353,6 +345,7 @@ impl SimpleComponent for AppModel {
 
             AppInput::SharedMessageReceived(message) => {
                 info!("SharedMessageReceived: {:?}", message);
+                self.sync_tab_buttons(message.monitor_info.tag_count(), &sender);
                 self.process_shared_message(message);
                 // 刷新 tab、布局开关与选项、监视器图标
                 self.sync_tabs_ui();
@@ -458,10 +451,10 @@ impl AppModel {
         self.last_shared_message = Some(message.clone());
         self.layout_symbol = message.monitor_info.get_ltsymbol();
         self.monitor_num = message.monitor_info.monitor_num as u8;
-        self.set_tag_status_vec(message.monitor_info.tag_status_vec.to_vec());
+        self.set_tag_status_vec(message.monitor_info.tag_statuses().to_vec());
 
         // 更新活动标签
-        for (index, tag_status) in message.monitor_info.tag_status_vec.iter().enumerate() {
+        for (index, tag_status) in message.monitor_info.tag_statuses().iter().enumerate() {
             if tag_status.is_selected {
                 self.active_tab = index;
             }
@@ -552,9 +545,37 @@ impl AppModel {
         apply_metric_classes(&self.memory_label_widget, self.memory_usage);
     }
 
+    // tag 数量变化时增删 Tab 按钮，已有按钮保持不变
+    fn sync_tab_buttons(&mut self, count: usize, sender: &ComponentSender<Self>) {
+        while self.tab_buttons.len() > count {
+            if let Some(btn) = self.tab_buttons.pop() {
+                self.tab_box.remove(&btn);
+            }
+        }
+        while self.tab_buttons.len() < count {
+            let index = self.tab_buttons.len();
+            let btn = gtk::Button::with_label(pick_emoji(index));
+            btn.set_size_request(40, 32);
+            btn.add_css_class("tab-button");
+            let s = sender.clone();
+            btn.connect_clicked(move |_| s.input(AppInput::TabSelected(index)));
+            self.tab_box.append(&btn);
+            self.tab_buttons.push(btn);
+        }
+    }
+
     fn sync_tabs_ui(&self) {
+        // 收到 jwm 消息后使用其发布的标签
+        let labels = self
+            .last_shared_message
+            .as_ref()
+            .map(|message| message.monitor_info.get_tag_labels())
+            .unwrap_or_default();
         for (i, btn) in self.tab_buttons.iter().enumerate() {
-            btn.set_label(pick_emoji(i));
+            match labels.get(i) {
+                Some(label) => btn.set_label(label),
+                None => btn.set_label(pick_emoji(i)),
+            }
             let status = self.tag_status_vec.get(i);
             apply_tab_state_classes(btn, status);
         }
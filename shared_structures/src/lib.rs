@@ -4,7 +4,7 @@
 mod shared_message;
 pub use shared_message::{
     CommandType, MonitorInfo, SharedCommand, SharedMessage, TagStatus, MAX_CLIENT_NAME_LEN,
-    MAX_LT_SYMBOL_LEN, MAX_TAGS,
+    MAX_KEY_MODE_LEN, MAX_LAYOUTS, MAX_LT_SYMBOL_LEN, MAX_TAGS, MAX_TAG_LABEL_LEN,
 };
 
 // 核心环形缓冲区实现
@@ -6,7 +6,11 @@ use std::time::{SystemTime, UNIX_EPOCH};
 // 常量定义
 pub const MAX_CLIENT_NAME_LEN: usize = 128;
 pub const MAX_LT_SYMBOL_LEN: usize = 32;
-pub const MAX_TAGS: usize = 9;
+// 与 jwm 的 u32 tag 掩码一致
+pub const MAX_TAGS: usize = 32;
+pub const MAX_LAYOUTS: usize = 16;
+pub const MAX_TAG_LABEL_LEN: usize = 32;
+pub const MAX_KEY_MODE_LEN: usize = 32;
 
 #[inline]
 fn now_millis() -> u64 {
@@ -16,6 +20,18 @@ fn now_millis() -> u64 {
         .as_millis() as u64
 }
 
+// 截断到不超过 max 字节的完整 UTF-8 字符
+fn truncate_utf8(s: &str, max: usize) -> &str {
+    if s.len() <= max {
+        return s;
+    }
+    let mut end = max;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
 // 使用合理对齐
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Decode, Encode)]
@@ -57,10 +73,18 @@ pub struct MonitorInfo {
     pub monitor_height: i32,
     pub monitor_x: i32,
     pub monitor_y: i32,
+    // jwm 实际配置的 tag 数量，tag_status_vec 中只有前 tag_count 项有效
+    pub tag_count: u32,
     pub tag_status_vec: [TagStatus; MAX_TAGS],
+    // 每个 tag 的显示标签（名称或图标），空表示使用序号
+    pub tag_labels: [[u8; MAX_TAG_LABEL_LEN]; MAX_TAGS],
     #[serde(with = "BigArray")]
     pub client_name: [u8; MAX_CLIENT_NAME_LEN],
     pub ltsymbol: [u8; MAX_LT_SYMBOL_LEN],
+    // 可选布局的符号，下标即 SetLayout 命令的参数
+    pub layout_symbols: [[u8; MAX_LT_SYMBOL_LEN]; MAX_LAYOUTS],
+    // 当前按键模式名（如 "resize"），空表示默认模式
+    pub key_mode: [u8; MAX_KEY_MODE_LEN],
 }
 
 impl Default for MonitorInfo {
@@ -68,12 +92,16 @@ impl Default for MonitorInfo {
         Self {
             client_name: [0; MAX_CLIENT_NAME_LEN],
             tag_status_vec: [TagStatus::default(); MAX_TAGS],
+            tag_labels: [[0; MAX_TAG_LABEL_LEN]; MAX_TAGS],
             monitor_num: 0,
             monitor_width: 0,
             monitor_height: 0,
             monitor_x: 0,
             monitor_y: 0,
+            tag_count: 0,
             ltsymbol: [0; MAX_LT_SYMBOL_LEN],
+            layout_symbols: [[0; MAX_LT_SYMBOL_LEN]; MAX_LAYOUTS],
+            key_mode: [0; MAX_KEY_MODE_LEN],
         }
     }
 }
@@ -111,6 +139,88 @@ impl MonitorInfo {
         String::from_utf8_lossy(&self.ltsymbol[..null_pos]).to_string()
     }
 
+    pub fn set_layout_symbols<'a>(&mut self, symbols: impl IntoIterator<Item = &'a str>) {
+        self.layout_symbols = [[0; MAX_LT_SYMBOL_LEN]; MAX_LAYOUTS];
+        for (slot, symbol) in self.layout_symbols.iter_mut().zip(symbols) {
+            let bytes = symbol.as_bytes();
+            let len = bytes.len().min(MAX_LT_SYMBOL_LEN - 1);
+            slot[..len].copy_from_slice(&bytes[..len]);
+        }
+    }
+
+    pub fn get_layout_symbols(&self) -> Vec<String> {
+        self.layout_symbols
+            .iter()
+            .take_while(|slot| slot[0] != 0)
+            .map(|slot| {
+                let null_pos = slot
+                    .iter()
+                    .position(|&x| x == 0)
+                    .unwrap_or(MAX_LT_SYMBOL_LEN);
+                String::from_utf8_lossy(&slot[..null_pos]).to_string()
+            })
+            .collect()
+    }
+
+    pub fn set_tag_count(&mut self, count: usize) {
+        self.tag_count = count.min(MAX_TAGS) as u32;
+    }
+
+    pub fn tag_count(&self) -> usize {
+        (self.tag_count as usize).min(MAX_TAGS)
+    }
+
+    /// 有效的 tag 状态（前 tag_count 项）
+    pub fn tag_statuses(&self) -> &[TagStatus] {
+        &self.tag_status_vec[..self.tag_count()]
+    }
+
+    pub fn set_tag_labels<'a>(&mut self, labels: impl IntoIterator<Item = &'a str>) {
+        self.tag_labels = [[0; MAX_TAG_LABEL_LEN]; MAX_TAGS];
+        for (slot, label) in self.tag_labels.iter_mut().zip(labels) {
+            let bytes = truncate_utf8(label, MAX_TAG_LABEL_LEN - 1).as_bytes();
+            slot[..bytes.len()].copy_from_slice(bytes);
+        }
+    }
+
+    /// tag 的显示标签，未设置时为序号（从 1 开始）
+    pub fn get_tag_label(&self, index: usize) -> String {
+        let slot = match self.tag_labels.get(index) {
+            Some(slot) => slot,
+            None => return String::new(),
+        };
+        let null_pos = slot
+            .iter()
+            .position(|&x| x == 0)
+            .unwrap_or(MAX_TAG_LABEL_LEN);
+        if null_pos == 0 {
+            return (index + 1).to_string();
+        }
+        String::from_utf8_lossy(&slot[..null_pos]).to_string()
+    }
+
+    pub fn get_tag_labels(&self) -> Vec<String> {
+        (0..self.tag_count())
+            .map(|i| self.get_tag_label(i))
+            .collect()
+    }
+
+    pub fn set_key_mode(&mut self, mode: &str) {
+        let bytes = truncate_utf8(mode, MAX_KEY_MODE_LEN - 1).as_bytes();
+        self.key_mode = [0; MAX_KEY_MODE_LEN];
+        self.key_mode[..bytes.len()].copy_from_slice(bytes);
+    }
+
+    /// 当前按键模式名，默认模式为空字符串
+    pub fn get_key_mode(&self) -> String {
+        let null_pos = self
+            .key_mode
+            .iter()
+            .position(|&x| x == 0)
+            .unwrap_or(MAX_KEY_MODE_LEN);
+        String::from_utf8_lossy(&self.key_mode[..null_pos]).to_string()
+    }
+
     pub fn set_tag_status(&mut self, index: usize, status: TagStatus) {
         if index < MAX_TAGS {
             self.tag_status_vec[index] = status;
@@ -287,6 +397,73 @@ mod tests {
         assert_eq!(info.get_tag_status(MAX_TAGS), None);
     }
 
+    #[test]
+    fn test_tag_count() {
+        let mut info = MonitorInfo::default();
+        assert_eq!(info.tag_count(), 0);
+        assert!(info.tag_statuses().is_empty());
+
+        info.set_tag_count(12);
+        let status = TagStatus::new(true, false, false, true);
+        info.set_tag_status(11, status);
+        assert_eq!(info.tag_statuses().len(), 12);
+        assert_eq!(info.tag_statuses()[11], status);
+
+        info.set_tag_count(MAX_TAGS + 8);
+        assert_eq!(info.tag_count(), MAX_TAGS);
+        info.set_tag_status(MAX_TAGS - 1, status);
+        assert_eq!(info.get_tag_status(MAX_TAGS - 1), Some(status));
+    }
+
+    #[test]
+    fn test_tag_labels() {
+        let mut info = MonitorInfo::default();
+        info.set_tag_count(3);
+        assert_eq!(info.get_tag_labels(), vec!["1", "2", "3"]);
+
+        info.set_tag_labels(["web", "\u{f120}"]);
+        assert_eq!(info.get_tag_labels(), vec!["web", "\u{f120}", "3"]);
+
+        // 截断时不拆分多字节字符
+        let long_icons = "\u{f120}".repeat(MAX_TAG_LABEL_LEN);
+        info.set_tag_labels([long_icons.as_str()]);
+        let label = info.get_tag_label(0);
+        assert!(label.len() < MAX_TAG_LABEL_LEN);
+        assert!(label.chars().all(|c| c == '\u{f120}'));
+    }
+
+    #[test]
+    fn test_key_mode() {
+        let mut info = MonitorInfo::default();
+        assert_eq!(info.get_key_mode(), "");
+
+        info.set_key_mode("resize");
+        assert_eq!(info.get_key_mode(), "resize");
+
+        info.set_key_mode(&"模式".repeat(20));
+        assert!(info.get_key_mode().len() < MAX_KEY_MODE_LEN);
+        assert!(info.get_key_mode().starts_with("模式"));
+
+        info.set_key_mode("");
+        assert_eq!(info.get_key_mode(), "");
+    }
+
+    #[test]
+    fn test_layout_symbols() {
+        let mut info = MonitorInfo::default();
+        assert!(info.get_layout_symbols().is_empty());
+
+        info.set_layout_symbols(["[]=", "><>", "[M]"]);
+        assert_eq!(info.get_layout_symbols(), vec!["[]=", "><>", "[M]"]);
+
+        let many: Vec<String> = (0..MAX_LAYOUTS + 4).map(|i| format!("L{}", i)).collect();
+        info.set_layout_symbols(many.iter().map(String::as_str));
+        assert_eq!(info.get_layout_symbols().len(), MAX_LAYOUTS);
+
+        info.set_layout_symbols(["[M]"]);
+        assert_eq!(info.get_layout_symbols(), vec!["[M]"]);
+    }
+
     #[test]
     fn test_shared_command() {
         let cmd = SharedCommand::view_tag(1 << 2, 0);
@@ -13,7 +13,7 @@ use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 // --- 常量 ---
 const RING_BUFFER_MAGIC: u64 = 0x52494E47_42554646;
-const RING_BUFFER_VERSION: u64 = 8; // 版本号因结构调整而递增
+const RING_BUFFER_VERSION: u64 = 12; // 版本号因结构调整而递增
 const DEFAULT_BUFFER_SIZE: usize = 16;
 const CMD_BUFFER_SIZE: usize = 16;
 const DEFAULT_ADAPTIVE_POLL_SPINS: u32 = 400;
@@ -68,14 +68,21 @@ fn calculate_message_checksum(m: &SharedMessage) -> u32 {
     mix_i32(&mut sum, mi.monitor_height);
     mix_i32(&mut sum, mi.monitor_x);
     mix_i32(&mut sum, mi.monitor_y);
+    sum = sum.wrapping_add(mi.tag_count);
 
-    // tag_status_vec：将 bool 压缩成位
-    for ts in &mi.tag_status_vec {
+    // tag_status_vec：将 bool 压缩成位，按下标错位以区分不同 tag
+    for (i, ts) in mi.tag_status_vec.iter().enumerate() {
         let bits: u8 = (ts.is_selected as u8)
             | ((ts.is_urg as u8) << 1)
             | ((ts.is_filled as u8) << 2)
             | ((ts.is_occ as u8) << 3);
-        sum = sum.wrapping_add(bits as u32);
+        sum = sum.wrapping_add((bits as u32) << (i % 7 * 4));
+    }
+
+    for label in &mi.tag_labels {
+        for &b in label {
+            sum = sum.wrapping_add(b as u32);
+        }
     }
 
     // client_name 和 ltsymbol 数组
@@ -85,6 +92,14 @@ fn calculate_message_checksum(m: &SharedMessage) -> u32 {
     for &b in &mi.ltsymbol {
         sum = sum.wrapping_add(b as u32);
     }
+    for slot in &mi.layout_symbols {
+        for &b in slot {
+            sum = sum.wrapping_add(b as u32);
+        }
+    }
+    for &b in &mi.key_mode {
+        sum = sum.wrapping_add(b as u32);
+    }
 
     sum
 }
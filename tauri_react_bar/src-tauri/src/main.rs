@@ -23,6 +23,7 @@ pub struct MonitorInfoSnapshot {
     pub monitor_x: i32,
     pub monitor_y: i32,
     pub tag_status_vec: Vec<TagStatus>,
+    pub tag_labels: Vec<String>,
     pub client_name: String,
     pub ltsymbol: String,
 }
@@ -35,7 +36,8 @@ impl MonitorInfoSnapshot {
             monitor_height: monitor_info.monitor_height,
             monitor_x: monitor_info.monitor_x,
             monitor_y: monitor_info.monitor_y,
-            tag_status_vec: monitor_info.tag_status_vec.to_vec(),
+            tag_status_vec: monitor_info.tag_statuses().to_vec(),
+            tag_labels: monitor_info.get_tag_labels(),
             client_name: monitor_info.get_client_name(),
             ltsymbol: monitor_info.get_ltsymbol(),
         }
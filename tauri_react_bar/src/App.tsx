@@ -18,6 +18,7 @@ interface MonitorInfoSnapshot {
   monitor_x: number;
   monitor_y: number;
   tag_status_vec: TagStatus[];
+  tag_labels: string[];
   client_name: string;
   ltsymbol: string; // 形如: "[]=" 或 "[]=" + " s: 1.00, m: 0"
 }
@@ -75,7 +76,7 @@ function monitorIcon(num: number) {
 // --- 子组件 ---
 
 const TagButtons = (
-  { tags, monitorNum }: { tags: TagStatus[]; monitorNum: number },
+  { tags, labels, monitorNum }: { tags: TagStatus[]; labels: string[]; monitorNum: number },
 ) => {
   const [pressedButton, setPressedButton] = useState<number | null>(null);
 
@@ -94,7 +95,7 @@ const TagButtons = (
 
   return (
     <>
-      {BUTTONS.map((emoji, i) => {
+      {(labels.length > 0 ? labels : BUTTONS).map((emoji, i) => {
         const tagStatus = tags[i] || {
           is_selected: false,
           is_urg: false,
@@ -298,7 +299,11 @@ function App() {
   return (
     <div className="button-row">
       <div className="buttons-container">
-        <TagButtons tags={mis.tag_status_vec} monitorNum={mis.monitor_num} />
+        <TagButtons
+          tags={mis.tag_status_vec}
+          labels={mis.tag_labels}
+          monitorNum={mis.monitor_num}
+        />
         <LayoutControls ltsymbol={mis.ltsymbol} monitorNum={mis.monitor_num} />
       </div>
 
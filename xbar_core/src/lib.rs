@@ -62,7 +62,8 @@ pub struct Colors {
     pub text: Color,
     pub white: Color,
     pub black: Color,
-    pub tag_colors: [Color; 9],
+    // tag 数量超过颜色数时循环使用
+    pub tag_colors: Vec<Color>,
     pub gray: Color,
     pub red: Color,
     pub green: Color,
@@ -80,7 +81,7 @@ pub fn default_colors() -> Colors {
         text: Color::rgb(255, 255, 255),
         white: Color::rgb(255, 255, 255),
         black: Color::rgb(0, 0, 0),
-        tag_colors: [
+        tag_colors: vec![
             Color::rgb(255, 107, 107), // red
             Color::rgb(78, 205, 196),  // cyan
             Color::rgb(69, 183, 209),  // blue
@@ -155,18 +156,25 @@ impl Default for BarConfig {
 
 // ================= AppState 与业务逻辑 =================
 
+// 尚未收到 jwm 消息时显示的 tag 数量
+const DEFAULT_TAG_COUNT: usize = 9;
+
 pub struct AppState {
     pub shared_buffer: Option<Arc<SharedRingBuffer>>,
     pub monitor_info: Option<MonitorInfo>,
     pub monitor_num: i32,
     pub layout_symbol: String,
+    // jwm 发布的可选布局符号，下标即 SetLayout 的参数
+    pub layout_symbols: Vec<String>,
+    // jwm 当前的按键模式，默认模式为空
+    pub key_mode: String,
 
-    pub tag_rects: [Rect; 9],
+    pub tag_rects: Vec<Rect>,
     pub active_tab: usize,
 
     pub layout_button_rect: Rect,
     pub layout_selector_open: bool,
-    pub layout_option_rects: [Rect; 3],
+    pub layout_option_rects: Vec<Rect>,
 
     pub ss_rect: Rect,
     pub time_rect: Rect,
@@ -204,6 +212,11 @@ pub enum HoverTarget {
     Monitor,
 }
 
+// jwm 尚未发布布局列表前使用的内置布局
+fn default_layout_symbols() -> Vec<String> {
+    vec!["[]=".to_string(), "><>".to_string(), "[M]".to_string()]
+}
+
 impl AppState {
     pub fn new(shared_buffer: Option<Arc<SharedRingBuffer>>) -> Self {
         Self {
@@ -211,12 +224,14 @@ impl AppState {
             monitor_info: None,
             monitor_num: 0,
             layout_symbol: "[]=".to_string(),
-            tag_rects: [Rect::default(); 9],
+            layout_symbols: default_layout_symbols(),
+            key_mode: String::new(),
+            tag_rects: Vec::new(),
             active_tab: 0,
 
             layout_button_rect: Rect::default(),
             layout_selector_open: false,
-            layout_option_rects: [Rect::default(), Rect::default(), Rect::default()],
+            layout_option_rects: Vec::new(),
 
             ss_rect: Rect::default(),
             time_rect: Rect::default(),
@@ -246,8 +261,13 @@ impl AppState {
         self.monitor_info = Some(msg.monitor_info);
         if let Some(mi) = self.monitor_info.as_ref() {
             self.layout_symbol = mi.get_ltsymbol();
+            let symbols = mi.get_layout_symbols();
+            if !symbols.is_empty() {
+                self.layout_symbols = symbols;
+            }
+            self.key_mode = mi.get_key_mode();
             self.monitor_num = mi.monitor_num;
-            for (i, tag) in mi.tag_status_vec.iter().enumerate() {
+            for (i, tag) in mi.tag_statuses().iter().enumerate() {
                 if tag.is_selected {
                     self.active_tab = i;
                 }
@@ -582,9 +602,9 @@ fn tag_visuals(
     mi: Option<&MonitorInfo>,
     idx: usize,
 ) -> (Color, f64, Color, Color, bool) {
-    let tag_color = colors.tag_colors[idx.min(colors.tag_colors.len() - 1)];
+    let tag_color = colors.tag_colors[idx % colors.tag_colors.len()];
     if let Some(monitor) = mi {
-        if let Some(status) = monitor.tag_status_vec.get(idx) {
+        if let Some(status) = monitor.tag_statuses().get(idx) {
             if status.is_urg {
                 return (colors.red, 2.0, colors.red, colors.white, true);
             } else if status.is_selected {
@@ -616,11 +636,19 @@ pub fn draw_bar(
 
     let pill_h = (height as f64) - 2.0 * cfg.padding_y;
 
-    // 左侧 tags
-    let tags = ["1", "2", "3", "4", "5", "6", "7", "8", "9"];
+    // 左侧 tags：数量和标签跟随 jwm 配置，未收到消息前显示默认的 9 个
+    let tag_count = state
+        .monitor_info
+        .as_ref()
+        .map_or(DEFAULT_TAG_COUNT, |mi| mi.tag_count());
+    state.tag_rects.resize(tag_count, Rect::default());
     let mut x = cfg.padding_x;
-    for (i, label) in tags.iter().enumerate() {
-        let (tw, _th) = pango_text_size(cr, font, label);
+    for i in 0..tag_count {
+        let label = state
+            .monitor_info
+            .as_ref()
+            .map_or_else(|| (i + 1).to_string(), |mi| mi.get_tag_label(i));
+        let (tw, _th) = pango_text_size(cr, font, &label);
         let w = ((tw as f64) + 2.0 * cfg.pill_hpadding).max(40.0);
 
         let (mut bg, mut bw, mut bc, txt_color, draw_bg) =
@@ -646,7 +674,7 @@ pub fn draw_bar(
                 bc,
                 Some(bg),
             )?;
-            pango_draw_text_centered(cr, font, txt_color, x, cfg.padding_y, w, pill_h, label);
+            pango_draw_text_centered(cr, font, txt_color, x, cfg.padding_y, w, pill_h, &label);
         }
         state.tag_rects[i] = Rect {
             x: x as i16,
@@ -701,13 +729,20 @@ pub fn draw_bar(
 
     // 布局选项
     if state.layout_selector_open {
-        let layouts: [(&str, u32, Color); 3] = [
-            ("[]=", 0, colors.green),
-            ("><>", 1, colors.blue),
-            ("[M]", 2, colors.purple),
+        let palette = [
+            colors.green,
+            colors.blue,
+            colors.purple,
+            colors.teal,
+            colors.orange,
+            colors.yellow,
         ];
+        state
+            .layout_option_rects
+            .resize(state.layout_symbols.len(), Rect::default());
         let mut opt_x = x;
-        for (i, (sym, _idx, base_color)) in layouts.iter().enumerate() {
+        for (i, sym) in state.layout_symbols.iter().enumerate() {
+            let base_color = &palette[i % palette.len()];
             let (tw, _th) = pango_text_size(cr, font, sym);
             let w = ((tw as f64) + 2.0 * (cfg.pill_hpadding - 2.0)).max(32.0);
 
@@ -740,8 +775,37 @@ pub fn draw_bar(
             };
             opt_x += w + cfg.tag_spacing;
         }
+        x = opt_x;
     } else {
-        state.layout_option_rects = [Rect::default(), Rect::default(), Rect::default()];
+        state.layout_option_rects.clear();
+    }
+
+    // 按键模式提示（非默认模式时显示）
+    if !state.key_mode.is_empty() {
+        let (kw, _kh) = pango_text_size(cr, font, &state.key_mode);
+        let kw_total = kw as f64 + 2.0 * cfg.pill_hpadding;
+        stroke_shape_with_fill(
+            cr,
+            state.shape_style,
+            x,
+            cfg.padding_y,
+            kw_total,
+            pill_h,
+            cfg.pill_radius,
+            2.0,
+            colors.orange,
+            Some(colors.orange),
+        )?;
+        pango_draw_text_centered(
+            cr,
+            font,
+            colors.black,
+            x,
+            cfg.padding_y,
+            kw_total,
+            pill_h,
+            &state.key_mode,
+        );
     }
 
     // 右侧从右往左